@@ -0,0 +1,125 @@
+//! Compares the arena of `Samples` with steps which allocate their quantities on their own.
+//!
+//! Run with `cargo +nightly bench -p euleretal-core`.  Since timings vary a lot between machines,
+//! `allocations()` additionally checks the number of allocations, which is deterministic.
+
+#![feature(test)]
+
+extern crate test;
+
+use ::euleretal_core::{
+    integration_step::builders, integrators::mid_point, scenarios::CenterMass, AccelerationField,
+    Duration, Integrator, Position, Samples, StartCondition, Step, Velocity,
+};
+use ::std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+use ::test::{black_box, Bencher};
+
+const NUM_STEPS: usize = 1_000;
+
+/// Counts the allocations of the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn start_condition() -> StartCondition {
+    let position = Position::new(0., 1., 0.);
+    StartCondition::new(
+        position,
+        Velocity::new(1., 0., 0.),
+        CenterMass.value_at(position),
+    )
+}
+
+fn dt() -> Duration {
+    0.01.into()
+}
+
+fn samples_in_one_arena() -> Samples {
+    let mut samples = Samples::new(NUM_STEPS);
+    let mut start_condition = start_condition();
+    for _ in 0..NUM_STEPS {
+        let mut step = samples.push_step(&start_condition, dt());
+        let mut builder = builders::Step::new(&CenterMass, &mut step);
+        let ((s, v, a), dt) = (builder.start_values(), builder.dt());
+        mid_point::SecondOrder.integrate_step(s, v, a, dt, &mut builder);
+        builder.finalize();
+        start_condition = step.next_condition();
+    }
+    samples.finalized()
+}
+
+fn steps_with_own_storage() -> Vec<Step<'static>> {
+    let mut steps = Vec::with_capacity(NUM_STEPS);
+    let mut step = Step::new(&start_condition(), dt());
+    for _ in 0..NUM_STEPS {
+        let mut builder = builders::Step::new(&CenterMass, &mut step);
+        let ((s, v, a), dt) = (builder.start_values(), builder.dt());
+        mid_point::SecondOrder.integrate_step(s, v, a, dt, &mut builder);
+        builder.finalize();
+        let next_step = step.create_next();
+        steps.push(::std::mem::replace(&mut step, next_step));
+    }
+    steps
+}
+
+fn allocations_of<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    black_box(f());
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn allocations() {
+    let in_arena = allocations_of(samples_in_one_arena);
+    let own_storage = allocations_of(steps_with_own_storage);
+    assert!(
+        in_arena < NUM_STEPS / 10,
+        "{} allocations for {} steps in one arena",
+        in_arena,
+        NUM_STEPS
+    );
+    assert!(
+        own_storage > NUM_STEPS,
+        "{} allocations for {} steps with storage per step",
+        own_storage,
+        NUM_STEPS
+    );
+}
+
+#[bench]
+fn bench_samples_in_one_arena(bencher: &mut Bencher) {
+    bencher.iter(samples_in_one_arena);
+}
+
+#[bench]
+fn bench_steps_with_own_storage(bencher: &mut Bencher) {
+    bencher.iter(steps_with_own_storage);
+}
@@ -1,11 +1,60 @@
-use super::{Acceleration, Position};
+use super::{Acceleration, Duration, Position, Velocity};
 use ::std::{any::TypeId, collections::hash_map::DefaultHasher, hash::Hash};
 
-pub trait AccelerationField: Send + Sync + 'static {
+/// Where integration steps take the acceleration from: any [`AccelerationField`], but also a body
+/// which is attracted by further bodies (see [`crate::bodies::BodyField`]).
+pub trait AccelerationSource {
+    /// The acceleration at `pos`, for the `sample`-th acceleration of a step.  The samples of a
+    /// step are numbered from 1 on, like the accelerations of its `symbolic::Trace` (0 is the
+    /// start acceleration).  Acceleration fields ignore the number, but the other bodies of a
+    /// system move during the step.
+    fn acceleration_at(&self, pos: Position, sample: usize) -> Acceleration;
+}
+
+pub trait AccelerationField: AccelerationSource + Send + Sync + 'static {
     fn value_at(&self, pos: Position) -> Acceleration;
 
+    /// Sets `accelerations[i]` to the value at `positions[i]`.  Evaluating many positions at once
+    /// saves the dynamic dispatch per position, and lets implementations vectorise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slices differ in length.
+    fn values_at(&self, positions: &[Position], accelerations: &mut [Acceleration]) {
+        assert_eq!(positions.len(), accelerations.len());
+        for (a, &pos) in accelerations.iter_mut().zip(positions) {
+            *a = self.value_at(pos);
+        }
+    }
+
+    /// The potential energy per unit mass at `pos`, if the field has a potential.  Used to
+    /// detect integrations whose energy blows up.
+    fn potential_at(&self, _pos: Position) -> Option<f32> {
+        None
+    }
+
+    /// The position and velocity of a particle which starts at `start` and moves in this field
+    /// for the time `t`, if the motion is known in closed form.  Then the reference solution is
+    /// exact instead of being integrated.
+    fn exact_motion(
+        &self,
+        _start: (Position, Velocity),
+        _t: Duration,
+    ) -> Option<(Position, Velocity)> {
+        None
+    }
+
     fn label(&self) -> String;
 
+    /// Checks if the parameters of the field are well-defined.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first invalid parameter.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
     fn hash(&self, state: &mut DefaultHasher) {
         TypeId::of::<Self>().hash(state);
     }
@@ -14,3 +63,78 @@ pub trait AccelerationField: Send + Sync + 'static {
         &self,
     ) -> crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe;
 }
+
+impl<T: AccelerationField + ?Sized> AccelerationSource for T {
+    fn acceleration_at(&self, pos: Position, _sample: usize) -> Acceleration {
+        self.value_at(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AccelerationField;
+    use crate::{
+        scenarios::{
+            serde_box_dyn_acceleration_field::AccelerationFieldSerDe, CenterMass, DoubleWell,
+            HarmonicOscillator, PointMass, QuarticOscillator,
+        },
+        Position,
+    };
+
+    #[allow(clippy::cast_precision_loss)]
+    fn positions() -> Vec<Position> {
+        (0..37)
+            .map(|i| {
+                Position::new(
+                    i as f32 * 0.3 - 5.,
+                    2. - i as f32 * 0.1,
+                    0.7 - i as f32 * 0.05,
+                )
+            })
+            .collect()
+    }
+
+    /// Calls the override of `values_at()` of `F` directly.
+    fn assert_override_agrees<F: AccelerationField>(field: &F) {
+        let positions = positions();
+        let mut accelerations = vec![field.value_at(Position::origin()); positions.len()];
+        field.values_at(&positions, &mut accelerations);
+        for (a, &pos) in accelerations.iter().zip(&positions) {
+            assert_eq!(*a, field.value_at(pos), "{}", field.label());
+        }
+    }
+
+    #[test]
+    fn overrides_agree_with_single_values() {
+        assert_override_agrees(&CenterMass);
+        assert_override_agrees(&PointMass {
+            mass: 2.5,
+            softening: 0.3,
+        });
+        assert_override_agrees(&HarmonicOscillator {
+            omega_x: 0.5,
+            omega_y: 3.,
+        });
+        assert_override_agrees(&QuarticOscillator {
+            stiffness: 0.5,
+            anharmonicity: 2.,
+        });
+        assert_override_agrees(&DoubleWell {
+            separation: 1.5,
+            barrier: 2.,
+        });
+    }
+
+    #[test]
+    fn batch_agrees_with_single_values() {
+        let positions = positions();
+        for field in AccelerationFieldSerDe::variants() {
+            let field = field.into_box();
+            let mut accelerations = vec![field.value_at(Position::origin()); positions.len()];
+            field.values_at(&positions, &mut accelerations);
+            for (a, &pos) in accelerations.iter().zip(&positions) {
+                assert_eq!(*a, field.value_at(pos), "{}", field.label());
+            }
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! Bodies which attract each other by gravity, in addition to the acceleration field of their
+//! scenario.
+//!
+//! The particle of a scenario and its further bodies form a system of N bodies.  Its state is the
+//! vector of the start conditions of all bodies, the particle first.  As the acceleration of each
+//! body depends on the positions of all others, the bodies are integrated in lock-step: the
+//! [`Trace`] of an integrator is evaluated for the whole state vector, which yields the
+//! intermediate positions of all bodies at each sample of the acceleration.  The step of each body
+//! is then computed by the integrator itself, in the [`BodyField`] of the other bodies at their
+//! intermediate positions.
+
+use super::{
+    import::{OrderedF32, Point3, Vec3},
+    integration_step::symbolic::{Expression, Symbol, Trace},
+    Acceleration, AccelerationField, AccelerationSource, Duration, Integrator, Position,
+    StartCondition, Velocity,
+};
+use ::std::hash::{Hash, Hasher};
+
+/// A body besides the particle of a scenario
+#[derive(Clone, Copy, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+pub struct Body {
+    pub mass: f32,
+    pub position: Position,
+    pub velocity: Velocity,
+}
+
+/// The acceleration field of a scenario together with the mutual attraction of its bodies
+pub struct System<'a> {
+    acceleration_field: &'a dyn AccelerationField,
+    /// Bodies without mass are attracted by the others, but do not attract them.
+    masses: Vec<f32>,
+}
+
+/// Evaluates the steps of an integrator for all bodies of a [`System`] at once
+pub struct LockStep {
+    trace: Trace,
+}
+
+/// Where the bodies are whenever an integrator samples the acceleration during a step of all
+/// bodies, see [`LockStep::evaluate()`]
+pub struct Evaluation {
+    /// `positions[n][body]` for the `n`-th sample of the step, like `Symbol::Acceleration(n)`.
+    /// The last sample is taken at the resulting positions.
+    positions: Vec<Vec<Position>>,
+}
+
+/// The acceleration of one body of a [`System`] during a step: the acceleration field plus the
+/// attraction of the other bodies at their positions of the same sample
+pub struct BodyField<'a> {
+    system: &'a System<'a>,
+    body: usize,
+    evaluation: &'a Evaluation,
+}
+
+impl Body {
+    #[must_use]
+    pub fn new(mass: f32, position: Position, velocity: Velocity) -> Self {
+        Self {
+            mass,
+            position,
+            velocity,
+        }
+    }
+
+    /// Checks if the body is well-defined.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first invalid attribute.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.mass.is_finite() || self.mass < 0. {
+            return Err(format!(
+                "Mass of a body must not be negative (is {}).",
+                self.mass
+            ));
+        }
+        if !self.position.as_vector().iter().all(|c| c.is_finite()) {
+            return Err("Position of a body must be finite.".to_string());
+        }
+        if !self.velocity.as_vector().iter().all(|c| c.is_finite()) {
+            return Err("Velocity of a body must be finite.".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Hash for Body {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        OrderedF32::from(self.mass).hash(state);
+        self.position.hash(state);
+        self.velocity.hash(state);
+    }
+}
+
+impl<'a> System<'a> {
+    #[must_use]
+    pub fn new(acceleration_field: &'a dyn AccelerationField, masses: Vec<f32>) -> Self {
+        Self {
+            acceleration_field,
+            masses,
+        }
+    }
+
+    #[must_use]
+    pub fn num_bodies(&self) -> usize {
+        self.masses.len()
+    }
+
+    #[must_use]
+    pub fn acceleration_field(&self) -> &'a dyn AccelerationField {
+        self.acceleration_field
+    }
+
+    /// Sets `accelerations[i]` to the acceleration of the body at `positions[i]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slices do not have one element per body.
+    pub fn accelerations(&self, positions: &[Position], accelerations: &mut [Acceleration]) {
+        assert_eq!(positions.len(), self.num_bodies());
+        self.acceleration_field.values_at(positions, accelerations);
+        for i in 0..positions.len() {
+            for j in i + 1..positions.len() {
+                let (m_i, m_j) = (self.masses[i], self.masses[j]);
+                if m_i == 0. && m_j == 0. {
+                    continue;
+                }
+                let attraction = attraction(positions[i], positions[j]);
+                accelerations[i] += (m_j * attraction).into();
+                accelerations[j] -= (m_i * attraction).into();
+            }
+        }
+    }
+
+    /// The acceleration of the body `idx` at `s`, while the other bodies are at `positions`
+    #[must_use]
+    pub fn acceleration_of(&self, idx: usize, s: Position, positions: &[Position]) -> Acceleration {
+        let mut acceleration = self.acceleration_field.value_at(s);
+        for (j, (&mass, &position)) in self.masses.iter().zip(positions).enumerate() {
+            if j != idx && mass != 0. {
+                acceleration += (mass * attraction(s, position)).into();
+            }
+        }
+        acceleration
+    }
+
+    /// The start conditions of bodies which start at `(position, velocity)`
+    #[must_use]
+    pub fn start_conditions(&self, starts: &[(Position, Velocity)]) -> Vec<StartCondition> {
+        let positions = starts.iter().map(|&(s, _)| s).collect::<Vec<_>>();
+        let mut accelerations = vec![Acceleration::zeros(); starts.len()];
+        self.accelerations(&positions, &mut accelerations);
+        starts
+            .iter()
+            .zip(accelerations)
+            .map(|(&(s, v), a)| StartCondition::new(s, v, a))
+            .collect()
+    }
+
+    /// The total energy of the bodies in `states`, or `None` if the acceleration field has no
+    /// potential.
+    #[must_use]
+    pub fn energy(&self, states: &[StartCondition]) -> Option<f32> {
+        let mut energy = 0.;
+        for (i, (state, &m_i)) in states.iter().zip(&self.masses).enumerate() {
+            let potential = self.acceleration_field.potential_at(state.position())?;
+            energy += m_i * (0.5 * state.velocity().as_vector().norm_squared() + potential);
+            for (other, &m_j) in states.iter().zip(&self.masses).skip(i + 1) {
+                let distance = state.position().distance_squared(other.position()).sqrt();
+                energy -= m_i * m_j / distance;
+            }
+        }
+        Some(energy)
+    }
+
+    /// The sum of the absolute kinetic and potential energies of the bodies in `states`, as a
+    /// scale for the drift of their `energy()`
+    #[must_use]
+    pub fn energy_scale(&self, states: &[StartCondition]) -> f32 {
+        let mut scale = 0.;
+        for (i, (state, &m_i)) in states.iter().zip(&self.masses).enumerate() {
+            let potential = self
+                .acceleration_field
+                .potential_at(state.position())
+                .unwrap_or(0.);
+            scale += m_i * (0.5 * state.velocity().as_vector().norm_squared() + potential.abs());
+            for (other, &m_j) in states.iter().zip(&self.masses).skip(i + 1) {
+                let distance = state.position().distance_squared(other.position()).sqrt();
+                scale += m_i * m_j / distance;
+            }
+        }
+        scale
+    }
+}
+
+/// The acceleration of a body at `from` towards a unit mass at `to`, which is infinite or NaN if
+/// the positions coincide
+fn attraction(from: Position, to: Position) -> Vec3 {
+    let direction = from.vector_to(to);
+    let distance_squared_recip = direction.norm_squared().recip();
+    direction * distance_squared_recip.sqrt() * distance_squared_recip
+}
+
+impl LockStep {
+    #[must_use]
+    pub fn new(integrator: &dyn Integrator) -> Self {
+        Self {
+            trace: Trace::of(integrator),
+        }
+    }
+
+    /// The positions of all bodies at which the integrator samples the acceleration during a step
+    /// of duration `dt` from the state vector `starts`.  Like `builders::Step::finalize()`, the
+    /// last sample is taken at the resulting positions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `starts` does not contain one start condition per body of `system`.
+    #[must_use]
+    pub fn evaluate(&self, system: &System, starts: &[StartCondition], dt: Duration) -> Evaluation {
+        assert_eq!(starts.len(), system.num_bodies());
+        let num_bodies = starts.len();
+        let mut values = Values::default();
+        values.set(
+            Symbol::Position(0),
+            starts.iter().map(|start| start.position().into()).collect(),
+        );
+        values.set(
+            Symbol::Velocity(0),
+            starts.iter().map(|start| start.velocity().into()).collect(),
+        );
+        values.set(
+            Symbol::Acceleration(0),
+            starts
+                .iter()
+                .map(|start| start.acceleration().into())
+                .collect(),
+        );
+        let mut sampled = vec![starts.iter().map(StartCondition::position).collect()];
+        let positions_of = |values: &Values, position: Symbol| {
+            values
+                .get(position)
+                .iter()
+                .map(|&s| Point3::from(s).into())
+                .collect::<Vec<Position>>()
+        };
+        let mut sample = |positions: Vec<Position>| {
+            let mut accelerations = vec![Acceleration::zeros(); num_bodies];
+            system.accelerations(&positions, &mut accelerations);
+            sampled.push(positions);
+            accelerations
+                .into_iter()
+                .map(Vec3::from)
+                .collect::<Vec<_>>()
+        };
+        for assignment in &self.trace.assignments {
+            let result = match &assignment.expression {
+                Expression::AccelerationAt(position) => sample(positions_of(&values, *position)),
+                Expression::Sum(terms) => (0..num_bodies)
+                    .map(|body| {
+                        terms.iter().fold(Vec3::zeros(), |sum, term| {
+                            let dt = f32::from(term.dt_fraction * dt);
+                            sum + term.factor
+                                * values.get(term.symbol)[body]
+                                * dt.powi(i32::from(term.dt_power))
+                        })
+                    })
+                    .collect(),
+            };
+            values.set(assignment.target, result);
+        }
+        sample(positions_of(&values, self.trace.result_position()));
+        Evaluation { positions: sampled }
+    }
+}
+
+impl Evaluation {
+    /// The resulting positions of the bodies (before bouncing off obstacles)
+    #[must_use]
+    pub fn end_positions(&self) -> &[Position] {
+        self.positions.last().map_or(&[], Vec::as_slice)
+    }
+
+    #[must_use]
+    pub fn body_field<'a>(&'a self, system: &'a System<'a>, body: usize) -> BodyField<'a> {
+        BodyField {
+            system,
+            body,
+            evaluation: self,
+        }
+    }
+}
+
+/// The values of the symbols of a `Trace` for all bodies, e.g. `positions[n][body]` for
+/// `Symbol::Position(n)`
+#[derive(Default)]
+struct Values {
+    positions: Vec<Vec<Vec3>>,
+    velocities: Vec<Vec<Vec3>>,
+    accelerations: Vec<Vec<Vec3>>,
+}
+
+impl Values {
+    fn get(&self, symbol: Symbol) -> &[Vec3] {
+        match symbol {
+            Symbol::Position(n) => &self.positions[n],
+            Symbol::Velocity(n) => &self.velocities[n],
+            Symbol::Acceleration(n) => &self.accelerations[n],
+        }
+    }
+
+    fn set(&mut self, symbol: Symbol, values: Vec<Vec3>) {
+        let (all, n) = match symbol {
+            Symbol::Position(n) => (&mut self.positions, n),
+            Symbol::Velocity(n) => (&mut self.velocities, n),
+            Symbol::Acceleration(n) => (&mut self.accelerations, n),
+        };
+        if all.len() <= n {
+            all.resize(n + 1, Vec::new());
+        }
+        all[n] = values;
+    }
+}
+
+impl AccelerationSource for BodyField<'_> {
+    /// # Panics
+    ///
+    /// Panics if the integrator takes more samples than during the evaluation.
+    fn acceleration_at(&self, pos: Position, sample: usize) -> Acceleration {
+        self.system
+            .acceleration_of(self.body, pos, &self.evaluation.positions[sample])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Body, LockStep, System};
+    use crate::{
+        integration_step::builders,
+        integrators::mid_point,
+        scenarios::{CenterMass, FreeSpace},
+        Acceleration, AccelerationSource, Integrator, Position, StartCondition, Step, Velocity,
+    };
+
+    #[test]
+    fn pairwise_attraction() {
+        let system = System::new(&FreeSpace, vec![1., 2., 0.]);
+        let positions = [
+            Position::new(0., 0., 0.),
+            Position::new(2., 0., 0.),
+            Position::new(0., 1., 0.),
+        ];
+        let mut accelerations = [Acceleration::zeros(); 3];
+        system.accelerations(&positions, &mut accelerations);
+        assert_eq!(accelerations[0], Acceleration::new(0.5, 0., 0.));
+        assert_eq!(accelerations[1], Acceleration::new(-0.25, 0., 0.));
+        // without mass, the third body does not attract the others:
+        let momentum = accelerations[0] + 2. * accelerations[1];
+        assert!(momentum.as_vector().norm() < 1e-6);
+        assert_eq!(
+            system.acceleration_of(2, positions[2], &positions),
+            accelerations[2]
+        );
+        let body = Body::new(-1., Position::origin(), Velocity::zeros());
+        assert!(body.validate().is_err());
+    }
+
+    #[test]
+    fn lock_step_agrees_with_single_body() {
+        let system = System::new(&CenterMass, vec![1.]);
+        let start =
+            system.start_conditions(&[(Position::new(0., 1., 0.), Velocity::new(1., 0., 0.))]);
+        let integrate = |acceleration_field: &dyn AccelerationSource, start: &StartCondition| {
+            let mut step = Step::new(start, 0.3.into());
+            let mut builder = builders::Step::new(acceleration_field, &mut step);
+            let ((s, v, a), dt) = (builder.start_values(), builder.dt());
+            mid_point::SecondOrder.integrate_step(s, v, a, dt, &mut builder);
+            builder.finalize();
+            step.next_condition()
+        };
+        let evaluation =
+            LockStep::new(&mid_point::SecondOrder).evaluate(&system, &start, 0.3.into());
+        let in_lock_step = integrate(&evaluation.body_field(&system, 0), &start[0]);
+        let direct = integrate(&CenterMass, &start[0]);
+        assert_eq!(in_lock_step, direct);
+        assert_eq!(evaluation.end_positions(), &[direct.position()]);
+    }
+
+    #[test]
+    fn body_field_depends_on_position_and_sample_only() {
+        let system = System::new(&FreeSpace, vec![1., 1.]);
+        let start = system.start_conditions(&[
+            (Position::new(-1., 0., 0.), Velocity::new(0., 0.5, 0.)),
+            (Position::new(1., 0., 0.), Velocity::new(0., -0.5, 0.)),
+        ]);
+        let evaluation =
+            LockStep::new(&mid_point::SecondOrder).evaluate(&system, &start, 0.3.into());
+        let field = evaluation.body_field(&system, 0);
+        let pos = Position::new(-0.5, 0.2, 0.);
+        // the other body moves during the step:
+        let (mid, end) = (field.acceleration_at(pos, 1), field.acceleration_at(pos, 2));
+        assert_ne!(mid, end);
+        // regardless of the order of the requests:
+        assert_eq!(field.acceleration_at(pos, 2), end);
+        assert_eq!(field.acceleration_at(pos, 1), mid);
+        assert_eq!(
+            end,
+            system.acceleration_of(0, pos, evaluation.end_positions())
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! A vector for data which grows by appending, e.g. when a scenario is integrated further.
+//!
+//! The elements are stored in chunks of fixed size which are shared between clones.  Thus a clone
+//! can be continued without copying more than its last chunk.
+
+use ::std::sync::Arc;
+
+/// The number of elements per chunk
+const CHUNK_LEN: usize = 1024;
+
+pub struct ChunkedVec<T> {
+    /// All chunks but the last one are full, and the last one is not empty.
+    chunks: Vec<Arc<Vec<T>>>,
+}
+
+impl<T: Clone> ChunkedVec<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { chunks: Vec::new() }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.chunks
+            .last()
+            .map_or(0, |last| (self.chunks.len() - 1) * CHUNK_LEN + last.len())
+    }
+
+    #[must_use]
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        self.chunks.get(idx / CHUNK_LEN)?.get(idx % CHUNK_LEN)
+    }
+
+    #[must_use]
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    #[must_use]
+    pub fn last(&self) -> Option<&T> {
+        self.chunks.last()?.last()
+    }
+
+    /// Copies the last chunk if it is shared with a clone.
+    pub fn last_mut(&mut self) -> Option<&mut T> {
+        Arc::make_mut(self.chunks.last_mut()?).last_mut()
+    }
+
+    /// Copies the last chunk if it is shared with a clone (and not full).
+    pub fn push(&mut self, value: T) {
+        match self.chunks.last_mut() {
+            Some(last) if last.len() < CHUNK_LEN => Arc::make_mut(last).push(value),
+            _ => {
+                let mut chunk = Vec::with_capacity(CHUNK_LEN);
+                chunk.push(value);
+                self.chunks.push(Arc::new(chunk));
+            }
+        }
+    }
+
+    /// Like `slice::partition_point()`: The index of the first element for which `pred` is
+    /// `false`, provided that it is `true` for all elements before it and `false` for all after.
+    pub fn partition_point(&self, pred: impl Fn(&T) -> bool) -> usize {
+        let chunk_idx = self
+            .chunks
+            .partition_point(|chunk| chunk.last().is_some_and(&pred));
+        self.chunks.get(chunk_idx).map_or(self.len(), |chunk| {
+            chunk_idx * CHUNK_LEN + chunk.partition_point(&pred)
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.chunks.iter().flat_map(|chunk| chunk.iter())
+    }
+}
+
+impl<T> Clone for ChunkedVec<T> {
+    /// Shares all chunks.
+    fn clone(&self) -> Self {
+        Self {
+            chunks: self.chunks.clone(),
+        }
+    }
+}
+
+impl<T: Clone> Default for ChunkedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> ::std::ops::Index<usize> for ChunkedVec<T> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &T {
+        &self.chunks[idx / CHUNK_LEN][idx % CHUNK_LEN]
+    }
+}
+
+impl<T: Clone> ::std::ops::IndexMut<usize> for ChunkedVec<T> {
+    /// Copies the chunk of `idx` if it is shared with a clone.
+    fn index_mut(&mut self, idx: usize) -> &mut T {
+        &mut Arc::make_mut(&mut self.chunks[idx / CHUNK_LEN])[idx % CHUNK_LEN]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkedVec, CHUNK_LEN};
+    use ::std::sync::Arc;
+
+    #[test]
+    fn clones_share_full_chunks() {
+        let mut original = ChunkedVec::new();
+        for i in 0..2 * CHUNK_LEN + 3 {
+            original.push(i);
+        }
+        let mut continued = original.clone();
+        continued.push(0);
+        continued[1] = 42;
+        assert!(Arc::ptr_eq(&original.chunks[1], &continued.chunks[1]));
+        assert!(!Arc::ptr_eq(&original.chunks[0], &continued.chunks[0]));
+        assert_eq!(
+            (original.len(), continued.len()),
+            (2 * CHUNK_LEN + 3, 2 * CHUNK_LEN + 4)
+        );
+        assert_eq!((original[1], continued[1]), (1, 42));
+        assert!(original.iter().copied().eq(0..2 * CHUNK_LEN + 3));
+        assert_eq!(original.partition_point(|&i| i < 1500), 1500);
+        assert_eq!(original.get(CHUNK_LEN), Some(&CHUNK_LEN));
+    }
+}
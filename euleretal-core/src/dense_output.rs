@@ -0,0 +1,743 @@
+//! A continuous extension of the reference solution of a scenario.
+//!
+//! The reference solution is computed with a small, fixed step size `h`.  In between the
+//! resulting nodes, positions are interpolated by cubic Hermite polynomials of the positions and
+//! velocities of the two neighbouring nodes, and velocities by cubic Hermite polynomials of their
+//! velocities and accelerations.  Thus the reference can be queried at any time, e.g. at the
+//! intermediate times `dt_fraction * dt` at which integrators sample the acceleration field.
+//!
+//! If a step crosses the surface of an obstacle, the time of the collision is located by
+//! bisection on the interpolation of the step, and the rest of the step is integrated again with
+//! the reflected velocity.  The states right before and after the bounce are kept, so that the
+//! step can be interpolated piecewise.  At most one bounce per step is considered.
+//!
+//! Several bodies which attract each other are integrated in lock-step, with one dense output per
+//! body.
+//!
+//! If the motion of a single particle without obstacles is known in closed form (see
+//! [`AccelerationField::exact_motion()`]), the nodes are taken from it, and so is the reference
+//! solution in between.
+
+use super::{
+    bodies::System,
+    chunked_vec::ChunkedVec,
+    import::{Point3, Vec3},
+    job::Progress,
+    obstacle::{Collision, Obstacle},
+    samples::NonFinalized,
+    Acceleration, AccelerationField, Duration, Position, Samples, StartCondition, Velocity,
+};
+use ::std::sync::Arc;
+
+/// The reference solution at some point in time.
+#[derive(Clone, Copy, Debug)]
+pub struct State {
+    pub s: Position,
+    pub v: Velocity,
+    pub a: Acceleration,
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+    s: Vec3,
+    v: Vec3,
+    a: Vec3,
+}
+
+/// A collision within the step which starts at the node `index`
+#[derive(Clone, Copy)]
+struct Bounce {
+    index: usize,
+    /// The time between the node `index` and the collision
+    tau: f32,
+    before: Node,
+    after: Node,
+}
+
+/// The motion of a particle in a field whose `exact_motion()` is known
+#[derive(Clone)]
+struct Exact {
+    field: Arc<dyn AccelerationField>,
+    start: (Position, Velocity),
+}
+
+#[derive(Default)]
+pub struct DenseOutput {
+    /// The duration between two nodes
+    h: f32,
+    /// shared with the extensions of the dense output
+    nodes: ChunkedVec<Node>,
+    /// ordered by `index`
+    bounces: ChunkedVec<Bounce>,
+    exact: Option<Exact>,
+}
+
+impl DenseOutput {
+    /// Integrates the motion of a single particle in `acceleration_field` over `duration` in
+    /// `num_steps` steps.  Returns `None` if cancelled via `progress`.
+    #[cfg(test)]
+    pub(crate) fn integrate(
+        acceleration_field: &dyn crate::AccelerationField,
+        obstacles: &[Obstacle],
+        start_position: Position,
+        start_velocity: Velocity,
+        duration: Duration,
+        num_steps: usize,
+        progress: &Progress,
+    ) -> Option<Self> {
+        Self::integrate_ensemble(
+            &System::new(acceleration_field, vec![0.]),
+            obstacles,
+            &[(start_position, start_velocity)],
+            duration,
+            num_steps,
+            progress,
+        )?
+        .pop()
+    }
+
+    /// Integrates the motions of the bodies of `system`, each starting at `(position, velocity)`,
+    /// in lock-step, so that the accelerations are evaluated for all of them at once.  Returns
+    /// `None` if cancelled via `progress`.
+    pub(crate) fn integrate_ensemble(
+        system: &System,
+        obstacles: &[Obstacle],
+        starts: &[(Position, Velocity)],
+        duration: Duration,
+        num_steps: usize,
+        progress: &Progress,
+    ) -> Option<Vec<Self>> {
+        #![allow(clippy::cast_precision_loss)]
+
+        let num_steps = num_steps.max(1);
+        let h = f32::from(duration) / num_steps as f32;
+        let mut ensemble = system
+            .start_conditions(starts)
+            .iter()
+            .map(|start| {
+                let mut nodes = ChunkedVec::new();
+                nodes.push(Node {
+                    s: start.position().into(),
+                    v: start.velocity().into(),
+                    a: start.acceleration().into(),
+                });
+                Self {
+                    h,
+                    nodes,
+                    bounces: ChunkedVec::new(),
+                    exact: None,
+                }
+            })
+            .collect::<Vec<_>>();
+        if let ([dense], [start], true) = (&mut ensemble[..], starts, obstacles.is_empty()) {
+            dense.exact = Exact::of(system.acceleration_field(), *start);
+        }
+        Self::append(&mut ensemble, system, obstacles, num_steps, progress)?;
+        Some(ensemble)
+    }
+
+    /// Continues the lock-step integration of the bodies of `system` with the same step size
+    /// until `duration` is covered.  The nodes of `ensemble` are shared rather than copied.
+    /// Returns `None` if cancelled via `progress`.
+    pub(crate) fn extended_ensemble(
+        ensemble: &[&Self],
+        system: &System,
+        obstacles: &[Obstacle],
+        duration: Duration,
+        progress: &Progress,
+    ) -> Option<Vec<Self>> {
+        #![allow(clippy::cast_possible_truncation)]
+        #![allow(clippy::cast_sign_loss)]
+
+        let first = ensemble.first()?;
+        let missing_steps = ((f32::from(duration) - f32::from(first.duration())) / first.h)
+            .ceil()
+            .max(0.) as usize;
+        let mut result = ensemble
+            .iter()
+            .map(|dense| Self {
+                h: dense.h,
+                nodes: dense.nodes.clone(),
+                bounces: dense.bounces.clone(),
+                exact: dense.exact.clone(),
+            })
+            .collect::<Vec<_>>();
+        Self::append(&mut result, system, obstacles, missing_steps, progress)?;
+        Some(result)
+    }
+
+    /// Appends `num_steps` nodes to the last one of each member of `ensemble`.  Returns `None`
+    /// if cancelled via `progress`.
+    fn append(
+        ensemble: &mut [Self],
+        system: &System,
+        obstacles: &[Obstacle],
+        num_steps: usize,
+        progress: &Progress,
+    ) -> Option<()> {
+        if let [Self {
+            h,
+            nodes,
+            exact: Some(exact),
+            ..
+        }] = ensemble
+        {
+            return exact.append(nodes, *h, num_steps, progress);
+        }
+        let mut positions = vec![Position::origin(); ensemble.len()];
+        let mut accelerations = vec![Acceleration::new(0., 0., 0.); ensemble.len()];
+
+        progress.expect(num_steps);
+        for _ in 0..num_steps {
+            for (s1_tmp, dense) in positions.iter_mut().zip(ensemble.iter()) {
+                let (n0, h) = (dense.nodes.last()?, dense.h);
+                *s1_tmp = Point3::from(n0.predicted_position(h)).into();
+            }
+            system.accelerations(&positions, &mut accelerations);
+            for ((s1, &a1), dense) in positions
+                .iter_mut()
+                .zip(&accelerations)
+                .zip(ensemble.iter_mut())
+            {
+                let (n0, h) = (*dense.nodes.last()?, dense.h);
+                let node = n0.corrected(a1.into(), h);
+                dense.nodes.push(node);
+                *s1 = Point3::from(node.s).into();
+            }
+            system.accelerations(&positions, &mut accelerations);
+            for (&a, dense) in accelerations.iter().zip(ensemble.iter_mut()) {
+                if let Some(node) = dense.nodes.last_mut() {
+                    node.a = a.into();
+                }
+            }
+            if !obstacles.is_empty() {
+                for (idx, dense) in ensemble.iter_mut().enumerate() {
+                    dense.bounce_off(|s| system.acceleration_of(idx, s, &positions), obstacles);
+                }
+            }
+            if !progress.advance() {
+                return None;
+            }
+        }
+        Some(())
+    }
+
+    /// Lets the last step bounce off the first obstacle it crosses.  The rest of the step is
+    /// integrated in the field of `acceleration`, i.e. with the other bodies at the end of the
+    /// step.
+    fn bounce_off(
+        &mut self,
+        acceleration: impl Fn(Position) -> Acceleration,
+        obstacles: &[Obstacle],
+    ) {
+        let (h, index) = (self.h, self.nodes.len().saturating_sub(2));
+        let (Some(&n0), Some(&n1)) = (self.nodes.get(index), self.nodes.get(index + 1)) else {
+            return;
+        };
+        let Some(crossing) = Obstacle::first_crossing(
+            obstacles,
+            Point3::from(n0.s).into(),
+            Point3::from(n1.s).into(),
+        ) else {
+            return;
+        };
+        let obstacle = crossing.obstacle;
+        // The chord of the step crosses the surface, but the interpolation may cross it
+        // somewhere else:
+        let theta = crossing.refined_fraction(|theta| {
+            Point3::from(Node::interpolated(&n0, &n1, h, theta).s).into()
+        });
+        let tau = theta * h;
+        let before = Node::interpolated(&n0, &n1, h, theta);
+        let contact = Point3::from(before.s).into();
+        let normal = obstacle.normal_at(contact);
+        let after = Node {
+            v: obstacle.bounce(before.v.into(), &normal).into(),
+            ..before
+        };
+        let mut end = after.stepped(&acceleration, h - tau);
+        // e.g. a particle resting on the surface after an inelastic bounce would sink into it:
+        let s = obstacle.reflect(Point3::from(end.s).into(), contact, &normal);
+        if s.as_point().coords != end.s {
+            end.s = s.as_point().coords;
+            end.a = acceleration(s).into();
+        }
+        self.nodes[index + 1] = end;
+        self.bounces.push(Bounce {
+            index,
+            tau,
+            before,
+            after,
+        });
+    }
+
+    fn bounce_in(&self, index: usize) -> Option<&Bounce> {
+        self.bounces
+            .get(self.bounces.partition_point(|bounce| bounce.index < index))
+            .filter(|bounce| bounce.index == index)
+    }
+
+    /// All collisions with obstacles, in chronological order
+    pub fn collisions(&self) -> impl Iterator<Item = Collision> + '_ {
+        #![allow(clippy::cast_precision_loss)]
+        self.bounces.iter().map(|bounce| Collision {
+            t: (self.h * bounce.index as f32 + bounce.tau).into(),
+            s: Point3::from(bounce.before.s).into(),
+        })
+    }
+
+    /// The duration between two nodes
+    #[must_use]
+    pub fn step_duration(&self) -> Duration {
+        self.h.into()
+    }
+
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        #![allow(clippy::cast_precision_loss)]
+        (self.h * self.nodes.len().saturating_sub(1) as f32).into()
+    }
+
+    /// `true` if the nodes reach up to `duration` (apart from rounding errors)
+    #[must_use]
+    pub fn covers(&self, duration: Duration) -> bool {
+        f32::from(duration) - f32::from(self.duration()) <= 1e-3 * self.h
+    }
+
+    /// The positions of the nodes up to `duration`, e.g. to draw the trajectory.
+    pub fn node_positions(&self, duration: Duration) -> impl Iterator<Item = Position> + '_ {
+        #![allow(clippy::cast_possible_truncation)]
+        #![allow(clippy::cast_sign_loss)]
+        let num_nodes = (f32::from(duration) / self.h).ceil() as usize + 1;
+        self.nodes
+            .iter()
+            .take(num_nodes)
+            .enumerate()
+            .flat_map(move |(index, node)| {
+                ::std::iter::once(node.s).chain(self.bounce_in(index).map(|bounce| bounce.before.s))
+            })
+            .map(|s| Point3::from(s).into())
+    }
+
+    /// The reference solution at time `t`, which is clamped to `0..=self.duration()`.  Returns
+    /// `None` if nothing has been integrated.
+    #[must_use]
+    pub fn at(&self, t: Duration) -> Option<State> {
+        #![allow(clippy::cast_precision_loss)]
+        #![allow(clippy::cast_possible_truncation)]
+        #![allow(clippy::cast_sign_loss)]
+
+        let (first, last) = (self.nodes.first()?, self.nodes.last()?);
+        if let Some(exact) = &self.exact {
+            let t = f32::from(t).clamp(0., self.duration().into());
+            return Some((&exact.node_at(t)).into());
+        }
+        let steps = f32::from(t) / self.h;
+        if self.nodes.len() == 1 || steps.is_nan() || steps <= 0. {
+            return Some(first.into());
+        }
+        let index = steps.floor() as usize;
+        if index + 1 >= self.nodes.len() {
+            return Some(last.into());
+        }
+        let (n0, n1, h) = (&self.nodes[index], &self.nodes[index + 1], self.h);
+        let tau = (steps - index as f32) * h;
+        let node = match self.bounce_in(index) {
+            Some(bounce) if tau < bounce.tau => {
+                Node::interpolated(n0, &bounce.before, bounce.tau, tau / bounce.tau)
+            }
+            Some(bounce) => Node::interpolated(
+                &bounce.after,
+                n1,
+                h - bounce.tau,
+                (tau - bounce.tau) / (h - bounce.tau),
+            ),
+            None => Node::interpolated(n0, n1, h, tau / h),
+        };
+        Some((&node).into())
+    }
+
+    /// The reference samples for the first `num_steps` steps of duration `dt`.
+    #[must_use]
+    pub fn samples(&self, dt: Duration, num_steps: usize) -> Samples {
+        self.continued_samples(Samples::new(num_steps), dt, num_steps)
+    }
+
+    /// Like `samples()`, but continues `samples`, which must have been taken with the same `dt`
+    /// from this dense output or a shorter one it has been extended from.
+    #[must_use]
+    pub(crate) fn continued_samples(
+        &self,
+        mut samples: Samples<NonFinalized>,
+        dt: Duration,
+        num_steps: usize,
+    ) -> Samples {
+        #![allow(clippy::cast_precision_loss)]
+
+        samples.truncate(num_steps);
+        let kept = samples.len();
+        let start = match kept.checked_sub(1) {
+            Some(last) => Some(samples.at(last).next_condition()),
+            None => self
+                .at(0.0.into())
+                .map(|start| StartCondition::new(start.s, start.v, start.a)),
+        };
+        if let Some(mut start) = start {
+            for index in kept + 1..=num_steps {
+                let mut step = samples.push_step(&start, dt);
+                if let Some(end) = self.at(dt * index as f32) {
+                    step.raw_end_condition(end.s, end.v, end.a);
+                }
+                start = step.next_condition();
+            }
+        }
+        samples.finalized()
+    }
+}
+
+impl Exact {
+    /// Returns `None` unless the exact motion in `field` is known.
+    fn of(field: &dyn AccelerationField, start: (Position, Velocity)) -> Option<Self> {
+        field.exact_motion(start, 0.0.into())?;
+        Some(Self {
+            field: Arc::from(field.to_concrete_type().into_box()),
+            start,
+        })
+    }
+
+    fn node_at(&self, t: f32) -> Node {
+        let (s, v) = self
+            .field
+            .exact_motion(self.start, t.into())
+            .unwrap_or(self.start);
+        Node {
+            s: s.into(),
+            v: v.into(),
+            a: self.field.value_at(s).into(),
+        }
+    }
+
+    /// Appends `num_steps` nodes to `nodes`, which are `h` apart.  Returns `None` if cancelled
+    /// via `progress`.
+    fn append(
+        &self,
+        nodes: &mut ChunkedVec<Node>,
+        h: f32,
+        num_steps: usize,
+        progress: &Progress,
+    ) -> Option<()> {
+        #![allow(clippy::cast_precision_loss)]
+        progress.expect(num_steps);
+        for _ in 0..num_steps {
+            nodes.push(self.node_at(h * nodes.len() as f32));
+            if !progress.advance() {
+                return None;
+            }
+        }
+        Some(())
+    }
+}
+
+impl Node {
+    /// The interpolation at `theta` (between 0 and 1) of a step of duration `h` from `n0` to
+    /// `n1`
+    fn interpolated(n0: &Self, n1: &Self, h: f32, theta: f32) -> Self {
+        #![allow(clippy::many_single_char_names)]
+        let (theta2, theta3) = (theta * theta, theta * theta * theta);
+        let h00 = 2. * theta3 - 3. * theta2 + 1.;
+        let h10 = theta3 - 2. * theta2 + theta;
+        let h01 = -2. * theta3 + 3. * theta2;
+        let h11 = theta3 - theta2;
+        // derivatives with respect to theta:
+        let dh00 = 6. * theta2 - 6. * theta;
+        let dh10 = 3. * theta2 - 4. * theta + 1.;
+        let dh11 = 3. * theta2 - 2. * theta;
+
+        Self {
+            s: h00 * n0.s + h10 * h * n0.v + h01 * n1.s + h11 * h * n1.v,
+            v: h00 * n0.v + h10 * h * n0.a + h01 * n1.v + h11 * h * n1.a,
+            a: dh00 * (n0.v - n1.v) / h + dh10 * n0.a + dh11 * n1.a,
+        }
+    }
+
+    /// The position after `h`, which is exact for uniform acceleration
+    fn predicted_position(&self, h: f32) -> Vec3 {
+        self.s + self.v * h + 0.5 * self.a * h * h
+    }
+
+    /// The node after `h`, given the acceleration `a1` at the `predicted_position()`.  The
+    /// acceleration of the result still has to be set.
+    fn corrected(&self, a1: Vec3, h: f32) -> Self {
+        Self {
+            s: self.s + (self.v * h + (2. * self.a + a1) / 6. * h * h),
+            v: self.v + 0.5 * (self.a + a1) * h,
+            a: Vec3::zeros(),
+        }
+    }
+
+    /// The node after `h`, computed like the steps of `DenseOutput::append()`
+    fn stepped(&self, acceleration: impl Fn(Position) -> Acceleration, h: f32) -> Self {
+        let a1 = acceleration(Point3::from(self.predicted_position(h)).into());
+        let mut result = self.corrected(a1.into(), h);
+        result.a = acceleration(Point3::from(result.s).into()).into();
+        result
+    }
+}
+
+/// The position at the `fraction` of a step of duration `h` from `start` to `end`, interpolated
+/// like the positions of a dense output.
+pub(crate) fn interpolated_position(
+    start: &StartCondition,
+    end: &StartCondition,
+    h: f32,
+    fraction: f32,
+) -> Position {
+    let node = |condition: &StartCondition| Node {
+        s: condition.position().into(),
+        v: condition.velocity().into(),
+        a: condition.acceleration().into(),
+    };
+    Point3::from(Node::interpolated(&node(start), &node(end), h, fraction).s).into()
+}
+
+impl From<&Node> for State {
+    fn from(node: &Node) -> Self {
+        Self {
+            s: Point3::from(node.s).into(),
+            v: node.v.into(),
+            a: node.a.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DenseOutput, Progress};
+    use crate::{
+        bodies::System,
+        obstacle::{Obstacle, Shape},
+        scenarios::{
+            serde_box_dyn_acceleration_field::AccelerationFieldSerDe, CenterMass,
+            ConstantAcceleration, HarmonicOscillator, QuarticOscillator,
+        },
+        AccelerationField, Duration, Position, Vec3, Velocity,
+    };
+
+    fn dense_output(field: &dyn AccelerationField, num_steps: usize) -> DenseOutput {
+        DenseOutput::integrate(
+            field,
+            &[],
+            Position::new(0., 1., 0.),
+            Velocity::new(1., 0., 0.),
+            2.0.into(),
+            num_steps,
+            &Progress::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn exact_for_constant_acceleration() {
+        let dense = dense_output(&ConstantAcceleration, 3);
+        let a = ConstantAcceleration.value_at(Position::origin());
+        for t in [0., 0.1, 0.5, 1.3, 2.] {
+            let state = dense.at(t.into()).unwrap();
+            let expected_s = Position::new(0., 1., 0.)
+                + Velocity::new(1., 0., 0.) * Duration::from(t)
+                + a * Duration::from(t) * Duration::from(0.5 * t);
+            assert!(state.s.distance_squared(expected_s) < 1e-10, "t = {}", t);
+            let expected_v = Velocity::new(1., 0., 0.) + a * Duration::from(t);
+            assert!(
+                (state.v - expected_v).as_vector().norm() < 1e-5,
+                "t = {}",
+                t
+            );
+            assert!((state.a - a).as_vector().norm() < 1e-5, "t = {}", t);
+        }
+    }
+
+    #[test]
+    fn circular_orbit_between_nodes() {
+        let dense = dense_output(&CenterMass, 200);
+        for t in [0.005_f32, 0.333, 1.2345] {
+            let state = dense.at(t.into()).unwrap();
+            let expected = Position::new(t.sin(), t.cos(), 0.);
+            assert!(
+                state.s.distance_squared(expected).sqrt() < 1e-5,
+                "t = {}",
+                t
+            );
+            let expected = Velocity::new(t.cos(), -t.sin(), 0.);
+            assert!((state.v - expected).as_vector().norm() < 1e-4, "t = {}", t);
+            let expected = CenterMass.value_at(state.s);
+            assert!((state.a - expected).as_vector().norm() < 1e-3, "t = {}", t);
+        }
+    }
+
+    #[test]
+    fn extended() {
+        let field = CenterMass;
+        let short = dense_output(&field, 200);
+        let long = DenseOutput::extended_ensemble(
+            &[&short],
+            &System::new(&field, vec![0.]),
+            &[],
+            3.0.into(),
+            &Progress::default(),
+        )
+        .unwrap()
+        .pop()
+        .unwrap();
+        assert!(!short.covers(3.0.into()));
+        assert!(long.covers(3.0.into()));
+        assert_eq!(long.node_positions(2.0.into()).count(), 201);
+        let direct = DenseOutput::integrate(
+            &field,
+            &[],
+            Position::new(0., 1., 0.),
+            Velocity::new(1., 0., 0.),
+            3.0.into(),
+            300,
+            &Progress::default(),
+        )
+        .unwrap();
+        for t in [0.5_f32, 2.5, 3.] {
+            let (extended, direct) = (long.at(t.into()).unwrap(), direct.at(t.into()).unwrap());
+            assert!(
+                extended.s.distance_squared(direct.s).sqrt() < 1e-6,
+                "t = {}",
+                t
+            );
+        }
+    }
+
+    #[test]
+    fn ensemble_agrees_with_single_integrations() {
+        let starts = [
+            (Position::new(0., 1., 0.), Velocity::new(1., 0., 0.)),
+            (Position::new(0., 2., 0.), Velocity::new(0.5, 0., 0.)),
+            (Position::new(-1., 0., 0.), Velocity::new(0., 0.8, 0.)),
+        ];
+        let ensemble = DenseOutput::integrate_ensemble(
+            &System::new(&CenterMass, vec![0.; starts.len()]),
+            &[],
+            &starts,
+            2.0.into(),
+            100,
+            &Progress::default(),
+        )
+        .unwrap();
+        assert_eq!(ensemble.len(), starts.len());
+        for (dense, &(s, v)) in ensemble.iter().zip(&starts) {
+            let single = DenseOutput::integrate(
+                &CenterMass,
+                &[],
+                s,
+                v,
+                2.0.into(),
+                100,
+                &Progress::default(),
+            )
+            .unwrap();
+            assert!(dense
+                .node_positions(2.0.into())
+                .eq(single.node_positions(2.0.into())));
+        }
+    }
+
+    #[test]
+    fn exact_for_harmonic_oscillator() {
+        let field = HarmonicOscillator::lissajous();
+        // only two nodes, the rest comes from the exact motion:
+        let dense = dense_output(&field, 1);
+        // without anharmonicity, the quartic oscillator is integrated:
+        let integrated = dense_output(
+            &QuarticOscillator {
+                stiffness: 4.,
+                anharmonicity: 0.,
+            },
+            2000,
+        );
+        for t in [0., 0.1, 0.77, 1.5, 2.] {
+            let state = dense.at(t.into()).unwrap();
+            let expected = Position::new((2. * t).sin() / 2., (3. * t).cos(), 0.);
+            assert!(state.s.distance_squared(expected) < 1e-10, "t = {}", t);
+            let expected = Velocity::new((2. * t).cos(), -3. * (3. * t).sin(), 0.);
+            assert!((state.v - expected).as_vector().norm() < 1e-5, "t = {}", t);
+            let integrated = integrated.at(t.into()).unwrap();
+            // same x motion:
+            assert!((state.s.as_point().x - integrated.s.as_point().x).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn conserves_energy() {
+        for field in AccelerationFieldSerDe::variants() {
+            let field = field.into_box();
+            let Some(start_potential) = field.potential_at(Position::new(0., 1., 0.)) else {
+                continue;
+            };
+            let dense = dense_output(&*field, 400);
+            for t in [0.3, 1., 2.] {
+                let state = dense.at(t.into()).unwrap();
+                let energy =
+                    field.potential_at(state.s).unwrap() + 0.5 * state.v.as_vector().norm_squared();
+                assert!(
+                    (energy - start_potential - 0.5).abs() < 1e-4,
+                    "{}: t = {}",
+                    field.label(),
+                    t
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn samples() {
+        let dense = dense_output(&CenterMass, 200);
+        let samples = dense.samples(0.5.into(), 4);
+        assert_eq!(samples.len(), 4);
+        let last = samples.at(3).last_s();
+        assert!(last.distance_squared(Position::new(2_f32.sin(), 2_f32.cos(), 0.)) < 1e-8);
+    }
+
+    #[test]
+    fn bounces_off_the_ground() {
+        let ground = Obstacle::new(
+            Shape::Wall {
+                normal: Vec3::new(0., 1., 0.),
+            },
+            Position::origin(),
+        );
+        let dense = DenseOutput::integrate(
+            &ConstantAcceleration,
+            &[ground],
+            Position::new(0., 1., 0.),
+            Velocity::new(1., 0., 0.),
+            4.0.into(),
+            40,
+            &Progress::default(),
+        )
+        .unwrap();
+        // hits the ground at t = √2, and is back at y = 1 at 2√2:
+        let collisions = dense.collisions().collect::<Vec<_>>();
+        assert_eq!(collisions.len(), 1);
+        assert!((f32::from(collisions[0].t) - 2_f32.sqrt()).abs() < 1e-5);
+        assert!(collisions[0].s.as_point().y.abs() < 1e-5);
+        let apex = dense.at((2. * 2_f32.sqrt()).into()).unwrap();
+        assert!((apex.s.as_point().y - 1.).abs() < 1e-4);
+        assert!(apex.v.as_vector().y.abs() < 1e-4);
+        for t in [1.3_f32, 1.4, 1.42, 1.5] {
+            let before_and_after = dense.at(t.into()).unwrap();
+            let y = 1. - 0.5 * (2_f32.sqrt() - (t - 2_f32.sqrt()).abs()).powi(2);
+            assert!(
+                (before_and_after.s.as_point().y - y).abs() < 1e-4,
+                "t = {}",
+                t
+            );
+        }
+        assert!(dense
+            .node_positions(4.0.into())
+            .any(|s| s.as_point().y.abs() < 1e-5));
+    }
+}
@@ -0,0 +1,219 @@
+//! Detects integrations which have gone off the rails, e.g. after a close pass of a point mass
+//! where the acceleration grows without bounds.
+//!
+//! A step diverges if its end condition is not finite, if the total energy has drifted much
+//! further from its initial value than the energies involved at the start, or if the particle
+//! (or any other body) has moved much further away than it possibly could with its initial
+//! velocity and acceleration within the elapsed time.  As the limits do not depend on the duration
+//! of the scenario, a step which diverges does so regardless of how long the integration goes on.
+
+use super::{bodies::System, Duration, Position, StartCondition};
+
+/// How far an integration may drift before it counts as diverged
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limits {
+    /// The tolerated change of the total energy, relative to the sum of the absolute kinetic and
+    /// potential energies at the start
+    pub energy_factor: f32,
+    /// The tolerated distance from the start position, relative to the distance which the
+    /// initial velocity and acceleration would cover within the elapsed time
+    pub escape_factor: f32,
+}
+
+/// Why an integration has been stopped
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Divergence {
+    /// A position, velocity or acceleration is infinite or NaN.
+    NonFinite,
+    /// The total energy has drifted by `drift`.
+    EnergyBlowUp { drift: f32 },
+    /// A body is `distance` away from its start position.
+    Escaped { distance: f32 },
+}
+
+/// Where and when an integration diverged
+#[derive(Clone, Copy, Debug)]
+pub struct Diverged {
+    /// The start of the step which diverged
+    pub t: Duration,
+    /// The last position before the divergence
+    pub s: Position,
+    pub divergence: Divergence,
+}
+
+/// Checks the end conditions of the bodies after each step against the `Limits`, relative to
+/// their start conditions
+pub struct Monitor<'a> {
+    system: &'a System<'a>,
+    escape_factor: f32,
+    /// The start conditions of the bodies
+    starts: Vec<StartCondition>,
+    /// only for fields with a potential
+    start_energy: Option<f32>,
+    max_drift: f32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            energy_factor: 10.,
+            escape_factor: 10.,
+        }
+    }
+}
+
+impl ::std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match self {
+            Self::NonFinite => write!(f, "non-finite values"),
+            Self::EnergyBlowUp { drift } => write!(f, "energy drifted by {:.3e}", drift),
+            Self::Escaped { distance } => write!(f, "escaped to a distance of {:.3e}", distance),
+        }
+    }
+}
+
+impl ::std::error::Error for Divergence {}
+
+impl<'a> Monitor<'a> {
+    /// Monitors a scenario whose bodies start with `starts`.
+    #[must_use]
+    pub fn new(limits: Limits, system: &'a System<'a>, starts: &[StartCondition]) -> Self {
+        let start_energy = system.energy(starts);
+        let energy_scale = system.energy_scale(starts);
+        Self {
+            system,
+            escape_factor: limits.escape_factor,
+            starts: starts.to_vec(),
+            start_energy,
+            max_drift: limits.energy_factor * energy_scale.max(1.),
+        }
+    }
+
+    /// Checks the end conditions of the bodies after a step which ends at `t`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first limit which `ends` exceed.
+    pub fn check(&self, ends: &[StartCondition], t: Duration) -> Result<(), Divergence> {
+        let is_finite = ends.iter().all(|end| {
+            [
+                end.position().as_vector(),
+                end.velocity().as_vector(),
+                end.acceleration().as_vector(),
+            ]
+            .iter()
+            .all(|vector| vector.iter().all(|c| c.is_finite()))
+        });
+        if !is_finite {
+            return Err(Divergence::NonFinite);
+        }
+        if let Some(start_energy) = self.start_energy {
+            let drift = self.system.energy(ends).unwrap_or(start_energy) - start_energy;
+            if !drift.is_finite() {
+                return Err(Divergence::NonFinite);
+            }
+            if drift.abs() > self.max_drift {
+                return Err(Divergence::EnergyBlowUp { drift });
+            }
+        }
+        let t = f32::from(t);
+        for (end, start) in ends.iter().zip(&self.starts) {
+            let distance_scale = start.position().as_vector().norm()
+                + start.velocity().as_vector().norm() * t
+                + 0.5 * start.acceleration().as_vector().norm() * t * t;
+            let distance = end.position().distance_squared(start.position()).sqrt();
+            if distance > self.escape_factor * distance_scale.max(1.) {
+                return Err(Divergence::Escaped { distance });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Divergence, Limits, Monitor};
+    use crate::{
+        bodies::System,
+        scenarios::{CenterMass, ConstantAcceleration, FreeSpace},
+        Acceleration, AccelerationField, Position, StartCondition, Velocity,
+    };
+
+    fn start_condition(field: &dyn AccelerationField, s: Position, v: Velocity) -> StartCondition {
+        StartCondition::new(s, v, field.value_at(s))
+    }
+
+    #[test]
+    fn detects_divergence() {
+        let field = CenterMass;
+        let system = System::new(&field, vec![1.]);
+        let monitor = Monitor::new(
+            Limits::default(),
+            &system,
+            &[start_condition(
+                &field,
+                Position::new(0., 1., 0.),
+                Velocity::new(1., 0., 0.),
+            )],
+        );
+        let close_to_start = start_condition(
+            &field,
+            Position::new(0.1, 1., 0.),
+            Velocity::new(1., 0., 0.),
+        );
+        assert_eq!(monitor.check(&[close_to_start], 3.0.into()), Ok(()));
+        let singular = start_condition(&field, Position::origin(), Velocity::new(1., 0., 0.));
+        assert_eq!(
+            monitor.check(&[singular], 3.0.into()),
+            Err(Divergence::NonFinite)
+        );
+        let slingshot = start_condition(
+            &field,
+            Position::new(0., 0.1, 0.),
+            Velocity::new(30., 0., 0.),
+        );
+        assert!(matches!(
+            monitor.check(&[slingshot], 3.0.into()),
+            Err(Divergence::EnergyBlowUp { .. })
+        ));
+        let far_away = StartCondition::new(
+            Position::new(1000., 0., 0.),
+            Velocity::zeros(),
+            Acceleration::zeros(),
+        );
+        assert!(matches!(
+            Monitor::new(
+                Limits::default(),
+                &System::new(&ConstantAcceleration, vec![1.]),
+                &[start_condition(
+                    &ConstantAcceleration,
+                    Position::origin(),
+                    Velocity::zeros()
+                )],
+            )
+            .check(&[far_away], 1.0.into()),
+            Err(Divergence::Escaped { .. })
+        ));
+    }
+
+    #[test]
+    fn escape_limit_grows_with_elapsed_time() {
+        let start = StartCondition::new(
+            Position::origin(),
+            Velocity::new(1., 0., 0.),
+            Acceleration::zeros(),
+        );
+        let system = System::new(&FreeSpace, vec![1.]);
+        let monitor = Monitor::new(Limits::default(), &system, &[start]);
+        let ends = [StartCondition::new(
+            Position::new(50., 0., 0.),
+            Velocity::new(1., 0., 0.),
+            Acceleration::zeros(),
+        )];
+        assert!(matches!(
+            monitor.check(&ends, 2.0.into()),
+            Err(Divergence::Escaped { .. })
+        ));
+        assert_eq!(monitor.check(&ends, 10.0.into()), Ok(()));
+    }
+}
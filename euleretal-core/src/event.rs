@@ -0,0 +1,220 @@
+//! Events are zero crossings of an event function of position and velocity, like "hit the
+//! ground".  They are located on the reference solution by root-finding on its dense output,
+//! and on integrations by interpolating within the step in which the event function changes its
+//! sign.
+
+use super::{dense_output::DenseOutput, Duration, Position, StartCondition, Velocity};
+
+/// The functions whose zero crossings define the events
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ::serde::Deserialize, ::serde::Serialize)]
+pub enum EventFunction {
+    /// y crosses 0 while falling
+    HitGround,
+    /// y crosses 0 in any direction
+    CrossXAxis,
+    /// The radial velocity turns from inward to outward (closest approach to the origin).
+    Periapsis,
+}
+
+/// An event, and whether the scenario ends with its first occurrence
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ::serde::Deserialize, ::serde::Serialize)]
+pub struct Event {
+    pub function: EventFunction,
+    pub terminal: bool,
+}
+
+/// Where and when an event occurred
+#[derive(Clone, Copy, Debug)]
+pub struct Occurrence {
+    pub t: Duration,
+    pub s: Position,
+    pub v: Velocity,
+}
+
+/// The number of bisections used to locate an event between two nodes of a dense output
+const BISECTIONS: usize = 30;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Rising,
+    Falling,
+    Any,
+}
+
+impl EventFunction {
+    #[must_use]
+    pub fn variants() -> [Self; 3] {
+        [Self::HitGround, Self::CrossXAxis, Self::Periapsis]
+    }
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::HitGround => "Hit the Ground",
+            Self::CrossXAxis => "Cross x-Axis",
+            Self::Periapsis => "Periapsis",
+        }
+    }
+
+    /// The event occurs where this value crosses zero.
+    #[must_use]
+    pub fn value(self, s: Position, v: Velocity) -> f32 {
+        match self {
+            Self::HitGround | Self::CrossXAxis => s.as_point().y,
+            Self::Periapsis => s.as_point().coords.dot(v.as_vector()),
+        }
+    }
+
+    /// `true` if the event occurs between two states with the values `before` and `after`.
+    #[must_use]
+    pub fn is_crossed(self, before: f32, after: f32) -> bool {
+        let rising = before < 0. && after >= 0.;
+        let falling = before > 0. && after <= 0.;
+        match self.direction() {
+            Direction::Rising => rising,
+            Direction::Falling => falling,
+            Direction::Any => rising || falling,
+        }
+    }
+
+    fn direction(self) -> Direction {
+        match self {
+            Self::HitGround => Direction::Falling,
+            Self::CrossXAxis => Direction::Any,
+            Self::Periapsis => Direction::Rising,
+        }
+    }
+
+    /// The first occurrence within a step from `start` at `t0` to (`s`, `v`) at `t1`.  The time
+    /// and state of the event are interpolated linearly.
+    #[must_use]
+    pub fn occurrence_in_step(
+        self,
+        start: &StartCondition,
+        (s, v): (Position, Velocity),
+        (t0, t1): (Duration, Duration),
+    ) -> Option<Occurrence> {
+        let (before, after) = (
+            self.value(start.position(), start.velocity()),
+            self.value(s, v),
+        );
+        self.is_crossed(before, after).then(|| {
+            let fraction = before / (before - after);
+            Occurrence {
+                t: t0 + (t1 - t0) * fraction,
+                s: (start.position().as_point()
+                    + (s.as_point() - start.position().as_point()) * fraction)
+                    .into(),
+                v: start.velocity() + (v - start.velocity()) * fraction,
+            }
+        })
+    }
+
+    /// The first occurrence on `dense_output` up to `until`, located by bisection between the
+    /// nodes which bracket it
+    #[must_use]
+    pub fn first_occurrence(
+        self,
+        dense_output: &DenseOutput,
+        until: Duration,
+    ) -> Option<Occurrence> {
+        let value_at = |t: Duration| dense_output.at(t).map(|state| self.value(state.s, state.v));
+        let h = dense_output.step_duration();
+        let mut t0 = Duration::from(0.);
+        let mut before = value_at(t0)?;
+        while t0 < until {
+            let t1 = (t0 + h).min(until);
+            let after = value_at(t1)?;
+            if self.is_crossed(before, after) {
+                let (mut lower, mut upper) = (t0, t1);
+                for _ in 0..BISECTIONS {
+                    let mid = (lower + upper) * 0.5;
+                    if self.is_crossed(before, value_at(mid)?) {
+                        upper = mid;
+                    } else {
+                        lower = mid;
+                    }
+                }
+                let state = dense_output.at(upper)?;
+                return Some(Occurrence {
+                    t: upper,
+                    s: state.s,
+                    v: state.v,
+                });
+            }
+            (t0, before) = (t1, after);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventFunction, Occurrence};
+    use crate::{
+        dense_output::DenseOutput, job::Progress, scenarios::ConstantAcceleration,
+        AccelerationField, Position, StartCondition, Velocity,
+    };
+
+    #[test]
+    fn hit_ground_on_reference() {
+        // falls from y = 1 with a = -1, so it hits the ground at t = √2:
+        let dense = DenseOutput::integrate(
+            &ConstantAcceleration,
+            &[],
+            Position::new(0., 1., 0.),
+            Velocity::new(1., 0., 0.),
+            2.0.into(),
+            20,
+            &Progress::default(),
+        )
+        .unwrap();
+        let Occurrence { t, s, .. } = EventFunction::HitGround
+            .first_occurrence(&dense, 2.0.into())
+            .unwrap();
+        assert!((f32::from(t) - 2_f32.sqrt()).abs() < 1e-5);
+        assert!(s.as_point().y.abs() < 1e-5);
+        assert!(EventFunction::HitGround
+            .first_occurrence(&dense, 1.0.into())
+            .is_none());
+        // The x-axis is crossed at the same time, but periapsis never happens:
+        assert!(EventFunction::CrossXAxis
+            .first_occurrence(&dense, 2.0.into())
+            .is_some());
+        assert!(EventFunction::Periapsis
+            .first_occurrence(&dense, 2.0.into())
+            .is_none());
+    }
+
+    #[test]
+    fn occurrence_in_step() {
+        let start = StartCondition::new(
+            Position::new(0., 1., 0.),
+            Velocity::new(1., -1., 0.),
+            ConstantAcceleration.value_at(Position::origin()),
+        );
+        let end = (Position::new(1., -3., 0.), Velocity::new(1., -2., 0.));
+        let occurrence = EventFunction::HitGround
+            .occurrence_in_step(&start, end, (1.0.into(), 1.5.into()))
+            .unwrap();
+        assert!((f32::from(occurrence.t) - 1.125).abs() < 1e-6);
+        assert!(
+            occurrence
+                .s
+                .distance_squared(Position::new(0.25, 0., 0.))
+                .abs()
+                < 1e-10
+        );
+        // rising is not falling:
+        let (start, end) = (
+            StartCondition::new(end.0, end.1, start.acceleration()),
+            (start.position(), start.velocity()),
+        );
+        assert!(EventFunction::HitGround
+            .occurrence_in_step(&start, end, (0.0.into(), 1.0.into()))
+            .is_none());
+        assert!(EventFunction::CrossXAxis
+            .occurrence_in_step(&start, end, (0.0.into(), 1.0.into()))
+            .is_some());
+    }
+}
@@ -18,6 +18,16 @@ impl Fraction {
             denominator,
         }
     }
+
+    #[must_use]
+    pub const fn numerator(self) -> usize {
+        self.numerator
+    }
+
+    #[must_use]
+    pub const fn denominator(self) -> usize {
+        self.denominator
+    }
 }
 
 impl Default for Fraction {
@@ -1,19 +1,197 @@
 use super::{
-    integration_step, AccelerationField, Duration, Integrator, Position, Samples, Scenario,
-    StartCondition,
+    bodies::{LockStep, System},
+    dense_output::{interpolated_position, DenseOutput, State},
+    divergence::{Diverged, Limits, Monitor},
+    event::{Event, Occurrence},
+    integration_step,
+    job::{Job, Progress, Status},
+    obstacle::{Collision, Obstacle},
+    orbit::{OrbitErrors, OrbitMetrics},
+    Acceleration, AccelerationSource, Duration, Integrator, PointIndex, Position, ReferenceCache,
+    ReferenceSamples, Samples, Scenario, StartCondition, Step, Track,
 };
 use ::std::{
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher},
+    sync::Arc,
 };
 
+/// The effort of an integration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cost {
+    /// Samples of the acceleration field taken by the integrator for all bodies (excluding the
+    /// start condition)
+    pub evaluations: usize,
+    /// Wall-clock time of the integration (not available for wasm)
+    pub duration: Option<::std::time::Duration>,
+}
+
+impl Cost {
+    /// The share of the cost of `len` steps which is spent on the first `kept` of them
+    fn scaled(self, kept: usize, len: usize) -> Self {
+        #![allow(clippy::cast_possible_truncation)]
+        #![allow(clippy::cast_precision_loss)]
+        Self {
+            evaluations: self.evaluations * kept / len,
+            duration: self
+                .duration
+                .map(|duration| duration.mul_f64(kept as f64 / len as f64)),
+        }
+    }
+}
+
+/// What has been detected while integrating, with the indices of the steps it happened in
+#[derive(Clone, Default)]
+struct Detections {
+    /// The first occurrence of the scenario's event
+    event: Option<(usize, Occurrence)>,
+    /// in chronological order
+    collisions: Vec<(usize, Collision)>,
+    /// The step which has not been kept because it diverged
+    divergence: Option<(usize, Diverged)>,
+}
+
+impl Detections {
+    /// Only what has been detected within the first `len` steps
+    fn truncated(mut self, len: usize) -> Self {
+        self.event = self.event.filter(|&(step_idx, _)| step_idx < len);
+        self.collisions.retain(|&(step_idx, _)| step_idx < len);
+        self.divergence = self.divergence.filter(|&(step_idx, _)| step_idx < len);
+        self
+    }
+
+    /// `true` once the `event` has occurred, if it is terminal
+    fn is_terminated(&self, event: Option<Event>) -> bool {
+        self.event.is_some() && event.is_some_and(|event| event.terminal)
+    }
+
+    /// Looks for the first occurrence of `event` in the step at `step_idx`, which leads from
+    /// `start` to `end` within `interval`.
+    fn detect_event(
+        &mut self,
+        event: Option<Event>,
+        step_idx: usize,
+        (start, end): (&StartCondition, &StartCondition),
+        interval: (Duration, Duration),
+    ) {
+        if let (Some(event), None) = (event, self.event) {
+            self.event = event
+                .function
+                .occurrence_in_step(start, (end.position(), end.velocity()), interval)
+                .map(|occurrence| (step_idx, occurrence));
+        }
+    }
+
+    /// Checks if the step at `step_idx`, which leads the bodies from `starts` to `ends` within
+    /// `interval`, diverges according to `monitor`.
+    fn detect_divergence(
+        &mut self,
+        monitor: &Monitor,
+        step_idx: usize,
+        (starts, ends): (&[StartCondition], &[StartCondition]),
+        (t0, t1): (Duration, Duration),
+    ) -> bool {
+        if let (Err(divergence), Some(start)) = (monitor.check(ends, t1), starts.first()) {
+            self.divergence = Some((
+                step_idx,
+                Diverged {
+                    t: t0,
+                    s: start.position(),
+                    divergence,
+                },
+            ));
+        }
+        self.divergence.is_some()
+    }
+}
+
+/// The samples to be continued by [`Integration::integrate()`]
+struct Previous {
+    tracks: Vec<Arc<Track>>,
+    cost: Cost,
+    detections: Detections,
+}
+
+impl Previous {
+    /// How many of the samples can be kept for an integration of `num_steps`.  All of them, as
+    /// the limits of the `Monitor` do not depend on the duration: the steps which have been
+    /// accepted stay accepted, and the samples already end before a step which diverged.
+    fn kept_steps(&self, num_steps: usize) -> usize {
+        self.len().min(num_steps)
+    }
+
+    fn len(&self) -> usize {
+        self.tracks.first().map_or(0, |track| track.samples.len())
+    }
+}
+
+/// What an integration keeps of each step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Recording {
+    /// All computed quantities and their contributions, e.g. for the inspector.
+    Full,
+    /// Only the position, velocity and acceleration at the end of each step.  This is much
+    /// faster for large numbers of steps.  See `Integration::recorded_step()` for the rest.
+    EndStates,
+}
+
 pub struct Integration {
-    /// invariant: samples.len() == reference_samples.len()
-    samples: Option<Samples>,
+    recording: Recording,
+    /// The samples of the particle and the further bodies of the scenario, in this order.  The
+    /// point index of each track contains all computed positions, including those which are not
+    /// recorded.  Empty until the first integration has finished.
+    ///
+    /// invariant: samples.len() == reference_samples.len() for each track
+    tracks: Vec<Arc<Track>>,
+    cost: Option<Cost>,
+    /// Events and collisions of the particle, and the divergence after the `tracks`
+    detections: Detections,
+    /// The first occurrence of the scenario's event on the reference solution
+    reference_event: Option<Occurrence>,
     sample_validity: u64,
+    /// Like `sample_validity`, but regardless of the duration of the scenario.  The samples can
+    /// be continued (or truncated) as long as this does not change.
+    continuation_validity: u64,
     /// invariant: samples.len() == reference_samples.len()
-    reference_samples: Option<Samples>,
+    reference_samples: Option<Arc<ReferenceSamples>>,
+    /// Identifies the scenario, the step duration and the number of reference samples
     ref_sample_validity: u64,
+    /// The computation of the samples for the most recent inputs
+    job: Option<Job<Outcome>>,
+    requested_validity: u64,
+    /// The steps at one index, re-calculated with all computed quantities by `record_steps()`
+    recorded: Option<Recorded>,
+}
+
+/// See [`Integration::record_steps()`]
+struct Recorded {
+    idx: usize,
+    /// of the samples which have been re-calculated
+    sample_validity: u64,
+    /// one per body
+    steps: Vec<Step<'static>>,
+}
+
+/// Where a [`Job`] started by [`Integration::update()`] takes the reference samples from
+struct Reference {
+    cache: Arc<ReferenceCache>,
+    scenario_hash: u64,
+    /// Has hashed the scenario and the step duration, but not yet the number of steps
+    hasher: DefaultHasher,
+    /// Of the current reference samples, which are only replaced if this changes
+    current_validity: u64,
+}
+
+/// The result of a [`Job`] started by [`Integration::update()`]
+struct Outcome {
+    tracks: Vec<Track>,
+    cost: Cost,
+    detections: Detections,
+    reference_event: Option<Occurrence>,
+    sample_validity: u64,
+    continuation_validity: u64,
+    /// only if the reference samples had to be re-calculated
+    reference: Option<(Arc<ReferenceSamples>, u64)>,
 }
 
 impl Default for Integration {
@@ -25,124 +203,1109 @@ impl Default for Integration {
 impl Integration {
     #[must_use]
     pub fn new() -> Self {
+        Self::with_recording(Recording::Full)
+    }
+
+    #[must_use]
+    pub fn with_recording(recording: Recording) -> Self {
         Self {
-            samples: None,
+            recording,
+            tracks: Vec::new(),
+            cost: None,
+            detections: Detections::default(),
+            reference_event: None,
             sample_validity: 0,
+            continuation_validity: 0,
             reference_samples: None,
             ref_sample_validity: 0,
+            job: None,
+            requested_validity: 0,
+            recorded: None,
         }
     }
 
-    #[allow(clippy::missing_panics_doc)]
+    /// Starts the re-calculation if the inputs have changed since the last call, cancelling any
+    /// obsolete one.  Until the new samples are available, the previous ones remain accessible
+    /// (see `is_stale()`).
+    ///
+    /// If only the duration of the scenario has changed, the current samples are continued or
+    /// truncated instead of being re-calculated.
+    ///
+    /// If the scenario has a terminal event, the integration ends with the step in which the
+    /// event has been detected, and the reference samples are truncated accordingly.
+    ///
+    /// The reference samples are taken from (or added to) `references`.
+    ///
     /// returns `true` if something was actually updated
     pub fn update(
         &mut self,
         scenario: &Scenario,
         integrator: &dyn Integrator,
         step_duration: Duration,
+        references: &Arc<ReferenceCache>,
     ) -> bool {
+        let mut updated = self.receive();
+
         // check if we have to re-calculate:
         let mut hasher = DefaultHasher::new();
-        scenario.hash_default(&mut hasher);
+        scenario.hash_without_duration(&mut hasher);
+        let scenario_hash = hasher.finish();
         step_duration.hash(&mut hasher);
-        let ref_sample_validity = hasher.finish();
+        let reference_hasher = hasher.clone();
+        scenario.event.hash(&mut hasher);
         integrator.hash(&mut hasher);
+        self.recording.hash(&mut hasher);
+        let continuation_validity = hasher.finish();
+        scenario.duration.hash(&mut hasher);
         let sample_validity = hasher.finish();
 
-        if sample_validity == self.sample_validity {
-            false
-        } else {
-            #[allow(clippy::cast_sign_loss)]
-            #[allow(clippy::cast_possible_truncation)]
-            let num_steps = (scenario.duration / step_duration) as usize;
-
-            let samples = Self::integrate(
-                integrator,
-                &*scenario.acceleration,
-                &StartCondition::new(
-                    scenario.start_position,
-                    scenario.start_velocity,
-                    scenario.acceleration.value_at(scenario.start_position),
-                ),
-                num_steps,
-                step_duration,
-            );
-            let num_samples = samples.len();
-            assert!(num_samples == num_steps);
-            self.samples = Some(samples);
-            self.sample_validity = sample_validity;
-
-            if self.ref_sample_validity != ref_sample_validity {
-                let reference_samples = scenario.calculate_reference_samples(step_duration);
-                let num_refs = reference_samples.len();
-                assert!(num_refs == num_samples);
-                self.reference_samples = Some(reference_samples);
-                self.ref_sample_validity = ref_sample_validity;
+        if sample_validity != self.requested_validity {
+            self.requested_validity = sample_validity;
+            if sample_validity == self.sample_validity {
+                // back to the inputs of the current samples
+                self.job = None;
+            } else {
+                let previous = match self.cost {
+                    Some(cost)
+                        if continuation_validity == self.continuation_validity
+                            && !self.tracks.is_empty() =>
+                    {
+                        Some(Previous {
+                            tracks: self.tracks.clone(),
+                            cost,
+                            detections: self.detections.clone(),
+                        })
+                    }
+                    _ => None,
+                };
+                self.job = Some(Self::spawn(
+                    scenario.clone(),
+                    integrator.to_concrete_type().into_box(),
+                    self.recording,
+                    step_duration,
+                    (sample_validity, continuation_validity),
+                    previous,
+                    Reference {
+                        cache: Arc::clone(references),
+                        scenario_hash,
+                        hasher: reference_hasher,
+                        current_validity: self.ref_sample_validity,
+                    },
+                ));
+                updated |= self.receive();
             }
-            true
         }
+        updated
+    }
+
+    /// Identifies the inputs of the current samples, e.g. to cache what is derived from them.
+    /// Changes whenever the samples are replaced.
+    #[must_use]
+    pub fn sample_validity(&self) -> u64 {
+        self.sample_validity
     }
 
+    #[must_use]
+    pub fn recording(&self) -> Recording {
+        self.recording
+    }
+
+    /// Takes effect with the next `update()`.
+    pub fn set_recording(&mut self, recording: Recording) {
+        self.recording = recording;
+    }
+
+    /// With `Recording::EndStates`, re-calculates the steps at `idx` of all bodies with all
+    /// computed quantities, in lock-step, so that they can be inspected by `recorded_step()`.
+    /// Does nothing if they have been re-calculated already, or while the samples are stale (as
+    /// they do not belong to the `integrator` then).
+    pub fn record_steps(&mut self, idx: usize, integrator: &dyn Integrator, system: &System) {
+        let num_samples = self.tracks.first().map_or(0, |track| track.samples.len());
+        let is_recorded = self.recorded.as_ref().is_some_and(|recorded| {
+            recorded.idx == idx && recorded.sample_validity == self.sample_validity
+        });
+        if self.recording == Recording::Full || self.is_stale() || idx >= num_samples || is_recorded
+        {
+            return;
+        }
+        let starts = self
+            .tracks
+            .iter()
+            .map(|track| track.samples.at(idx).get_start_condition())
+            .collect::<Vec<_>>();
+        let dt = self.tracks[0].samples.at(idx).dt();
+        let evaluation = (self.tracks.len() > 1)
+            .then(|| LockStep::new(integrator).evaluate(system, &starts, dt));
+        let steps = self
+            .tracks
+            .iter()
+            .zip(&starts)
+            .enumerate()
+            .map(|(body, (track, start))| {
+                let body_field = evaluation
+                    .as_ref()
+                    .map(|evaluation| evaluation.body_field(system, body));
+                let acceleration_field: &dyn AccelerationSource = match &body_field {
+                    Some(body_field) => body_field,
+                    None => system.acceleration_field(),
+                };
+                let mut step = Step::new(start, dt);
+                let mut builder =
+                    integration_step::builders::Step::new(acceleration_field, &mut step);
+                let ((s, v, a), dt) = (builder.start_values(), builder.dt());
+                integrator.integrate_step(s, v, a, dt, &mut builder);
+                builder.finalize();
+                // bounces off obstacles are not re-calculated:
+                if track.bounces.binary_search(&idx).is_ok() {
+                    let end = track.samples.at(idx).next_condition();
+                    step.as_mut().raw_end_condition(
+                        end.position(),
+                        end.velocity(),
+                        end.acceleration(),
+                    );
+                }
+                step
+            })
+            .collect();
+        self.recorded = Some(Recorded {
+            idx,
+            sample_validity: self.sample_validity,
+            steps,
+        });
+    }
+
+    /// The sample at `idx` of the particle (`body` 0) or a further body of the scenario, with
+    /// all computed quantities and their contributions.  With `Recording::EndStates`, only the
+    /// steps which have been re-calculated by `record_steps()` are available.
+    #[must_use]
+    pub fn recorded_step(&self, body: usize, idx: usize) -> Option<Step<'_>> {
+        match self.recording {
+            Recording::Full => self.body_samples(body)?.get(idx),
+            Recording::EndStates => {
+                let recorded = self.recorded.as_ref().filter(|recorded| {
+                    recorded.idx == idx && recorded.sample_validity == self.sample_validity
+                })?;
+                recorded.steps.get(body).map(Step::view)
+            }
+        }
+    }
+
+    /// Blocks until the pending calculation (if any) has finished.
+    ///
+    /// returns `true` if something was actually updated
+    pub fn wait(&mut self) -> bool {
+        match self.job.take().and_then(|job| job.wait()) {
+            Some(outcome) => {
+                self.apply(outcome);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// `true` while the samples do not belong to the inputs of the last `update()`
+    #[must_use]
+    pub fn is_stale(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// The progress of the pending calculation, between 0 and 1.
+    #[must_use]
+    pub fn progress(&self) -> Option<f32> {
+        self.job.as_ref().map(Job::progress)
+    }
+
+    fn spawn(
+        scenario: Scenario,
+        integrator: Box<dyn Integrator>,
+        recording: Recording,
+        step_duration: Duration,
+        (sample_validity, continuation_validity): (u64, u64),
+        previous: Option<Previous>,
+        reference: Reference,
+    ) -> Job<Outcome> {
+        Job::spawn(
+            &format!("integration {}", integrator.label()),
+            move |progress| {
+                #[allow(clippy::cast_sign_loss)]
+                #[allow(clippy::cast_possible_truncation)]
+                let num_steps = (scenario.duration / step_duration) as usize;
+
+                let (tracks, cost, detections) = Self::integrate(
+                    &*integrator,
+                    &scenario.system(),
+                    (scenario.event, &scenario.obstacles),
+                    &scenario.start_conditions(),
+                    num_steps,
+                    step_duration,
+                    recording,
+                    previous,
+                    progress,
+                )?;
+                let num_samples = tracks[0].samples.len();
+                assert!(num_samples <= num_steps);
+
+                let Reference {
+                    cache,
+                    scenario_hash,
+                    mut hasher,
+                    current_validity,
+                } = reference;
+                num_samples.hash(&mut hasher);
+                let validity = hasher.finish();
+                let reference = if validity == current_validity {
+                    None
+                } else {
+                    let reference_samples = cache.first_reference_samples(
+                        &scenario,
+                        scenario_hash,
+                        step_duration,
+                        num_samples,
+                        progress,
+                    )?;
+                    let num_refs = reference_samples.samples().len();
+                    assert!(num_refs == num_samples);
+                    Some((reference_samples, validity))
+                };
+                let reference_event = match scenario.event {
+                    Some(event) => event.function.first_occurrence(
+                        &cache
+                            .trajectory(&scenario, scenario_hash, step_duration, progress)?
+                            .dense_output,
+                        scenario.duration,
+                    ),
+                    None => None,
+                };
+                Some(Outcome {
+                    tracks,
+                    cost,
+                    detections,
+                    reference_event,
+                    sample_validity,
+                    continuation_validity,
+                    reference,
+                })
+            },
+        )
+    }
+
+    /// Takes the result of the pending calculation, if it has finished.
+    fn receive(&mut self) -> bool {
+        let status = match &self.job {
+            Some(job) => job.poll(),
+            None => return false,
+        };
+        match status {
+            Status::Running => false,
+            Status::Done(outcome) => {
+                self.job = None;
+                self.apply(outcome);
+                true
+            }
+            Status::Failed => {
+                log::error!("Integration failed");
+                self.job = None;
+                false
+            }
+        }
+    }
+
+    fn apply(&mut self, outcome: Outcome) {
+        self.tracks = outcome.tracks.into_iter().map(Arc::new).collect();
+        self.cost = Some(outcome.cost);
+        self.detections = outcome.detections;
+        self.reference_event = outcome.reference_event;
+        self.sample_validity = outcome.sample_validity;
+        self.continuation_validity = outcome.continuation_validity;
+        if let Some((reference_samples, ref_sample_validity)) = outcome.reference {
+            self.reference_samples = Some(reference_samples);
+            self.ref_sample_validity = ref_sample_validity;
+        }
+    }
+
+    /// Continues the `previous` samples (of the same inputs apart from the duration) if given,
+    /// truncating them if they have more than `num_steps` steps.  The bodies of the `system`
+    /// are integrated in lock-step, see [`LockStep`].  Stops early after the first occurrence of
+    /// a terminal `event` (of the particle).  Steps which cross one of the `obstacles` bounce off
+    /// it, see `bounce_off()`.  Stops before the first step which diverges, see
+    /// [`Monitor::check()`].
+    ///
+    /// returns `None` if cancelled via `progress`
+    #[allow(clippy::too_many_arguments)]
+    #[allow(clippy::too_many_lines)]
     fn integrate(
         integrator: &dyn Integrator,
-        acceleration_field: &dyn AccelerationField,
-        start_condition: &StartCondition,
+        system: &System,
+        (event, obstacles): (Option<Event>, &[Obstacle]),
+        start_conditions: &[StartCondition],
         num_steps: usize,
         dt: Duration,
-    ) -> Samples {
+        recording: Recording,
+        previous: Option<Previous>,
+        progress: &Progress,
+    ) -> Option<(Vec<Track>, Cost, Detections)> {
         #[cfg(not(target_arch = "wasm32"))]
         let start = ::std::time::Instant::now();
 
-        let mut samples = Samples::new(num_steps);
+        let monitor = Monitor::new(Limits::default(), system, start_conditions);
+        let lock_step = (system.num_bodies() > 1).then(|| LockStep::new(integrator));
+        let kept = previous
+            .as_ref()
+            .map_or(0, |previous| previous.kept_steps(num_steps));
+        let (mut tracks, mut start_conditions, kept, kept_cost, mut detections) = match previous {
+            Some(previous) if kept > 0 => {
+                let kept_cost = previous.cost.scaled(kept, previous.len());
+                let start_conditions = previous
+                    .tracks
+                    .iter()
+                    .map(|track| track.samples.at(kept - 1).next_condition())
+                    .collect::<Vec<_>>();
+                let tracks = previous
+                    .tracks
+                    .into_iter()
+                    .map(|track| {
+                        let samples = track.samples.first_steps(kept, num_steps);
+                        // the clone shares all but the last blocks with the previous track:
+                        let mut point_index = track.point_index.clone();
+                        point_index.truncate(kept);
+                        let mut bounces = track.bounces.clone();
+                        bounces.retain(|&step_idx| step_idx < kept);
+                        (samples, point_index, Vec::new(), bounces)
+                    })
+                    .collect::<Vec<_>>();
+                let detections = previous.detections.truncated(kept);
+                (tracks, start_conditions, kept, kept_cost, detections)
+            }
+            _ => (
+                start_conditions
+                    .iter()
+                    .map(|_| {
+                        let samples = Samples::new(num_steps);
+                        (samples, PointIndex::default(), Vec::new(), Vec::new())
+                    })
+                    .collect(),
+                start_conditions.to_vec(),
+                0,
+                Cost {
+                    evaluations: 0,
+                    duration: Some(::std::time::Duration::ZERO),
+                },
+                Detections::default(),
+            ),
+        };
+        let mut evaluations = kept_cost.evaluations;
 
-        let mut step = integration_step::Step::new(start_condition, dt);
-        let mut builder = integration_step::builders::Step::new(acceleration_field, &mut step);
-        for _ in 0..num_steps {
-            let ((s, v, a), dt) = (builder.start_values(), builder.dt());
-            integrator.integrate_step(s, v, a, dt, &mut builder);
-            builder.finalize();
-            let next_step = step.create_next();
-            samples.push_sample(step);
-            step = next_step;
-            builder = integration_step::builders::Step::new(acceleration_field, &mut step);
+        progress.expect(num_steps - kept);
+        for step_idx in kept..num_steps {
+            if detections.is_terminated(event) {
+                break;
+            }
+            #[allow(clippy::cast_precision_loss)]
+            let interval = (dt * step_idx as f32, dt * (step_idx + 1) as f32);
+            let evaluation = lock_step
+                .as_ref()
+                .map(|lock_step| lock_step.evaluate(system, &start_conditions, dt));
+            let mut end_conditions = Vec::with_capacity(tracks.len());
+            let mut collision = None;
+            for (body, ((samples, _, points, bounces), start_condition)) in
+                tracks.iter_mut().zip(&start_conditions).enumerate()
+            {
+                let body_field = evaluation
+                    .as_ref()
+                    .map(|evaluation| evaluation.body_field(system, body));
+                let (acceleration_field, end_positions): (&dyn AccelerationSource, &[Position]) =
+                    match (&body_field, &evaluation) {
+                        (Some(body_field), Some(evaluation)) => {
+                            (body_field, evaluation.end_positions())
+                        }
+                        _ => (system.acceleration_field(), &[]),
+                    };
+                let mut step = samples.push_step(start_condition, dt);
+                evaluations +=
+                    Self::integrate_step(integrator, acceleration_field, recording, &mut step);
+                let body_collision = Self::bounce_off(
+                    &mut step,
+                    obstacles,
+                    |s| system.acceleration_of(body, s, end_positions),
+                    interval,
+                );
+                if body_collision.is_some() {
+                    bounces.push(step_idx);
+                }
+                if body == 0 {
+                    collision = body_collision;
+                }
+                points.extend(step.view().positions_iter().map(|s| (s, step_idx)));
+                if recording == Recording::EndStates {
+                    step.keep_start_and_end_only();
+                }
+                end_conditions.push(step.next_condition());
+            }
+            if detections.detect_divergence(
+                &monitor,
+                step_idx,
+                (&start_conditions, &end_conditions),
+                interval,
+            ) {
+                for (samples, _, points, bounces) in &mut tracks {
+                    samples.truncate(step_idx);
+                    points.retain(|&(_, idx)| idx < step_idx);
+                    bounces.retain(|&idx| idx < step_idx);
+                }
+                break;
+            }
+            if let Some(collision) = collision {
+                detections.collisions.push((step_idx, collision));
+            }
+            detections.detect_event(
+                event,
+                step_idx,
+                (&start_conditions[0], &end_conditions[0]),
+                interval,
+            );
+            start_conditions = end_conditions;
+            if !progress.advance() {
+                return None;
+            }
         }
-        let result = samples.finalized();
+        let tracks = tracks
+            .into_iter()
+            .map(|(samples, mut point_index, points, bounces)| {
+                point_index.extend(points);
+                Track {
+                    samples: samples.finalized(),
+                    point_index,
+                    bounces,
+                }
+            })
+            .collect();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let duration = kept_cost.duration.map(|kept| kept + start.elapsed());
+        #[cfg(target_arch = "wasm32")]
+        let duration = None;
 
         #[cfg(not(target_arch = "wasm32"))]
         log::debug!("{}: {}µs", integrator.label(), start.elapsed().as_micros());
 
-        result
+        Some((
+            tracks,
+            Cost {
+                evaluations,
+                duration,
+            },
+            detections,
+        ))
+    }
+
+    /// Lets the `integrator` compute `step`, and returns the number of evaluations of the
+    /// `acceleration_field`.
+    fn integrate_step(
+        integrator: &dyn Integrator,
+        acceleration_field: &dyn AccelerationSource,
+        recording: Recording,
+        step: &mut integration_step::StepMut,
+    ) -> usize {
+        let mut builder = match recording {
+            Recording::Full => integration_step::builders::Step::new(acceleration_field, step),
+            Recording::EndStates => {
+                integration_step::builders::Step::new_lightweight(acceleration_field, step)
+            }
+        };
+        let ((s, v, a), dt) = (builder.start_values(), builder.dt());
+        integrator.integrate_step(s, v, a, dt, &mut builder);
+        builder.finalize()
+    }
+
+    /// If `step` (from `t0` to `t1`) crosses the surface of one of the `obstacles`, appends the
+    /// end condition after bouncing off it: the penetrating end position is reflected at the
+    /// surface, and so is the end velocity.  The computed quantities of the integrator are kept,
+    /// so that they can still be inspected.
+    ///
+    /// Returns where the step hits the obstacle, interpolating between its start and end
+    /// conditions like the reference solution does (see `Crossing::refined_fraction()`).
+    fn bounce_off(
+        step: &mut integration_step::StepMut,
+        obstacles: &[Obstacle],
+        acceleration: impl Fn(Position) -> Acceleration,
+        (t0, t1): (Duration, Duration),
+    ) -> Option<Collision> {
+        let (start, end) = (step.view().get_start_condition(), step.next_condition());
+        let crossing = Obstacle::first_crossing(obstacles, start.position(), end.position())?;
+        let obstacle = crossing.obstacle;
+        let dt = f32::from(t1 - t0);
+        let fraction =
+            crossing.refined_fraction(|fraction| interpolated_position(&start, &end, dt, fraction));
+        let contact = interpolated_position(&start, &end, dt, fraction);
+        let normal = obstacle.normal_at(contact);
+        let s = obstacle.reflect(end.position(), contact, &normal);
+        let v = obstacle.bounce(end.velocity(), &normal);
+        step.raw_end_condition(s, v, acceleration(s));
+        Some(Collision {
+            t: t0 + (t1 - t0) * fraction,
+            s: contact,
+        })
+    }
+
+    #[must_use]
+    pub fn cost(&self) -> Option<Cost> {
+        self.cost
+    }
+
+    /// Where the integration detected the first occurrence of the scenario's event, by
+    /// interpolating within the step in which it occurred.
+    #[must_use]
+    pub fn detected_event(&self) -> Option<Occurrence> {
+        self.detections.event.map(|(_, occurrence)| occurrence)
+    }
+
+    /// Where and why the integration stopped early because it diverged.  The samples end
+    /// before the step which diverged.
+    #[must_use]
+    pub fn divergence(&self) -> Option<Diverged> {
+        self.detections.divergence.map(|(_, diverged)| diverged)
+    }
+
+    /// Where the integration bounced off an obstacle within the step at `idx`
+    #[must_use]
+    pub fn collision_in_step(&self, idx: usize) -> Option<Collision> {
+        self.detections
+            .collisions
+            .iter()
+            .find(|&&(step_idx, _)| step_idx == idx)
+            .map(|&(_, collision)| collision)
+    }
+
+    /// Where the integration bounced off obstacles
+    pub fn collisions(&self) -> impl Iterator<Item = Collision> + '_ {
+        self.detections
+            .collisions
+            .iter()
+            .map(|&(_, collision)| collision)
+    }
+
+    /// Where the reference solution bounced off obstacles during the integrated steps
+    pub fn reference_collisions(&self) -> impl Iterator<Item = Collision> + '_ {
+        #![allow(clippy::cast_precision_loss)]
+        let (reference, until) = match (&self.reference_samples, self.samples()) {
+            (Some(reference), Some(samples)) if !samples.is_empty() => (
+                Some(&reference.trajectory.dense_output),
+                samples.at(0).dt() * samples.len() as f32,
+            ),
+            _ => (None, Duration::from(0.)),
+        };
+        reference
+            .into_iter()
+            .flat_map(DenseOutput::collisions)
+            .take_while(move |collision| collision.t <= until)
+    }
+
+    /// The first occurrence of the scenario's event on the reference solution.
+    #[must_use]
+    pub fn reference_event(&self) -> Option<Occurrence> {
+        self.reference_event
+    }
+
+    /// The distance between the last computed position of the particle and its reference
+    /// position, i.e. the global error at the end of the integration.
+    #[must_use]
+    pub fn final_error(&self) -> Option<f32> {
+        #![allow(clippy::cast_precision_loss)]
+        let samples = self.samples()?;
+        let last = samples.at(samples.len().checked_sub(1)?);
+        self.position_error_at(last.last_s(), last.dt() * samples.len() as f32)
+    }
+
+    /// How the orbit of the particle around the origin deviates from the reference orbit, see
+    /// [`OrbitMetrics`].  Returns `None` for scenarios with further bodies and for orbits without
+    /// a complete period.
+    #[must_use]
+    pub fn orbit_metrics(&self) -> Option<(OrbitMetrics, OrbitErrors)> {
+        if self.num_bodies() != 1 {
+            return None;
+        }
+        let metrics = OrbitMetrics::of(self.samples()?)?;
+        let reference = OrbitMetrics::of(self.reference_samples()?)?;
+        Some((metrics, metrics.errors(&reference)))
+    }
+
+    /// The distance of `position`, computed for time `t`, from the reference position of the
+    /// particle at `t`.
+    #[must_use]
+    pub fn position_error_at(&self, position: Position, t: Duration) -> Option<f32> {
+        Some(position.distance_squared(self.reference_at(t)?.s).sqrt())
+    }
+
+    /// The reference solution of the particle at any time `t` within the duration of the
+    /// scenario.
+    #[must_use]
+    pub fn reference_at(&self, t: Duration) -> Option<State> {
+        self.body_reference_at(0, t)
+    }
+
+    /// Like `reference_at()`, but of the particle (`body` 0) or a further body
+    #[must_use]
+    pub fn body_reference_at(&self, body: usize, t: Duration) -> Option<State> {
+        self.reference_samples
+            .as_ref()?
+            .trajectory
+            .body(body)?
+            .at(t)
     }
 
     /// invariant: `samples()?.len() == reference_samples()?.len()`
     #[must_use]
     pub fn reference_samples(&self) -> Option<&Samples> {
-        self.reference_samples.as_ref()
+        self.body_reference_samples(0)
     }
 
     /// invariant: `samples()?.len() == reference_samples()?.len()`
     #[must_use]
     pub fn samples(&self) -> Option<&Samples> {
-        self.samples.as_ref()
+        self.body_samples(0)
     }
 
-    /// Finds the (computed or reference) sample which is closest to the given pointer position.
-    /// Returns `None` if there are no samples.
+    /// The particle and the further bodies of the scenario
     #[must_use]
-    pub fn closest_sample_index(&self, pos: &Position) -> Option<usize> {
-        if let (Some(references), Some(samples)) =
-            (self.reference_samples.as_ref(), self.samples.as_ref())
-        {
-            if let (Some(closest_reference), Some(closest_sample)) =
-                (references.closest(pos), samples.closest(pos))
-            {
-                return if closest_reference.distance < closest_sample.distance {
-                    Some(closest_reference.index)
-                } else {
-                    Some(closest_sample.index)
-                };
+    pub fn num_bodies(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// The reference samples of the particle (`body` 0) or a further body
+    #[must_use]
+    pub fn body_reference_samples(&self, body: usize) -> Option<&Samples> {
+        self.reference_samples
+            .as_deref()?
+            .tracks
+            .get(body)
+            .map(|track| &track.samples)
+    }
+
+    /// The samples of the particle (`body` 0) or a further body
+    #[must_use]
+    pub fn body_samples(&self, body: usize) -> Option<&Samples> {
+        self.tracks.get(body).map(|track| &track.samples)
+    }
+
+    /// Finds the body and the index of the sample with the (computed or reference) point which is
+    /// closest to the given pointer position.  Any computed point counts, not just the positions
+    /// at the end of the steps.  Returns `None` if there are no samples.
+    #[must_use]
+    pub fn closest_sample(&self, pos: &Position) -> Option<(usize, usize)> {
+        let reference = self.reference_samples.as_ref()?;
+        reference
+            .tracks
+            .iter()
+            .zip(&self.tracks)
+            .enumerate()
+            .flat_map(|(body, (reference, track))| {
+                [
+                    reference.point_index.closest(pos),
+                    track.point_index.closest(pos),
+                ]
+                .into_iter()
+                .flatten()
+                .map(move |(idx, distance)| (body, idx, distance))
+            })
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+            .map(|(body, idx, _)| (body, idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        divergence::Divergence,
+        event::EventFunction,
+        integrators::{euler, mid_point},
+        obstacle::Shape,
+        scenarios::{
+            self, serde_box_dyn_acceleration_field::AccelerationFieldSerDe, CenterMass,
+            ConstantAcceleration,
+        },
+        AccelerationField, Vec3, Velocity,
+    };
+
+    fn scenario() -> Scenario {
+        Scenario {
+            acceleration: Box::new(CenterMass),
+            start_position: Position::new(0., 1., 0.),
+            start_velocity: Velocity::new(1., 0., 0.),
+            duration: 2.0.into(),
+            event: None,
+            obstacles: Vec::new(),
+            mass: 1.,
+            bodies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn cost() {
+        let scenario = scenario();
+        let mut integration = Integration::new();
+        assert!(integration.cost().is_none());
+        integration.update(&scenario, &mid_point::Euler, 0.5.into(), &Arc::default());
+        integration.wait();
+        assert!(!integration.is_stale());
+        let cost = integration.cost().unwrap();
+        assert_eq!(
+            cost.evaluations,
+            4 * mid_point::Euler.metadata().samples_per_step
+        );
+        assert!(integration.final_error().unwrap() > 0.);
+    }
+
+    #[test]
+    fn continued_when_duration_changes() {
+        let references = Arc::default();
+        let integrate_from_scratch = |scenario: &Scenario| {
+            let mut integration = Integration::new();
+            integration.update(scenario, &mid_point::SecondOrder, 0.1.into(), &references);
+            integration.wait();
+            integration
+        };
+        let mut scenario = scenario();
+        let mut continued = integrate_from_scratch(&scenario);
+        for duration in [3.0, 1.5, 2.5] {
+            scenario.duration = duration.into();
+            continued.update(&scenario, &mid_point::SecondOrder, 0.1.into(), &references);
+            continued.wait();
+            let expected = integrate_from_scratch(&scenario);
+            let (samples, expected_samples) =
+                (continued.samples().unwrap(), expected.samples().unwrap());
+            assert_eq!(samples.len(), expected_samples.len());
+            assert_eq!(continued.reference_samples().unwrap().len(), samples.len());
+            assert!(samples
+                .step_positions()
+                .eq(expected_samples.step_positions()));
+            assert_eq!(
+                continued.cost().unwrap().evaluations,
+                expected.cost().unwrap().evaluations
+            );
+            let idx = samples.len() - 1;
+            assert!(samples
+                .at(idx)
+                .positions_iter()
+                .eq(expected_samples.at(idx).positions_iter()));
+        }
+    }
+
+    #[test]
+    fn terminated_by_event() {
+        let mut scenario = scenario();
+        scenario.event = Some(Event {
+            function: EventFunction::CrossXAxis,
+            terminal: true,
+        });
+        scenario.duration = 5.0.into();
+        let references = Arc::default();
+        let mut integration = Integration::new();
+        integration.update(&scenario, &mid_point::SecondOrder, 0.1.into(), &references);
+        integration.wait();
+        // the circular orbit crosses the x-axis after a quarter of a revolution:
+        let (detected, reference) = (
+            integration.detected_event().unwrap(),
+            integration.reference_event().unwrap(),
+        );
+        assert!((f32::from(reference.t) - ::std::f32::consts::FRAC_PI_2).abs() < 1e-4);
+        assert!((f32::from(detected.t) - f32::from(reference.t)).abs() < 0.01);
+        let samples = integration.samples().unwrap();
+        assert_eq!(samples.len(), 16);
+        assert_eq!(
+            integration.reference_samples().unwrap().len(),
+            samples.len()
+        );
+
+        // continuing does not go beyond the event:
+        scenario.duration = 6.0.into();
+        integration.update(&scenario, &mid_point::SecondOrder, 0.1.into(), &references);
+        integration.wait();
+        assert_eq!(integration.samples().unwrap().len(), 16);
+
+        // non-terminal events are only detected:
+        scenario.event = Some(Event {
+            function: EventFunction::CrossXAxis,
+            terminal: false,
+        });
+        integration.update(&scenario, &mid_point::SecondOrder, 0.1.into(), &references);
+        integration.wait();
+        assert_eq!(integration.samples().unwrap().len(), 60);
+        assert_eq!(integration.reference_samples().unwrap().len(), 60);
+        assert!(integration.detected_event().is_some());
+    }
+
+    #[test]
+    fn bounces_off_obstacles() {
+        let mut scenario = scenario();
+        scenario.acceleration = Box::new(ConstantAcceleration);
+        scenario.obstacles.push(Obstacle::new(
+            Shape::Wall {
+                normal: Vec3::new(0., 1., 0.),
+            },
+            Position::origin(),
+        ));
+        scenario.duration = 4.0.into();
+        for recording in [Recording::Full, Recording::EndStates] {
+            let mut integration = Integration::with_recording(recording);
+            integration.update(
+                &scenario,
+                &mid_point::SecondOrder,
+                0.1.into(),
+                &Arc::default(),
+            );
+            integration.wait();
+            let idx = 14; // the step from 1.4 to 1.5
+            integration.record_steps(idx, &mid_point::SecondOrder, &scenario.system());
+            let samples = integration.samples().unwrap();
+            assert!(samples.step_positions().all(|s| s.as_point().y >= 0.));
+            let (collision, reference) = (
+                integration.collisions().next().unwrap(),
+                integration.reference_collisions().next().unwrap(),
+            );
+            assert!((f32::from(collision.t) - f32::from(reference.t)).abs() < 0.01);
+            assert!(integration.collision_in_step(idx).is_some());
+            let step = integration.recorded_step(0, idx).unwrap();
+            // the penetrating position of the integrator, and the reflected one:
+            assert!(step.positions_iter().any(|s| s.as_point().y < 0.));
+            assert_eq!(step.last_s(), samples.at(idx).last_s());
+        }
+    }
+
+    #[test]
+    fn rests_on_an_inelastic_floor() {
+        let mut scenario = scenario();
+        scenario.acceleration = Box::new(ConstantAcceleration);
+        scenario.obstacles.push(Obstacle {
+            restitution: 0.,
+            ..Obstacle::new(
+                Shape::Wall {
+                    normal: Vec3::new(0., 1., 0.),
+                },
+                Position::origin(),
+            )
+        });
+        scenario.duration = 4.0.into();
+        let mut integration = Integration::new();
+        integration.update(
+            &scenario,
+            &mid_point::SecondOrder,
+            0.1.into(),
+            &Arc::default(),
+        );
+        integration.wait();
+        // hits the floor at t = √2 and slides on it from then on:
+        for samples in [integration.samples(), integration.reference_samples()] {
+            let samples = samples.unwrap();
+            assert_eq!(samples.len(), 40);
+            assert!(samples.step_positions().all(|s| s.as_point().y > -1e-4));
+            let last = samples.at(39).next_condition();
+            assert!(last.position().as_point().y.abs() < 1e-4);
+            assert!((last.position().as_point().x - 4.).abs() < 1e-3);
+        }
+        assert!(integration.collisions().count() > 20);
+    }
+
+    #[test]
+    fn stops_when_diverging() {
+        let mut scenario = scenario();
+        // falling straight into the center mass:
+        scenario.start_velocity = Velocity::zeros();
+        let references = Arc::default();
+        let mut integration = Integration::new();
+        integration.update(&scenario, &mid_point::Euler, 0.1.into(), &references);
+        integration.wait();
+        let diverged = integration.divergence().unwrap();
+        let samples = integration.samples().unwrap();
+        let num_samples = samples.len();
+        assert!(num_samples < 20);
+        assert_eq!(
+            integration.reference_samples().unwrap().len(),
+            samples.len()
+        );
+        #[allow(clippy::cast_precision_loss)]
+        let t = 0.1 * num_samples as f32;
+        assert!((f32::from(diverged.t) - t).abs() < 1e-4);
+        assert!(samples.step_positions().all(|s| s.as_point().y.is_finite()));
+        assert!(integration.final_error().unwrap().is_finite());
+
+        // continuing stops at the same step:
+        scenario.duration = 3.0.into();
+        integration.update(&scenario, &mid_point::Euler, 0.1.into(), &references);
+        integration.wait();
+        assert_eq!(integration.samples().unwrap().len(), num_samples);
+        assert!(integration.divergence().is_some());
+    }
+
+    /// `a(s) = s`, without a potential, so that only the escape limit applies
+    struct Repulsion;
+
+    impl AccelerationField for Repulsion {
+        fn value_at(&self, pos: Position) -> Acceleration {
+            Acceleration::from(*pos.as_vector())
+        }
+
+        fn label(&self) -> String {
+            "Repulsion".to_string()
+        }
+
+        fn to_concrete_type(&self) -> AccelerationFieldSerDe {
+            unimplemented!() // not required for integrating
+        }
+    }
+
+    #[test]
+    fn extending_keeps_divergence() {
+        let system = System::new(&Repulsion, vec![1.]);
+        let start = system.start_conditions(&[(Position::new(1., 0., 0.), Velocity::zeros())]);
+        let integrate = |num_steps, previous| {
+            Integration::integrate(
+                &euler::Broken,
+                &system,
+                (None, &[]),
+                &start,
+                num_steps,
+                0.1.into(),
+                Recording::EndStates,
+                previous,
+                &Progress::default(),
+            )
+            .unwrap()
+        };
+        let diverged_at = |detections: &Detections| {
+            let (step_idx, diverged) = detections.divergence.unwrap();
+            assert!(matches!(diverged.divergence, Divergence::Escaped { .. }));
+            step_idx
+        };
+        let (tracks, cost, detections) = integrate(70, None);
+        let step_idx = diverged_at(&detections);
+        assert_eq!(tracks[0].samples.len(), step_idx);
+
+        // a longer duration does not tolerate the escape:
+        let previous = Previous {
+            tracks: tracks.into_iter().map(Arc::new).collect(),
+            cost,
+            detections,
+        };
+        let (tracks, _, detections) = integrate(100, Some(previous));
+        assert_eq!(diverged_at(&detections), step_idx);
+        assert_eq!(tracks[0].samples.len(), step_idx);
+        let (_, _, from_scratch) = integrate(100, None);
+        assert_eq!(diverged_at(&from_scratch), step_idx);
+    }
+
+    #[test]
+    fn integrates_bodies_in_lock_step() {
+        let scenario = scenarios::n_body::figure_eight();
+        let system = scenario.system();
+        let momentum = |states: &[StartCondition]| {
+            states
+                .iter()
+                .map(|state| Vec3::from(state.velocity()))
+                .sum::<Vec3>()
+        };
+        let references = Arc::default();
+        let integrate = |recording| {
+            let mut integration = Integration::with_recording(recording);
+            integration.update(&scenario, &mid_point::SecondOrder, 0.01.into(), &references);
+            integration.wait();
+            integration
+        };
+        let (full, mut end_states) = (integrate(Recording::Full), integrate(Recording::EndStates));
+        let (body, idx) = (2, 123);
+        end_states.record_steps(idx, &mid_point::SecondOrder, &system);
+        assert_eq!(full.num_bodies(), 3);
+        assert!(full.divergence().is_none());
+        let last = full.samples().unwrap().len() - 1;
+        let ends = (0..3)
+            .map(|body| full.body_samples(body).unwrap().at(last).next_condition())
+            .collect::<Vec<_>>();
+        // all bodies have the same mass:
+        assert!(momentum(&ends).norm() < 1e-4);
+        let (start_energy, end_energy) = (
+            system.energy(&scenario.start_conditions()).unwrap(),
+            system.energy(&ends).unwrap(),
+        );
+        assert!((end_energy - start_energy).abs() < 1e-3);
+        // back at the start after one period:
+        for (body, end) in ends.iter().enumerate() {
+            let reference = full.body_reference_at(body, scenario.duration).unwrap();
+            assert!(end.position().distance_squared(reference.s).sqrt() < 0.01);
+        }
+        assert!(full.final_error().unwrap() < 0.01);
+        // two samples per step of each body:
+        assert_eq!(full.cost().unwrap().evaluations, 3 * 2 * (last + 1));
+        assert_eq!(
+            full.cost().unwrap().evaluations,
+            end_states.cost().unwrap().evaluations
+        );
+
+        let recorded = end_states.recorded_step(body, idx).unwrap();
+        let expected = full.body_samples(body).unwrap().at(idx);
+        assert!(recorded.positions_iter().eq(expected.positions_iter()));
+        let intermediate = expected.positions_iter().nth(1).unwrap();
+        assert_eq!(end_states.closest_sample(&intermediate), Some((body, idx)));
+    }
+
+    #[test]
+    fn picks_intermediate_points() {
+        let scenario = scenario();
+        let mut integration = Integration::with_recording(Recording::EndStates);
+        integration.update(
+            &scenario,
+            &mid_point::SecondOrder,
+            0.5.into(),
+            &Arc::default(),
+        );
+        integration.wait();
+        for idx in 0..integration.samples().unwrap().len() {
+            integration.record_steps(idx, &mid_point::SecondOrder, &scenario.system());
+            let step = integration.recorded_step(0, idx).unwrap();
+            let positions = step.positions_iter().collect::<Vec<_>>();
+            assert!(positions.len() > 2);
+            // neither the start nor the end position:
+            for position in &positions[1..positions.len() - 1] {
+                assert_eq!(integration.closest_sample(position), Some((0, idx)));
             }
         }
-        None
+    }
+
+    #[test]
+    fn end_states_agree_with_full_recording() {
+        let (scenario, references) = (scenario(), Arc::default());
+        let integrate = |recording| {
+            let mut integration = Integration::with_recording(recording);
+            integration.update(&scenario, &mid_point::SecondOrder, 0.1.into(), &references);
+            integration.wait();
+            integration
+        };
+        let (full, mut end_states) = (integrate(Recording::Full), integrate(Recording::EndStates));
+        let idx = 7;
+        end_states.record_steps(idx, &mid_point::SecondOrder, &scenario.system());
+        let (full_samples, end_samples) = (full.samples().unwrap(), end_states.samples().unwrap());
+        assert_eq!(full_samples.len(), end_samples.len());
+        assert_eq!(
+            full.cost().unwrap().evaluations,
+            end_states.cost().unwrap().evaluations
+        );
+        for idx in 0..full_samples.len() {
+            assert_eq!(full_samples.at(idx).last_s(), end_samples.at(idx).last_s());
+            assert_eq!(full_samples.at(idx).last_v(), end_samples.at(idx).last_v());
+        }
+
+        let recorded = end_states.recorded_step(0, idx).unwrap();
+        let expected = full_samples.at(idx);
+        assert!(recorded.positions_iter().eq(expected.positions_iter()));
+        assert_eq!(
+            recorded
+                .last_computed_position()
+                .contributions_iter()
+                .count(),
+            expected
+                .last_computed_position()
+                .contributions_iter()
+                .count()
+        );
+        assert!(end_samples.at(idx).positions_iter().count() < expected.positions_iter().count());
     }
 }
@@ -0,0 +1,211 @@
+use super::{
+    computed,
+    contributions::{self, DtFraction},
+    step::{AccelerationRef, PositionRef, VelocityRef},
+};
+use crate::Fraction;
+
+/// The storage of all computed quantities and their contributions of any number of `Step`s.
+/// Quantities are only ever appended, so that an integration never frees anything until its
+/// `Samples` (and with them, the arena) are dropped as a whole.
+#[derive(Clone, Default)]
+pub struct Arena {
+    positions: Vec<computed::Position>,
+    velocities: Vec<computed::Velocity>,
+    accelerations: Vec<computed::Acceleration>,
+    /// The dt fractions of the variants are meaningless, see `contributions::*::Generic`.
+    position_contributions: Vec<contributions::position::Variant<DtFraction<1, 1>>>,
+    velocity_contributions: Vec<contributions::velocity::Variant<DtFraction<1, 1>>>,
+}
+
+/// A range of indices into one of the vectors of an [`Arena`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(in crate::integration_step) struct Span {
+    pub(in crate::integration_step) start: usize,
+    pub(in crate::integration_step) end: usize,
+}
+
+impl Span {
+    const fn empty_at(start: usize) -> Self {
+        Self { start, end: start }
+    }
+
+    pub(in crate::integration_step) const fn is_empty(self) -> bool {
+        self.start == self.end
+    }
+
+    pub(in crate::integration_step) const fn range(self) -> ::std::ops::Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// The extent of one step in an [`Arena`].  All quantities of a step are stored contiguously.
+#[derive(Clone, Copy, Debug)]
+pub(in crate::integration_step) struct Extent {
+    pub(in crate::integration_step) positions: Span,
+    pub(in crate::integration_step) velocities: Span,
+    pub(in crate::integration_step) accelerations: Span,
+    pub(in crate::integration_step) position_contributions: Span,
+    pub(in crate::integration_step) velocity_contributions: Span,
+}
+
+impl Arena {
+    /// The (empty) extent of the next step to be added
+    pub(in crate::integration_step) fn next_extent(&self) -> Extent {
+        Extent {
+            positions: Span::empty_at(self.positions.len()),
+            velocities: Span::empty_at(self.velocities.len()),
+            accelerations: Span::empty_at(self.accelerations.len()),
+            position_contributions: Span::empty_at(self.position_contributions.len()),
+            velocity_contributions: Span::empty_at(self.velocity_contributions.len()),
+        }
+    }
+
+    /// A copy of everything before `extent`
+    pub(in crate::integration_step) fn prefix(&self, extent: &Extent) -> Self {
+        Self {
+            positions: self.positions[..extent.positions.start].to_vec(),
+            velocities: self.velocities[..extent.velocities.start].to_vec(),
+            accelerations: self.accelerations[..extent.accelerations.start].to_vec(),
+            position_contributions: self.position_contributions
+                [..extent.position_contributions.start]
+                .to_vec(),
+            velocity_contributions: self.velocity_contributions
+                [..extent.velocity_contributions.start]
+                .to_vec(),
+        }
+    }
+
+    /// Discards everything from `extent` on, which must be the extent of the last step.
+    pub(in crate::integration_step) fn truncate(&mut self, extent: &mut Extent) {
+        self.positions.truncate(extent.positions.start);
+        self.velocities.truncate(extent.velocities.start);
+        self.accelerations.truncate(extent.accelerations.start);
+        self.position_contributions
+            .truncate(extent.position_contributions.start);
+        self.velocity_contributions
+            .truncate(extent.velocity_contributions.start);
+        *extent = self.next_extent();
+    }
+
+    pub(in crate::integration_step) fn positions(&self, span: Span) -> &[computed::Position] {
+        &self.positions[span.range()]
+    }
+
+    pub(in crate::integration_step) fn velocities(&self, span: Span) -> &[computed::Velocity] {
+        &self.velocities[span.range()]
+    }
+
+    pub(in crate::integration_step) fn position_contributions(
+        &self,
+        span: Span,
+    ) -> &[contributions::position::Variant<DtFraction<1, 1>>] {
+        &self.position_contributions[span.range()]
+    }
+
+    pub(in crate::integration_step) fn velocity_contributions(
+        &self,
+        span: Span,
+    ) -> &[contributions::velocity::Variant<DtFraction<1, 1>>] {
+        &self.velocity_contributions[span.range()]
+    }
+
+    pub(in crate::integration_step) fn add_position(
+        &mut self,
+        position: computed::Position,
+        extent: &mut Extent,
+    ) -> PositionRef {
+        let p_ref = PositionRef::at(self.positions.len());
+        self.positions.push(position);
+        extent.positions.end = self.positions.len();
+        p_ref
+    }
+
+    pub(in crate::integration_step) fn add_velocity(
+        &mut self,
+        velocity: computed::Velocity,
+        extent: &mut Extent,
+    ) -> VelocityRef {
+        let v_ref = VelocityRef::at(self.velocities.len());
+        self.velocities.push(velocity);
+        extent.velocities.end = self.velocities.len();
+        v_ref
+    }
+
+    pub(in crate::integration_step) fn add_acceleration(
+        &mut self,
+        acceleration: computed::Acceleration,
+        extent: &mut Extent,
+    ) -> AccelerationRef {
+        let a_ref = AccelerationRef::at(self.accelerations.len());
+        self.accelerations.push(acceleration);
+        extent.accelerations.end = self.accelerations.len();
+        a_ref
+    }
+
+    pub(in crate::integration_step) fn add_position_contributions(
+        &mut self,
+        fraction: Fraction,
+        variants: impl IntoIterator<Item = contributions::position::Variant<DtFraction<1, 1>>>,
+        extent: &mut Extent,
+    ) -> contributions::position::collection::Generic {
+        let start = self.position_contributions.len();
+        self.position_contributions.extend(variants);
+        extent.position_contributions.end = self.position_contributions.len();
+        contributions::position::collection::Generic::new(
+            fraction,
+            Span {
+                start,
+                end: self.position_contributions.len(),
+            },
+        )
+    }
+
+    pub(in crate::integration_step) fn add_velocity_contributions(
+        &mut self,
+        fraction: Fraction,
+        variants: impl IntoIterator<Item = contributions::velocity::Variant<DtFraction<1, 1>>>,
+        extent: &mut Extent,
+    ) -> contributions::velocity::collection::Generic {
+        let start = self.velocity_contributions.len();
+        self.velocity_contributions.extend(variants);
+        extent.velocity_contributions.end = self.velocity_contributions.len();
+        contributions::velocity::collection::Generic::new(
+            fraction,
+            Span {
+                start,
+                end: self.velocity_contributions.len(),
+            },
+        )
+    }
+}
+
+impl ::std::ops::Index<PositionRef> for Arena {
+    type Output = computed::Position;
+
+    fn index(&self, p_ref: PositionRef) -> &Self::Output {
+        &self.positions[p_ref.index()]
+    }
+}
+
+impl ::std::ops::Index<VelocityRef> for Arena {
+    type Output = computed::Velocity;
+
+    fn index(&self, v_ref: VelocityRef) -> &Self::Output {
+        &self.velocities[v_ref.index()]
+    }
+}
+
+impl ::std::ops::IndexMut<VelocityRef> for Arena {
+    fn index_mut(&mut self, v_ref: VelocityRef) -> &mut Self::Output {
+        &mut self.velocities[v_ref.index()]
+    }
+}
+
+impl ::std::ops::Index<AccelerationRef> for Arena {
+    type Output = computed::Acceleration;
+
+    fn index(&self, a_ref: AccelerationRef) -> &Self::Output {
+        &self.accelerations[a_ref.index()]
+    }
+}
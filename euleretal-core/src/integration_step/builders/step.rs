@@ -1,30 +1,88 @@
 use super::{
     integration_step::{
         contributions,
-        step::{AccelerationRef, PositionRef, VelocityRef},
+        step::{AccelerationRef, PositionRef, StepMut, VelocityRef},
+        symbolic::Computed,
     },
     DtFraction,
 };
-use crate::AccelerationField;
+use crate::{AccelerationSource, Fraction};
 
 pub struct Step<'a> {
-    acceleration_field: &'a dyn AccelerationField,
-    step: &'a mut crate::Step,
+    acceleration_field: &'a dyn AccelerationSource,
+    step: StepMut<'a>,
+    /// The order of computation, only recorded for symbolic evaluation (see `symbolic::Trace`).
+    trace: Option<Vec<Computed>>,
+    /// Samples of the acceleration field taken so far
+    evaluations: usize,
+    /// If `false`, computed quantities are stored without their contributions.
+    records_contributions: bool,
 }
 
 impl<'a> Step<'a> {
-    pub fn new(acceleration_field: &'a dyn AccelerationField, step: &'a mut crate::Step) -> Self {
+    /// `step` is either a `&mut crate::Step` or a `StepMut`, e.g. from `Samples::push_step()`.
+    pub fn new(
+        acceleration_field: &'a dyn AccelerationSource,
+        step: impl Into<StepMut<'a>>,
+    ) -> Self {
         Self {
             acceleration_field,
-            step,
+            step: step.into(),
+            trace: None,
+            evaluations: 0,
+            records_contributions: true,
         }
     }
 
+    /// Evaluates the contributions of the computed quantities without storing them.  This is
+    /// considerably faster, but the resulting step cannot be inspected.
+    pub fn new_lightweight(
+        acceleration_field: &'a dyn AccelerationSource,
+        step: impl Into<StepMut<'a>>,
+    ) -> Self {
+        Self {
+            acceleration_field,
+            step: step.into(),
+            trace: None,
+            evaluations: 0,
+            records_contributions: false,
+        }
+    }
+
+    pub(in crate::integration_step) fn new_traced(
+        acceleration_field: &'a dyn AccelerationSource,
+        step: impl Into<StepMut<'a>>,
+    ) -> Self {
+        Self {
+            acceleration_field,
+            step: step.into(),
+            trace: Some(Vec::new()),
+            evaluations: 0,
+            records_contributions: true,
+        }
+    }
+
+    /// Returns the computed quantities in the order of computation (empty if not traced).
+    pub(in crate::integration_step) fn into_trace(self) -> Vec<Computed> {
+        self.trace.unwrap_or_default()
+    }
+
+    fn record<T: Copy>(&mut self, computed: T, into_computed: fn(T) -> Computed) -> T {
+        if let Some(trace) = &mut self.trace {
+            trace.push(into_computed(computed));
+        }
+        computed
+    }
+
     /// consumes `self`, and therefore cannot be called twice on the same instance.
-    pub fn finalize(mut self) {
+    ///
+    /// Returns the number of samples of the acceleration field taken for this step.
+    #[allow(clippy::must_use_candidate)] // the number of samples is often irrelevant
+    pub fn finalize(mut self) -> usize {
         self.set_display_position(self.step.last_velocity_ref(), self.step.last_position_ref());
         self.step
-            .compute_acceleration_at_last_position(self.acceleration_field);
+            .compute_acceleration_at_last_position(self.acceleration_field, self.evaluations + 1);
+        self.evaluations + 1
     }
 
     #[allow(clippy::unused_self)]
@@ -33,14 +91,10 @@ impl<'a> Step<'a> {
         DtFraction
     }
 
-    #[allow(clippy::unused_self)]
     #[must_use]
     pub fn start_values(&self) -> (PositionRef, VelocityRef, AccelerationRef) {
-        (
-            PositionRef::default(),
-            VelocityRef::default(),
-            AccelerationRef::default(),
-        )
+        let start = self.step.start_values();
+        (start.s, start.v, start.a)
     }
 
     pub fn set_display_position(&mut self, v_ref: VelocityRef, s_ref: PositionRef) {
@@ -48,8 +102,94 @@ impl<'a> Step<'a> {
     }
 
     pub fn acceleration_at(&mut self, s_ref: PositionRef) -> AccelerationRef {
-        self.step
-            .add_computed_acceleration(self.acceleration_field.value_at(self.step[s_ref].s), s_ref)
+        self.evaluations += 1;
+        let a_ref = self.step.add_computed_acceleration(
+            self.acceleration_field
+                .acceleration_at(self.step[s_ref].s, self.evaluations),
+            s_ref,
+        );
+        self.record(a_ref, Computed::Acceleration)
+    }
+
+    /// Computes `s + Σ factor·v·(dt_fraction·dt) + Σ factor·a·(dt_fraction·dt)²`.
+    ///
+    /// This is the counterpart of `compute()` for integrators which are only known at runtime
+    /// (`compute()` requires the dt fraction at compile time).
+    pub fn compute_position(
+        &mut self,
+        dt_fraction: Fraction,
+        s_ref: PositionRef,
+        velocity_terms: &[(f32, VelocityRef)],
+        acceleration_terms: &[(f32, AccelerationRef)],
+    ) -> PositionRef {
+        let dt = dt_fraction * self.step.dt();
+        let mut s = self.step[s_ref].s;
+        for &(factor, v_ref) in velocity_terms {
+            s += factor * self.step[v_ref].v * dt;
+        }
+        for &(factor, a_ref) in acceleration_terms {
+            s += factor * self.step[a_ref].a * dt * dt;
+        }
+        let variants = self
+            .records_contributions
+            .then(|| {
+                ::std::iter::once(contributions::position::Variant::StartPosition { s_ref })
+                    .chain(velocity_terms.iter().map(|&(factor, v_ref)| {
+                        contributions::position::Variant::VelocityDt {
+                            factor,
+                            v_ref,
+                            dt_fraction: DtFraction,
+                        }
+                    }))
+                    .chain(acceleration_terms.iter().map(|&(factor, a_ref)| {
+                        contributions::position::Variant::AccelerationDtDt {
+                            factor,
+                            a_ref,
+                            dt_fraction: DtFraction,
+                        }
+                    }))
+            })
+            .into_iter()
+            .flatten();
+        let s_ref = self.step.add_generic_position(s, dt_fraction, variants);
+        self.record(s_ref, Computed::Position)
+    }
+
+    /// Computes `v + Σ factor·a·(dt_fraction·dt)`.
+    ///
+    /// This is the counterpart of `compute()` for integrators which are only known at runtime
+    /// (`compute()` requires the dt fraction at compile time).
+    pub fn compute_velocity(
+        &mut self,
+        dt_fraction: Fraction,
+        v_ref: VelocityRef,
+        acceleration_terms: &[(f32, AccelerationRef)],
+    ) -> VelocityRef {
+        let dt = dt_fraction * self.step.dt();
+        let mut v = self.step[v_ref].v;
+        for &(factor, a_ref) in acceleration_terms {
+            v += factor * self.step[a_ref].a * dt;
+        }
+        let variants = self
+            .records_contributions
+            .then(|| {
+                ::std::iter::once(contributions::velocity::Variant::Velocity { v_ref }).chain(
+                    acceleration_terms.iter().map(|&(factor, a_ref)| {
+                        contributions::velocity::Variant::AccelerationDt {
+                            factor,
+                            a_ref,
+                            dt_fraction: DtFraction,
+                        }
+                    }),
+                )
+            })
+            .into_iter()
+            .flatten();
+        let sampling_position = self.step.last_position_ref(); // just a default. Can be overwritten.
+        let v_ref = self
+            .step
+            .add_generic_velocity(v, sampling_position, dt_fraction, variants);
+        self.record(v_ref, Computed::Velocity)
     }
 }
 
@@ -68,11 +208,19 @@ impl<'a, const N: usize, const D: usize> Collector<contributions::position::Coll
         contributions: contributions::position::Collection<N, D>,
     ) -> Self::Output {
         let mut s = crate::Position::origin();
+        let step = self.step.view();
         for contrib in &contributions {
-            s += contrib.evaluate_for(self.step);
+            s += contrib.evaluate_for(&step);
         }
-        self.step
-            .add_computed_position(s, DtFraction::<N, D>, contributions)
+        let contributions = if self.records_contributions {
+            contributions
+        } else {
+            contributions::position::Collection::empty()
+        };
+        let s_ref = self
+            .step
+            .add_computed_position(s, DtFraction::<N, D>, &contributions);
+        self.record(s_ref, Computed::Position)
     }
 }
 
@@ -86,14 +234,22 @@ impl<'a, const N: usize, const D: usize> Collector<contributions::velocity::Coll
         contributions: contributions::velocity::Collection<N, D>,
     ) -> Self::Output {
         let mut v = crate::Velocity::zeros();
+        let step = self.step.view();
         for contrib in &contributions {
-            v += contrib.evaluate_for(self.step);
+            v += contrib.evaluate_for(&step);
         }
-        self.step.add_computed_velocity(
+        let contributions = if self.records_contributions {
+            contributions
+        } else {
+            contributions::velocity::Collection::empty()
+        };
+        let sampling_position = self.step.last_position_ref(); // just a default. Can be overwritten.
+        let v_ref = self.step.add_computed_velocity(
             v,
-            self.step.last_position_ref(), // just a default. Can be overwritten.
+            sampling_position,
             DtFraction::<N, D>,
-            contributions,
-        )
+            &contributions,
+        );
+        self.record(v_ref, Computed::Velocity)
     }
 }
@@ -2,7 +2,7 @@
 
 use super::integration_step::StartCondition;
 use super::{integration_step::builders::step::Collector, Contribution, Step as StepBuilder};
-use crate::{Acceleration, AccelerationField, Duration, Position, Step, Velocity};
+use crate::{Acceleration, AccelerationField, Duration, Fraction, Position, Step, Velocity};
 // not used in super, so we use an absolute path (only for tests!):
 
 #[derive(Clone, Copy, ::serde::Deserialize, ::serde::Serialize)]
@@ -48,11 +48,11 @@ impl Default for Setup {
 }
 
 impl Setup {
-    fn new_builder_for<'a>(&'a self, step: &'a mut Step) -> StepBuilder<'a> {
+    fn new_builder_for<'a>(&'a self, step: &'a mut Step<'_>) -> StepBuilder<'a> {
         StepBuilder::new(&self.acceleration_field, step)
     }
 
-    fn new_step(&self) -> Step {
+    fn new_step(&self) -> Step<'static> {
         Step::new(&self.start_condition, self.dt)
     }
 
@@ -372,3 +372,81 @@ fn mid_point_euler() {
     assert_eq!(final_velocity.sampling_position().s(), s1);
     assert_eq!(final_velocity.v(), v1);
 }
+
+#[test]
+fn mid_point_euler_with_runtime_dt_fractions() {
+    let ctx = Setup::default();
+    let mut step = ctx.new_step();
+    let mut builder = ctx.new_builder_for(&mut step);
+
+    {
+        let (s, v, a) = builder.start_values();
+        let (half, one) = (Fraction::new(1, 2), Fraction::new(1, 1));
+        let v_mid = builder.compute_velocity(half, v, &[(1., a)]);
+        let s_mid = builder.compute_position(half, s, &[(1., v_mid)], &[]);
+        let a_mid = builder.acceleration_at(s_mid);
+        let v1 = builder.compute_velocity(one, v, &[(1., a_mid)]);
+        builder.compute_position(one, s, &[(1., v1)], &[]);
+    }
+    builder.finalize();
+
+    let ((s, v, a), dt) = (ctx.start_values(), ctx.dt);
+    let v_mid = v + a * dt * 0.5;
+    let s_mid = s + v_mid * dt * 0.5;
+    let a_mid = ctx.acceleration_field.value_at(s_mid);
+    let v1 = v + a_mid * dt;
+    let s1 = s + v1 * dt;
+
+    let final_position = step.last_computed_position();
+    let final_velocity = step.last_computed_velocity();
+    assert_eq!(final_position.s(), s1);
+    assert_eq!(final_velocity.v(), v1);
+
+    let mut s_contribs = final_position.contributions_iter();
+    assert_eq!(s_contribs.next().unwrap().sampling_position(), s);
+    assert_eq!(
+        s_contribs.next().unwrap().vector().unwrap(),
+        (v1 * dt).into()
+    );
+    assert!(s_contribs.next().is_none());
+}
+
+#[test]
+fn more_contributions_than_kept_inline() {
+    let ctx = Setup::default();
+    let mut step = ctx.new_step();
+    let mut builder = ctx.new_builder_for(&mut step);
+    {
+        // RK4-like weights with repeated terms:
+        let ((s0, v0, a0), dt) = (builder.start_values(), builder.dt());
+        let dt_half = dt.half();
+        builder.compute(
+            v0 + a0 * dt_half
+                + a0 * dt_half
+                + a0 * dt_half
+                + a0 * dt_half
+                + a0 * dt_half
+                + a0 * dt_half,
+        );
+        builder.compute(
+            s0 + v0 * dt_half
+                + v0 * dt_half
+                + v0 * dt_half
+                + v0 * dt_half
+                + v0 * dt_half
+                + v0 * dt_half,
+        );
+    }
+    builder.finalize();
+
+    let ((s0, v0, a0), dt) = (ctx.start_values(), ctx.dt);
+    let dt = f32::from(dt);
+    let final_position = step.last_computed_position();
+    let s1 = s0.as_point() + 3. * dt * v0.as_vector();
+    assert!((final_position.s().as_point() - s1).norm() < 1e-5);
+    assert_eq!(final_position.contributions_iter().count(), 7);
+    let final_velocity = step.last_computed_velocity();
+    let v1 = v0.as_vector() + 3. * dt * a0.as_vector();
+    assert!((final_velocity.v().as_vector() - v1).norm() < 1e-5);
+    assert_eq!(final_velocity.contributions_iter().count(), 7);
+}
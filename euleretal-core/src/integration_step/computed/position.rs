@@ -10,23 +10,20 @@ use crate::{Fraction, PhysicalQuantityKind, Vec3};
 #[derive(Clone)]
 pub struct Position {
     pub(in crate::integration_step) s: crate::Position,
-    contributions: contributions::position::collection::Generic,
+    pub(in crate::integration_step) contributions: contributions::position::collection::Generic,
 }
 
 impl Position {
-    pub(in crate::integration_step) fn new<const N: usize, const D: usize>(
+    pub(in crate::integration_step) fn new(
         s: crate::Position,
-        contributions: contributions::position::Collection<N, D>,
+        contributions: contributions::position::collection::Generic,
     ) -> Self {
-        Self {
-            s,
-            contributions: contributions.generalize(),
-        }
+        Self { s, contributions }
     }
 
     pub(in crate::integration_step) fn abstraction_for<'a>(
         &'a self,
-        step: &'a Step,
+        step: &'a Step<'a>,
     ) -> Abstraction<'a> {
         Abstraction {
             step,
@@ -47,7 +44,7 @@ impl Position {
 }
 
 pub struct Abstraction<'a> {
-    step: &'a Step,
+    step: &'a Step<'a>,
     position: &'a Position,
 }
 
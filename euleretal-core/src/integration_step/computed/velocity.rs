@@ -11,31 +11,30 @@ use super::{
 pub struct Velocity {
     pub(in crate::integration_step) v: crate::Velocity,
     pub(in crate::integration_step) sampling_position: PositionRef,
-    contributions: contributions::velocity::collection::Generic,
+    pub(in crate::integration_step) contributions: contributions::velocity::collection::Generic,
 }
 
 pub struct Abstraction<'a> {
-    step: &'a Step,
+    step: &'a Step<'a>,
     velocity: &'a Velocity,
 }
 
 impl Velocity {
-    pub(in crate::integration_step) fn new<const N: usize, const D: usize>(
+    pub(in crate::integration_step) fn new(
         v: crate::Velocity,
         sampling_position: PositionRef,
-        contributions: contributions::velocity::Collection<N, D>,
+        contributions: contributions::velocity::collection::Generic,
     ) -> Self {
-        #[allow(clippy::cast_precision_loss)]
         Self {
             v,
             sampling_position,
-            contributions: contributions.generalize(),
+            contributions,
         }
     }
 
     pub(in crate::integration_step) fn abstraction_for<'a>(
         &'a self,
-        step: &'a Step,
+        step: &'a Step<'a>,
     ) -> Abstraction<'a> {
         Abstraction {
             step,
@@ -4,6 +4,10 @@ pub mod position;
 mod r#trait;
 pub mod velocity;
 
-use super::step;
+use super::{arena, step};
 pub use dt_fraction::DtFraction;
 pub use r#trait::Contribution;
+
+/// The number of contributions to a quantity which are kept inline while it is being computed.
+/// This covers the integrators shipped with the app; further contributions are spilled.
+const MAX_CONTRIBUTIONS: usize = 4;
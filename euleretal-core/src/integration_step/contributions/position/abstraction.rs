@@ -3,13 +3,13 @@ use crate::{Fraction, PhysicalQuantityKind, Position, Vec3};
 
 #[derive(Clone)]
 pub struct Abstraction<'a> {
-    step: &'a Step,
+    step: &'a Step<'a>,
     // Abstraction cannot be parameterized, so we move the static fraction to a component
     variant: Variant<Fraction>,
 }
 
 impl<'a> Abstraction<'a> {
-    pub fn new(step: &'a Step, variant: Variant<Fraction>) -> Self {
+    pub fn new(step: &'a Step<'a>, variant: Variant<Fraction>) -> Self {
         Self { step, variant }
     }
 }
@@ -1,73 +1,99 @@
-use super::{Abstraction, DtFraction, Step, Variant};
+use super::{
+    super::{arena::Span, MAX_CONTRIBUTIONS},
+    step::PositionRef,
+    Abstraction, DtFraction, Step, Variant,
+};
 use crate::Fraction;
 
-#[derive(Default)]
-pub struct Collection<const N: usize, const D: usize>(Vec<Variant<DtFraction<N, D>>>);
-
+/// The contributions to a position which is about to be computed.  Up to `MAX_CONTRIBUTIONS` are
+/// kept inline, such that building them does not allocate.  Integrators with more terms spill
+/// the rest to a vector.  Once computed, they are moved to the `Arena`.
 #[derive(Clone)]
-pub struct Generic {
-    fraction: Fraction,
-    inner: Vec<Variant<DtFraction<1, 1>>>,
+pub struct Collection<const N: usize, const D: usize> {
+    len: usize,
+    variants: [Variant<DtFraction<N, D>>; MAX_CONTRIBUTIONS],
+    /// The contributions after the first `MAX_CONTRIBUTIONS`
+    spilled: Vec<Variant<DtFraction<N, D>>>,
 }
 
-impl<const N: usize, const D: usize> From<Vec<Variant<DtFraction<N, D>>>> for Collection<N, D> {
-    fn from(vec: Vec<Variant<DtFraction<N, D>>>) -> Self {
-        Self(vec)
-    }
+/// The contributions to a computed position, stored in the `Arena` of its step.
+#[derive(Clone, Copy)]
+pub struct Generic {
+    fraction: Fraction,
+    span: Span,
 }
 
 impl<const N: usize, const D: usize> Collection<N, D> {
     pub(in crate::integration_step) const fn empty() -> Self {
-        Self(Vec::new())
+        Self {
+            len: 0,
+            variants: [Variant::StartPosition {
+                s_ref: PositionRef::at(0),
+            }; MAX_CONTRIBUTIONS],
+            spilled: Vec::new(),
+        }
     }
 
-    pub(in crate::integration_step) fn generalize(self) -> Generic {
-        Generic {
-            fraction: Fraction::new(N, D),
-            inner: unsafe {
-                ::std::mem::transmute::<
-                    Vec<Variant<DtFraction<N, D>>>,
-                    Vec<Variant<DtFraction<1, 1>>>,
-                >(self.0)
-            },
-        }
+    pub(in crate::integration_step) fn of(variant: Variant<DtFraction<N, D>>) -> Self {
+        Self::empty() + variant
     }
 }
 
 impl<'a, const N: usize, const D: usize> IntoIterator for &'a Collection<N, D> {
     type Item = &'a Variant<DtFraction<N, D>>;
 
-    type IntoIter = std::slice::Iter<'a, Variant<DtFraction<N, D>>>;
-    //type IntoIter = impl IntoIterator<Variant<DtFraction<N, D>>>;
+    type IntoIter = std::iter::Chain<
+        std::slice::Iter<'a, Variant<DtFraction<N, D>>>,
+        std::slice::Iter<'a, Variant<DtFraction<N, D>>>,
+    >;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.iter()
+        self.variants[..self.len].iter().chain(&self.spilled)
     }
 }
 
 impl<const N: usize, const D: usize> std::ops::Add<Variant<DtFraction<N, D>>> for Collection<N, D> {
     type Output = Self;
 
-    fn add(self, rhs: Variant<DtFraction<N, D>>) -> Self::Output {
-        Self(self.0.into_iter().chain(Some(rhs)).collect())
+    fn add(mut self, rhs: Variant<DtFraction<N, D>>) -> Self::Output {
+        if self.len < MAX_CONTRIBUTIONS {
+            self.variants[self.len] = rhs;
+            self.len += 1;
+        } else {
+            self.spilled.push(rhs);
+        }
+        self
     }
 }
 
 impl Generic {
+    pub(in crate::integration_step) fn new(fraction: Fraction, span: Span) -> Self {
+        Self { fraction, span }
+    }
+
     pub(in crate::integration_step) fn is_empty(&self) -> bool {
-        self.inner.is_empty()
+        self.span.is_empty()
     }
 
     pub(in crate::integration_step) fn dt_fraction(&self) -> Fraction {
         self.fraction
     }
 
-    pub(in crate::integration_step) fn abstraction_iter_for<'a>(
-        &'a self,
+    /// The dt fractions of the variants are meaningless, use `dt_fraction()` instead.
+    pub(in crate::integration_step) fn variants<'a>(
+        &self,
         step: &'a Step,
+    ) -> &'a [Variant<DtFraction<1, 1>>] {
+        step.arena().position_contributions(self.span)
+    }
+
+    pub(in crate::integration_step) fn abstraction_iter_for<'a>(
+        &self,
+        step: &'a Step<'a>,
     ) -> impl Iterator<Item = Abstraction<'a>> {
-        self.inner
+        let fraction = self.fraction;
+        self.variants(step)
             .iter()
-            .map(move |variant| variant.abstraction_scaled_for(step, self.fraction))
+            .map(move |variant| variant.abstraction_scaled_for(step, fraction))
     }
 }
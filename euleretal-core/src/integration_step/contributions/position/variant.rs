@@ -63,34 +63,39 @@ impl<F: FractionSpec> Variant<F> {
     }
 
     pub fn abstraction_scaled_for<'a>(
-        &'a self,
-        step: &'a Step,
+        &self,
+        step: &'a Step<'a>,
         fraction: Fraction,
     ) -> Abstraction<'a> {
-        Abstraction::new(
-            step,
-            match *self {
-                Variant::StartPosition { s_ref } => Variant::StartPosition { s_ref },
-                Variant::VelocityDt {
-                    factor,
-                    v_ref,
-                    dt_fraction: _,
-                } => Variant::VelocityDt {
-                    factor,
-                    v_ref,
-                    dt_fraction: fraction,
-                },
-                Variant::AccelerationDtDt {
-                    factor,
-                    a_ref,
-                    dt_fraction: _,
-                } => Variant::AccelerationDtDt {
-                    factor,
-                    a_ref,
-                    dt_fraction: fraction,
-                },
+        Abstraction::new(step, self.with_fraction(fraction))
+    }
+
+    /// The same variant, but with the dt fraction replaced by `fraction`
+    pub(in crate::integration_step) fn with_fraction<G: FractionSpec>(
+        &self,
+        fraction: G,
+    ) -> Variant<G> {
+        match *self {
+            Variant::StartPosition { s_ref } => Variant::StartPosition { s_ref },
+            Variant::VelocityDt {
+                factor,
+                v_ref,
+                dt_fraction: _,
+            } => Variant::VelocityDt {
+                factor,
+                v_ref,
+                dt_fraction: fraction,
             },
-        )
+            Variant::AccelerationDtDt {
+                factor,
+                a_ref,
+                dt_fraction: _,
+            } => Variant::AccelerationDtDt {
+                factor,
+                a_ref,
+                dt_fraction: fraction,
+            },
+        }
     }
 }
 
@@ -98,6 +103,6 @@ impl<const N: usize, const D: usize> std::ops::Add for Variant<DtFraction<N, D>>
     type Output = Collection<N, D>;
 
     fn add(self, rhs: Self) -> Self::Output {
-        vec![self, rhs].into()
+        Collection::of(self) + rhs
     }
 }
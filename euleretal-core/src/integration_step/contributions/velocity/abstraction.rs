@@ -2,12 +2,12 @@ use super::{step::Step, Contribution, Variant};
 use crate::{Fraction, PhysicalQuantityKind, Position, Vec3};
 
 pub struct Abstraction<'a> {
-    step: &'a Step,
+    step: &'a Step<'a>,
     variant: Variant<Fraction>,
 }
 
 impl<'a> Abstraction<'a> {
-    pub fn new(step: &'a Step, variant: Variant<Fraction>) -> Self {
+    pub fn new(step: &'a Step<'a>, variant: Variant<Fraction>) -> Self {
         Self { step, variant }
     }
 }
@@ -1,62 +1,100 @@
-use super::{dt_fraction::DtFraction, step::Step, Contribution, Variant};
+use super::{
+    super::{arena::Span, MAX_CONTRIBUTIONS},
+    dt_fraction::DtFraction,
+    step::{Step, VelocityRef},
+    Contribution, Variant,
+};
 use crate::Fraction;
 
-#[derive(Default)]
-pub struct Collection<const N: usize, const D: usize>(
-    pub(in super::super) Vec<Variant<DtFraction<N, D>>>,
-);
-
+/// The contributions to a velocity which is about to be computed.  Up to `MAX_CONTRIBUTIONS` are
+/// kept inline, such that building them does not allocate.  Integrators with more terms spill
+/// the rest to a vector.  Once computed, they are moved to the `Arena`.
 #[derive(Clone)]
-pub struct Generic {
-    fraction: Fraction,
-    inner: Vec<Variant<DtFraction<1, 1>>>,
+pub struct Collection<const N: usize, const D: usize> {
+    len: usize,
+    variants: [Variant<DtFraction<N, D>>; MAX_CONTRIBUTIONS],
+    /// The contributions after the first `MAX_CONTRIBUTIONS`
+    spilled: Vec<Variant<DtFraction<N, D>>>,
 }
 
-impl<const N: usize, const D: usize> From<Vec<Variant<DtFraction<N, D>>>> for Collection<N, D> {
-    fn from(vec: Vec<Variant<DtFraction<N, D>>>) -> Self {
-        Self(vec)
-    }
+/// The contributions to a computed velocity, stored in the `Arena` of its step.
+#[derive(Clone, Copy)]
+pub struct Generic {
+    fraction: Fraction,
+    span: Span,
 }
 
 impl<const N: usize, const D: usize> Collection<N, D> {
     pub(in crate::integration_step) const fn empty() -> Self {
-        Self(Vec::new())
+        Self {
+            len: 0,
+            variants: [Variant::Velocity {
+                v_ref: VelocityRef::at(0),
+            }; MAX_CONTRIBUTIONS],
+            spilled: Vec::new(),
+        }
     }
 
-    pub(in crate::integration_step) fn generalize(self) -> Generic {
-        Generic {
-            fraction: Fraction::new(N, D),
-            inner: unsafe {
-                ::std::mem::transmute::<
-                    Vec<Variant<DtFraction<N, D>>>,
-                    Vec<Variant<DtFraction<1, 1>>>,
-                >(self.0)
-            },
-        }
+    pub(in crate::integration_step) fn of(variant: Variant<DtFraction<N, D>>) -> Self {
+        Self::empty() + variant
     }
 }
 
 impl<'a, const N: usize, const D: usize> IntoIterator for &'a Collection<N, D> {
     type Item = &'a Variant<DtFraction<N, D>>;
 
-    type IntoIter = std::slice::Iter<'a, Variant<DtFraction<N, D>>>;
+    type IntoIter = std::iter::Chain<
+        std::slice::Iter<'a, Variant<DtFraction<N, D>>>,
+        std::slice::Iter<'a, Variant<DtFraction<N, D>>>,
+    >;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.iter()
+        self.variants[..self.len].iter().chain(&self.spilled)
+    }
+}
+
+impl<const N: usize, const D: usize> std::ops::Add<Variant<DtFraction<N, D>>> for Collection<N, D> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Variant<DtFraction<N, D>>) -> Self::Output {
+        if self.len < MAX_CONTRIBUTIONS {
+            self.variants[self.len] = rhs;
+            self.len += 1;
+        } else {
+            self.spilled.push(rhs);
+        }
+        self
     }
 }
 
 impl Generic {
+    pub(in crate::integration_step) fn new(fraction: Fraction, span: Span) -> Self {
+        Self { fraction, span }
+    }
+
     pub(in crate::integration_step) fn is_empty(&self) -> bool {
-        self.inner.is_empty()
+        self.span.is_empty()
     }
 
-    pub(in crate::integration_step) fn abstraction_iter_for<'a>(
-        &'a self,
+    pub(in crate::integration_step) fn dt_fraction(&self) -> Fraction {
+        self.fraction
+    }
+
+    /// The dt fractions of the variants are meaningless, use `dt_fraction()` instead.
+    pub(in crate::integration_step) fn variants<'a>(
+        &self,
         step: &'a Step,
+    ) -> &'a [Variant<DtFraction<1, 1>>] {
+        step.arena().velocity_contributions(self.span)
+    }
+
+    pub(in crate::integration_step) fn abstraction_iter_for<'a>(
+        &self,
+        step: &'a Step<'a>,
     ) -> Box<dyn Iterator<Item = Box<dyn Contribution + 'a>> + 'a> {
-        Box::new(self.inner.iter().map(|variant| {
-            Box::new(variant.abstraction_scaled_for(step, self.fraction)) as Box<dyn Contribution>
+        let fraction = self.fraction;
+        Box::new(self.variants(step).iter().map(move |variant| {
+            Box::new(variant.abstraction_scaled_for(step, fraction)) as Box<dyn Contribution>
             /*
               Why do we need the cast here, but not in the line above?
               ⟶ https://stackoverflow.com/questions/52288980/how-does-the-mechanism-behind-the-creation-of-boxed-traits-work
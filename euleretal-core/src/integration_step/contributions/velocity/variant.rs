@@ -49,24 +49,29 @@ impl<F: FractionSpec> Variant<F> {
 
     pub fn abstraction_scaled_for<'step>(
         &self,
-        step: &'step Step,
+        step: &'step Step<'step>,
         fraction: Fraction,
     ) -> Abstraction<'step> {
-        Abstraction::new(
-            step,
-            match *self {
-                Variant::Velocity { v_ref } => Variant::Velocity { v_ref },
-                Variant::AccelerationDt {
-                    factor,
-                    a_ref,
-                    dt_fraction: _,
-                } => Variant::AccelerationDt {
-                    factor,
-                    a_ref,
-                    dt_fraction: fraction,
-                },
+        Abstraction::new(step, self.with_fraction(fraction))
+    }
+
+    /// The same variant, but with the dt fraction replaced by `fraction`
+    pub(in crate::integration_step) fn with_fraction<G: FractionSpec>(
+        &self,
+        fraction: G,
+    ) -> Variant<G> {
+        match *self {
+            Variant::Velocity { v_ref } => Variant::Velocity { v_ref },
+            Variant::AccelerationDt {
+                factor,
+                a_ref,
+                dt_fraction: _,
+            } => Variant::AccelerationDt {
+                factor,
+                a_ref,
+                dt_fraction: fraction,
             },
-        )
+        }
     }
 }
 
@@ -74,7 +79,7 @@ impl<const N: usize, const D: usize> std::ops::Add for Variant<DtFraction<N, D>>
     type Output = Collection<N, D>;
 
     fn add(self, rhs: Variant<DtFraction<N, D>>) -> Self::Output {
-        vec![self, rhs].into()
+        Collection::of(self) + rhs
     }
 }
 
@@ -1,14 +1,15 @@
+mod arena;
 pub mod builders;
 pub mod computed;
 mod contributions;
 mod start_condition;
 mod step;
+pub mod symbolic;
 
+pub(crate) use arena::Arena;
 pub use contributions::Contribution;
 pub use start_condition::StartCondition;
-pub use step::Step;
+pub(crate) use step::Header;
+pub use step::{Step, StepMut};
 
-use super::{
-    import,
-    integration_step, // self as integration_step
-};
+use super::integration_step; // self as integration_step
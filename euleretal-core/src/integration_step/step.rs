@@ -1,17 +1,32 @@
 use super::{
+    arena::{Arena, Extent},
     computed, contributions,
     contributions::DtFraction,
-    import::{shape, PointQuery},
     StartCondition,
 };
-use crate::{Acceleration, AccelerationField, Duration, Position, Velocity};
+use crate::{Acceleration, AccelerationSource, Duration, Fraction, Position, Velocity};
+use ::std::borrow::Cow;
 
+/// The quantities of a step are stored in an [`Arena`], which is usually shared with the other
+/// steps of the same `Samples`.  Steps created by `Step::new()` have an arena of their own.
 #[derive(Clone)]
-pub struct Step {
+pub struct Step<'a> {
+    arena: Cow<'a, Arena>,
+    header: Header,
+}
+
+/// A step under construction.  Its quantities are appended to the arena, so it must be the last
+/// step in there.
+pub struct StepMut<'a> {
+    arena: &'a mut Arena,
+    header: &'a mut Header,
+}
+
+/// Everything about a step which is not stored in its arena
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Header {
     dt: Duration,
-    positions: Vec<computed::Position>,
-    velocities: Vec<computed::Velocity>,
-    accelerations: Vec<computed::Acceleration>,
+    extent: Extent,
     last_computed_position: PositionRef,
     last_computed_velocity: VelocityRef,
     acceleration_at_last_position: AccelerationRef,
@@ -20,19 +35,39 @@ pub struct Step {
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct PositionRef(usize);
 
+impl PositionRef {
+    pub(in crate::integration_step) const fn at(index: usize) -> Self {
+        Self(index)
+    }
+
+    pub(in crate::integration_step) const fn index(self) -> usize {
+        self.0
+    }
+}
+
 impl<const N: usize, const D: usize>
     ::std::ops::Add<contributions::position::Variant<DtFraction<N, D>>> for PositionRef
 {
     type Output = contributions::position::Collection<N, D>;
 
     fn add(self, rhs: contributions::position::Variant<DtFraction<N, D>>) -> Self::Output {
-        vec![self.into(), rhs].into()
+        contributions::position::Collection::of(self.into()) + rhs
     }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct VelocityRef(usize);
 
+impl VelocityRef {
+    pub(in crate::integration_step) const fn at(index: usize) -> Self {
+        Self(index)
+    }
+
+    pub(in crate::integration_step) const fn index(self) -> usize {
+        self.0
+    }
+}
+
 impl<const N: usize, const D: usize> ::std::ops::Mul<DtFraction<N, D>> for VelocityRef {
     type Output = contributions::position::Variant<DtFraction<N, D>>;
 
@@ -51,13 +86,23 @@ impl<const N: usize, const D: usize>
     type Output = contributions::velocity::Collection<N, D>;
 
     fn add(self, rhs: contributions::velocity::Variant<DtFraction<N, D>>) -> Self::Output {
-        vec![self.into(), rhs].into()
+        contributions::velocity::Collection::of(self.into()) + rhs
     }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct AccelerationRef(usize);
 
+impl AccelerationRef {
+    pub(in crate::integration_step) const fn at(index: usize) -> Self {
+        Self(index)
+    }
+
+    pub(in crate::integration_step) const fn index(self) -> usize {
+        self.0
+    }
+}
+
 impl<const N: usize, const D: usize> ::std::ops::Mul<DtFraction<N, D>> for AccelerationRef {
     type Output = contributions::velocity::Variant<DtFraction<N, D>>;
 
@@ -88,122 +133,114 @@ pub struct ConditionRef {
     pub a: AccelerationRef,
 }
 
-impl Step {
+impl Header {
+    /// The header of a step which is about to be appended to `arena`
+    pub(crate) fn next_in(arena: &Arena, dt: Duration) -> Self {
+        let extent = arena.next_extent();
+        Self {
+            dt,
+            extent,
+            last_computed_position: PositionRef(extent.positions.start),
+            last_computed_velocity: VelocityRef(extent.velocities.start),
+            acceleration_at_last_position: AccelerationRef(extent.accelerations.start),
+        }
+    }
+
+    /// Discards this step and all steps after it from `arena`.
+    pub(crate) fn truncate_from(mut self, arena: &mut Arena) {
+        arena.truncate(&mut self.extent);
+    }
+
+    /// A copy of `arena` with the steps before this one only
+    pub(crate) fn preceding_in(self, arena: &Arena) -> Arena {
+        arena.prefix(&self.extent)
+    }
+
+    /// The positions of this step in `arena`, like `Step::positions_iter()`
+    pub(crate) fn positions_in(self, arena: &Arena) -> impl Iterator<Item = Position> + '_ {
+        arena
+            .positions(self.extent.positions)
+            .iter()
+            .map(|comp_pos| comp_pos.s)
+    }
+}
+
+impl Step<'static> {
     #[must_use]
     pub fn new(start_condition: &StartCondition, dt: Duration) -> Self {
+        let arena = Arena::default();
         let mut result = Self {
-            dt,
-            positions: Vec::new(),
-            velocities: Vec::new(),
-            accelerations: Vec::new(),
-            last_computed_position: PositionRef::default(),
-            last_computed_velocity: VelocityRef::default(),
-            acceleration_at_last_position: AccelerationRef::default(),
+            header: Header::next_in(&arena, dt),
+            arena: Cow::Owned(arena),
         };
-        result.set_start_condition(start_condition);
+        result.as_mut().set_start_condition(start_condition);
         result
     }
+}
 
-    #[must_use]
-    pub fn create_next(&self) -> Self {
-        let mut next = Self {
-            dt: self.dt,
-            positions: Vec::with_capacity(self.positions.capacity()),
-            velocities: Vec::with_capacity(self.velocities.capacity()),
-            accelerations: Vec::with_capacity(self.accelerations.capacity()),
-            last_computed_position: PositionRef::default(),
-            last_computed_velocity: VelocityRef::default(),
-            acceleration_at_last_position: AccelerationRef::default(),
-        };
-        next.set_start_condition(&self.next_condition());
-        next
+impl<'a> Step<'a> {
+    pub(crate) fn in_arena(arena: &'a Arena, header: Header) -> Self {
+        Self {
+            arena: Cow::Borrowed(arena),
+            header,
+        }
     }
 
-    pub fn raw_end_condition(&mut self, s: Position, v: Velocity, a: Acceleration) {
-        let p_ref = self.add_computed_position(
-            s,
-            DtFraction::<1, 1>,
-            contributions::position::Collection::empty(),
-        );
-        self.add_computed_velocity(
-            v,
-            p_ref,
-            DtFraction::<1, 1>,
-            contributions::velocity::Collection::empty(),
-        );
-        self.acceleration_at_last_position = self.add_computed_acceleration(a, p_ref);
+    /// A view of the step which borrows its arena
+    #[must_use]
+    pub fn view(&self) -> Step<'_> {
+        Step::in_arena(&self.arena, self.header)
     }
 
-    pub fn set_start_condition(&mut self, p: &StartCondition) -> ConditionRef {
-        let sref = self.add_computed_position(
-            p.position(),
-            DtFraction::<0, 1>,
-            contributions::position::Collection::empty(),
-        );
-        ConditionRef {
-            s: sref,
-            v: self.add_computed_velocity(
-                p.velocity(),
-                sref,
-                DtFraction::<0, 1>,
-                contributions::velocity::Collection::empty(),
-            ),
-            a: self.add_computed_acceleration(p.acceleration(), sref),
+    /// Note that this copies the arena if it is shared with other steps.
+    pub fn as_mut(&mut self) -> StepMut<'_> {
+        StepMut {
+            arena: self.arena.to_mut(),
+            header: &mut self.header,
         }
     }
 
+    #[must_use]
+    pub fn create_next(&self) -> Step<'static> {
+        Step::new(&self.next_condition(), self.header.dt)
+    }
+
     #[must_use]
     pub fn next_condition(&self) -> StartCondition {
         StartCondition::new(
-            self[self.last_computed_position].s,
-            self[self.last_computed_velocity].v,
-            self[self.acceleration_at_last_position].a,
+            self[self.header.last_computed_position].s,
+            self[self.header.last_computed_velocity].v,
+            self[self.header.acceleration_at_last_position].a,
         )
     }
 
-    pub fn compute_acceleration_at_last_position(&mut self, a: &dyn AccelerationField) {
-        let last_pref = self.last_computed_position;
-        self.acceleration_at_last_position =
-            self.add_computed_acceleration(a.value_at(self[last_pref].s), last_pref);
-    }
-
     #[must_use]
     pub fn dt(&self) -> Duration {
-        self.dt
+        self.header.dt
     }
 
     #[must_use]
     pub fn last_computed_position(&self) -> computed::position::Abstraction {
-        self[self.last_computed_position].abstraction_for(self)
+        self[self.header.last_computed_position].abstraction_for(self)
     }
 
     #[must_use]
     pub fn last_computed_velocity(&self) -> computed::velocity::Abstraction {
-        self[self.last_computed_velocity].abstraction_for(self)
+        self[self.header.last_computed_velocity].abstraction_for(self)
     }
 
     #[must_use]
     pub fn last_s(&self) -> Position {
-        self[self.last_computed_position].s
+        self[self.header.last_computed_position].s
     }
 
     #[must_use]
     pub fn last_v(&self) -> Velocity {
-        self[self.last_computed_velocity].v
+        self[self.header.last_computed_velocity].v
     }
 
     pub fn positions_iter(&self) -> impl Iterator<Item = Position> + '_ {
-        self.positions.iter().map(|comp_pos| comp_pos.s)
-    }
-
-    #[allow(clippy::missing_panics_doc)] // not expected to panic because the iterator will not be empty
-    #[must_use]
-    pub fn distance_to(&self, pos: &Position) -> f32 {
-        shape::Segment::new(
-            self.positions_iter().next().unwrap().into(),
-            self.positions_iter().last().unwrap().into(), // next() can be same as last()
-        )
-        .distance_to_local_point(pos.as_point(), true)
+        self.positions().iter().map(|comp_pos| comp_pos.s)
     }
 
     #[allow(clippy::missing_panics_doc)] // not expected to panic because the iterator will not be empty
@@ -213,7 +250,8 @@ impl Step {
         pos: impl Into<Position>,
     ) -> computed::velocity::Abstraction {
         let pos = pos.into();
-        self.velocities
+        self.arena
+            .velocities(self.header.extent.velocities)
             .iter()
             .filter(|v| v.has_contributions()) // no predecessor → not 'computed'
             .map(|v| (v, self[v.sampling_position].s.distance_squared(pos)))
@@ -236,7 +274,7 @@ impl Step {
         pos: impl Into<Position>,
     ) -> computed::position::Abstraction {
         let pos = pos.into();
-        self.positions
+        self.positions()
             .iter()
             .filter(|p| p.has_contributions()) // no predecessor → not 'computed'
             .map(|p| (p, p.s.distance_squared(pos)))
@@ -252,12 +290,141 @@ impl Step {
             .abstraction_for(self)
     }
 
+    pub(super) fn arena(&self) -> &Arena {
+        &self.arena
+    }
+
     pub(super) fn last_position_ref(&self) -> PositionRef {
-        PositionRef(self.positions.len() - 1)
+        self.header.last_position_ref()
     }
 
     pub(super) fn last_velocity_ref(&self) -> VelocityRef {
-        VelocityRef(self.velocities.len() - 1)
+        self.header.last_velocity_ref()
+    }
+
+    fn positions(&self) -> &[computed::Position] {
+        self.arena.positions(self.header.extent.positions)
+    }
+
+    #[must_use]
+    pub fn get_start_condition(&self) -> StartCondition {
+        let extent = self.header.extent;
+        StartCondition::new(
+            self[PositionRef(extent.positions.start)].s,
+            self[VelocityRef(extent.velocities.start)].v,
+            self[AccelerationRef(extent.accelerations.start)].a,
+        )
+    }
+}
+
+impl Header {
+    fn last_position_ref(&self) -> PositionRef {
+        PositionRef(self.extent.positions.end - 1)
+    }
+
+    fn last_velocity_ref(&self) -> VelocityRef {
+        VelocityRef(self.extent.velocities.end - 1)
+    }
+}
+
+impl<'a> StepMut<'a> {
+    /// Starts a new step at the end of `arena`.
+    pub(crate) fn start(
+        arena: &'a mut Arena,
+        header: &'a mut Header,
+        start_condition: &StartCondition,
+    ) -> Self {
+        let mut result = Self { arena, header };
+        result.set_start_condition(start_condition);
+        result
+    }
+
+    /// A read-only view of the step
+    #[must_use]
+    pub fn view(&self) -> Step<'_> {
+        Step::in_arena(self.arena, *self.header)
+    }
+
+    #[must_use]
+    pub fn dt(&self) -> Duration {
+        self.header.dt
+    }
+
+    #[must_use]
+    pub fn next_condition(&self) -> StartCondition {
+        self.view().next_condition()
+    }
+
+    pub(super) fn start_values(&self) -> ConditionRef {
+        let extent = self.header.extent;
+        ConditionRef {
+            s: PositionRef(extent.positions.start),
+            v: VelocityRef(extent.velocities.start),
+            a: AccelerationRef(extent.accelerations.start),
+        }
+    }
+
+    /// Discards all quantities but the start and end conditions, e.g. after a step has been
+    /// computed without contributions.
+    pub(crate) fn keep_start_and_end_only(&mut self) {
+        let (start, end) = (self.view().get_start_condition(), self.next_condition());
+        self.arena.truncate(&mut self.header.extent);
+        self.set_start_condition(&start);
+        self.raw_end_condition(end.position(), end.velocity(), end.acceleration());
+    }
+
+    pub fn raw_end_condition(&mut self, s: Position, v: Velocity, a: Acceleration) {
+        let p_ref = self.add_computed_position(
+            s,
+            DtFraction::<1, 1>,
+            &contributions::position::Collection::empty(),
+        );
+        self.add_computed_velocity(
+            v,
+            p_ref,
+            DtFraction::<1, 1>,
+            &contributions::velocity::Collection::empty(),
+        );
+        self.header.acceleration_at_last_position = self.add_computed_acceleration(a, p_ref);
+    }
+
+    pub fn set_start_condition(&mut self, p: &StartCondition) -> ConditionRef {
+        let sref = self.add_computed_position(
+            p.position(),
+            DtFraction::<0, 1>,
+            &contributions::position::Collection::empty(),
+        );
+        let result = ConditionRef {
+            s: sref,
+            v: self.add_computed_velocity(
+                p.velocity(),
+                sref,
+                DtFraction::<0, 1>,
+                &contributions::velocity::Collection::empty(),
+            ),
+            a: self.add_computed_acceleration(p.acceleration(), sref),
+        };
+        self.header.acceleration_at_last_position = result.a;
+        result
+    }
+
+    /// `sample` numbers the acceleration within the step, see `AccelerationSource`.
+    pub fn compute_acceleration_at_last_position(
+        &mut self,
+        a: &dyn AccelerationSource,
+        sample: usize,
+    ) {
+        let last_pref = self.header.last_computed_position;
+        self.header.acceleration_at_last_position =
+            self.add_computed_acceleration(a.acceleration_at(self[last_pref].s, sample), last_pref);
+    }
+
+    pub(super) fn last_position_ref(&self) -> PositionRef {
+        self.header.last_position_ref()
+    }
+
+    pub(super) fn last_velocity_ref(&self) -> VelocityRef {
+        self.header.last_velocity_ref()
     }
 
     /// parameter DtFraction<N,D> improves readability at calling positions
@@ -265,12 +432,34 @@ impl Step {
         &mut self,
         s: Position,
         _dt_fraction: DtFraction<N, D>,
-        contributions: contributions::position::Collection<N, D>,
+        contributions: &contributions::position::Collection<N, D>,
+    ) -> PositionRef {
+        self.add_generic_position(
+            s,
+            Fraction::new(N, D),
+            contributions
+                .into_iter()
+                .map(|variant| variant.with_fraction(DtFraction)),
+        )
+    }
+
+    /// The dt fractions of `contributions` are ignored in favour of `dt_fraction`.
+    pub(super) fn add_generic_position(
+        &mut self,
+        s: Position,
+        dt_fraction: Fraction,
+        contributions: impl IntoIterator<Item = contributions::position::Variant<DtFraction<1, 1>>>,
     ) -> PositionRef {
-        let p_ref = PositionRef(self.positions.len());
-        self.positions
-            .push(computed::Position::new(s, contributions));
-        self.last_computed_position = p_ref;
+        let contributions = self.arena.add_position_contributions(
+            dt_fraction,
+            contributions,
+            &mut self.header.extent,
+        );
+        let p_ref = self.arena.add_position(
+            computed::Position::new(s, contributions),
+            &mut self.header.extent,
+        );
+        self.header.last_computed_position = p_ref;
         p_ref
     }
 
@@ -280,12 +469,36 @@ impl Step {
         v: Velocity,
         sampling_position: PositionRef,
         _dt_fraction: DtFraction<N, D>,
-        contributions: contributions::velocity::Collection<N, D>,
+        contributions: &contributions::velocity::Collection<N, D>,
+    ) -> VelocityRef {
+        self.add_generic_velocity(
+            v,
+            sampling_position,
+            Fraction::new(N, D),
+            contributions
+                .into_iter()
+                .map(|variant| variant.with_fraction(DtFraction)),
+        )
+    }
+
+    /// The dt fractions of `contributions` are ignored in favour of `dt_fraction`.
+    pub(super) fn add_generic_velocity(
+        &mut self,
+        v: Velocity,
+        sampling_position: PositionRef,
+        dt_fraction: Fraction,
+        contributions: impl IntoIterator<Item = contributions::velocity::Variant<DtFraction<1, 1>>>,
     ) -> VelocityRef {
-        let v_ref = VelocityRef(self.velocities.len());
-        self.velocities
-            .push(computed::Velocity::new(v, sampling_position, contributions));
-        self.last_computed_velocity = v_ref;
+        let contributions = self.arena.add_velocity_contributions(
+            dt_fraction,
+            contributions,
+            &mut self.header.extent,
+        );
+        let v_ref = self.arena.add_velocity(
+            computed::Velocity::new(v, sampling_position, contributions),
+            &mut self.header.extent,
+        );
+        self.header.last_computed_velocity = v_ref;
         v_ref
     }
 
@@ -294,62 +507,81 @@ impl Step {
         a: Acceleration,
         sampling_position: PositionRef,
     ) -> AccelerationRef {
-        let a_ref = AccelerationRef(self.accelerations.len());
-        self.accelerations.push(computed::Acceleration {
-            a,
-            sampling_position,
-        });
-        a_ref
+        self.arena.add_acceleration(
+            computed::Acceleration {
+                a,
+                sampling_position,
+            },
+            &mut self.header.extent,
+        )
+    }
+}
+
+impl<'a, 'b> From<&'a mut Step<'b>> for StepMut<'a> {
+    fn from(step: &'a mut Step<'b>) -> Self {
+        step.as_mut()
     }
+}
 
-    #[must_use]
-    pub fn get_start_condition(&self) -> StartCondition {
-        StartCondition::new(
-            self.positions[0].s,
-            self.velocities[0].v,
-            self.accelerations[0].a,
-        )
+impl<'a, 'b> From<&'a mut StepMut<'b>> for StepMut<'a> {
+    fn from(step: &'a mut StepMut<'b>) -> Self {
+        StepMut {
+            arena: step.arena,
+            header: step.header,
+        }
     }
 }
 
-impl ::std::ops::Index<AccelerationRef> for Step {
+impl ::std::ops::Index<AccelerationRef> for Step<'_> {
     type Output = computed::Acceleration;
 
     fn index(&self, a_ref: AccelerationRef) -> &Self::Output {
-        &self.accelerations[a_ref.0]
+        &self.arena[a_ref]
     }
 }
 
-impl ::std::ops::IndexMut<AccelerationRef> for Step {
-    fn index_mut(&mut self, a_ref: AccelerationRef) -> &mut Self::Output {
-        &mut self.accelerations[a_ref.0]
+impl ::std::ops::Index<PositionRef> for Step<'_> {
+    type Output = computed::Position;
+
+    fn index(&self, p_ref: PositionRef) -> &Self::Output {
+        &self.arena[p_ref]
     }
 }
 
-impl ::std::ops::Index<PositionRef> for Step {
-    type Output = computed::Position;
+impl ::std::ops::Index<VelocityRef> for Step<'_> {
+    type Output = computed::Velocity;
 
-    fn index(&self, p_ref: PositionRef) -> &Self::Output {
-        &self.positions[p_ref.0]
+    fn index(&self, v_ref: VelocityRef) -> &Self::Output {
+        &self.arena[v_ref]
+    }
+}
+
+impl ::std::ops::Index<AccelerationRef> for StepMut<'_> {
+    type Output = computed::Acceleration;
+
+    fn index(&self, a_ref: AccelerationRef) -> &Self::Output {
+        &self.arena[a_ref]
     }
 }
 
-impl ::std::ops::IndexMut<PositionRef> for Step {
-    fn index_mut(&mut self, p_ref: PositionRef) -> &mut Self::Output {
-        &mut self.positions[p_ref.0]
+impl ::std::ops::Index<PositionRef> for StepMut<'_> {
+    type Output = computed::Position;
+
+    fn index(&self, p_ref: PositionRef) -> &Self::Output {
+        &self.arena[p_ref]
     }
 }
 
-impl ::std::ops::Index<VelocityRef> for Step {
+impl ::std::ops::Index<VelocityRef> for StepMut<'_> {
     type Output = computed::Velocity;
 
     fn index(&self, v_ref: VelocityRef) -> &Self::Output {
-        &self.velocities[v_ref.0]
+        &self.arena[v_ref]
     }
 }
 
-impl ::std::ops::IndexMut<VelocityRef> for Step {
+impl ::std::ops::IndexMut<VelocityRef> for StepMut<'_> {
     fn index_mut(&mut self, v_ref: VelocityRef) -> &mut Self::Output {
-        &mut self.velocities[v_ref.0]
+        &mut self.arena[v_ref]
     }
 }
@@ -0,0 +1,370 @@
+//! Symbolic evaluation of integrators.
+//!
+//! The integrator computes a single step on a builder which records the order of computation.  The
+//! numbers are meaningless, but the contributions of each computed quantity form its expression
+//! tree.  The resulting [`Trace`] is the canonical description of the integrator, independent of
+//! its hand-written [`Integrator::description()`].
+
+use super::{
+    builders, contributions,
+    step::{AccelerationRef, PositionRef, VelocityRef},
+    StartCondition, Step,
+};
+use crate::{Acceleration, AccelerationSource, Fraction, Integrator, Position, Velocity};
+
+/// A quantity computed by the builder, in the order of computation.
+#[derive(Clone, Copy, Debug)]
+pub enum Computed {
+    Position(PositionRef),
+    Velocity(VelocityRef),
+    Acceleration(AccelerationRef),
+}
+
+/// A quantity of the step: `0` is the start value, `n` is the `n`-th computed quantity of that
+/// kind.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symbol {
+    Position(usize),
+    Velocity(usize),
+    Acceleration(usize),
+}
+
+/// `factor · symbol · (dt_fraction · dt)^dt_power`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Term {
+    pub factor: f32,
+    pub symbol: Symbol,
+    pub dt_fraction: Fraction,
+    pub dt_power: u8,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    Sum(Vec<Term>),
+    /// The acceleration field, sampled at a position
+    AccelerationAt(Symbol),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Assignment {
+    pub target: Symbol,
+    pub expression: Expression,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trace {
+    pub assignments: Vec<Assignment>,
+    /// number of the resulting position, which is written `s'`
+    last_position: usize,
+    /// number of the resulting velocity, which is written `v'`
+    last_velocity: usize,
+}
+
+#[derive(Clone, Copy)]
+enum Notation {
+    Unicode,
+    Latex,
+}
+
+/// The values of the acceleration do not matter for symbolic evaluation.
+struct Unevaluated;
+
+impl AccelerationSource for Unevaluated {
+    fn acceleration_at(&self, _pos: Position, _sample: usize) -> Acceleration {
+        Acceleration::zeros()
+    }
+}
+
+impl Trace {
+    #[must_use]
+    pub fn of(integrator: &dyn Integrator) -> Self {
+        let start_condition =
+            StartCondition::new(Position::origin(), Velocity::zeros(), Acceleration::zeros());
+        let mut step = Step::new(&start_condition, 1.0.into());
+        let mut builder = builders::Step::new_traced(&Unevaluated, &mut step);
+        let ((s, v, a), dt) = (builder.start_values(), builder.dt());
+        integrator.integrate_step(s, v, a, dt, &mut builder);
+        let assignments = builder
+            .into_trace()
+            .into_iter()
+            .map(|computed| assignment_of(&step, computed))
+            .collect();
+        Self {
+            assignments,
+            last_position: step.last_position_ref().index(),
+            last_velocity: step.last_velocity_ref().index(),
+        }
+    }
+
+    /// The resulting position of the step, i.e. `s'`.
+    #[must_use]
+    pub fn result_position(&self) -> Symbol {
+        Symbol::Position(self.last_position)
+    }
+
+    /// The resulting velocity of the step, i.e. `v'`.
+    #[must_use]
+    pub fn result_velocity(&self) -> Symbol {
+        Symbol::Velocity(self.last_velocity)
+    }
+
+    /// One line per computed quantity, e.g. `v' = v + a dt`.
+    #[must_use]
+    pub fn to_unicode(&self) -> String {
+        self.assignments
+            .iter()
+            .map(|assignment| {
+                format!(
+                    "{} = {}",
+                    self.name(assignment.target, Notation::Unicode),
+                    self.format_expression(&assignment.expression, Notation::Unicode)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// An `aligned` environment with one row per computed quantity.
+    #[must_use]
+    pub fn to_latex(&self) -> String {
+        let rows = self
+            .assignments
+            .iter()
+            .map(|assignment| {
+                format!(
+                    "{} &= {}",
+                    self.name(assignment.target, Notation::Latex),
+                    self.format_expression(&assignment.expression, Notation::Latex)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" \\\\\n");
+        format!("\\begin{{aligned}}\n{}\n\\end{{aligned}}", rows)
+    }
+
+    fn name(&self, symbol: Symbol, notation: Notation) -> String {
+        const SUBSCRIPTS: [char; 10] = ['₀', '₁', '₂', '₃', '₄', '₅', '₆', '₇', '₈', '₉'];
+        let (letter, number, last) = match symbol {
+            Symbol::Position(number) => ("s", number, self.last_position),
+            Symbol::Velocity(number) => ("v", number, self.last_velocity),
+            Symbol::Acceleration(number) => ("a", number, usize::MAX),
+        };
+        if number == 0 {
+            letter.to_string()
+        } else if number == last {
+            format!("{}'", letter)
+        } else {
+            match notation {
+                Notation::Unicode => {
+                    let subscript = number
+                        .to_string()
+                        .chars()
+                        .filter_map(|digit| digit.to_digit(10))
+                        .map(|digit| SUBSCRIPTS[digit as usize])
+                        .collect::<String>();
+                    format!("{}{}", letter, subscript)
+                }
+                Notation::Latex => format!("{}_{{{}}}", letter, number),
+            }
+        }
+    }
+
+    fn format_expression(&self, expression: &Expression, notation: Notation) -> String {
+        match expression {
+            Expression::AccelerationAt(position) => {
+                format!("a({})", self.name(*position, notation))
+            }
+            Expression::Sum(terms) => {
+                let mut result = String::new();
+                for (n, term) in terms.iter().enumerate() {
+                    let sign = match (n, term.factor < 0.) {
+                        (0, false) => "",
+                        (0, true) => "-",
+                        (_, false) => " + ",
+                        (_, true) => " - ",
+                    };
+                    result += sign;
+                    result += &format_factor(term.factor.abs(), notation);
+                    result += &self.name(term.symbol, notation);
+                    result += &format_dt(term.dt_fraction, term.dt_power, notation);
+                }
+                result
+            }
+        }
+    }
+}
+
+fn assignment_of(step: &Step, computed: Computed) -> Assignment {
+    match computed {
+        Computed::Position(s_ref) => {
+            let contributions = &step[s_ref].contributions;
+            let dt_fraction = contributions.dt_fraction();
+            let terms = contributions
+                .variants(step)
+                .iter()
+                .map(|variant| match *variant {
+                    contributions::position::Variant::StartPosition { s_ref } => Term {
+                        factor: 1.,
+                        symbol: Symbol::Position(s_ref.index()),
+                        dt_fraction,
+                        dt_power: 0,
+                    },
+                    contributions::position::Variant::VelocityDt { factor, v_ref, .. } => Term {
+                        factor,
+                        symbol: Symbol::Velocity(v_ref.index()),
+                        dt_fraction,
+                        dt_power: 1,
+                    },
+                    contributions::position::Variant::AccelerationDtDt {
+                        factor, a_ref, ..
+                    } => Term {
+                        factor,
+                        symbol: Symbol::Acceleration(a_ref.index()),
+                        dt_fraction,
+                        dt_power: 2,
+                    },
+                })
+                .collect();
+            Assignment {
+                target: Symbol::Position(s_ref.index()),
+                expression: Expression::Sum(terms),
+            }
+        }
+        Computed::Velocity(v_ref) => {
+            let contributions = &step[v_ref].contributions;
+            let dt_fraction = contributions.dt_fraction();
+            let terms = contributions
+                .variants(step)
+                .iter()
+                .map(|variant| match *variant {
+                    contributions::velocity::Variant::Velocity { v_ref } => Term {
+                        factor: 1.,
+                        symbol: Symbol::Velocity(v_ref.index()),
+                        dt_fraction,
+                        dt_power: 0,
+                    },
+                    contributions::velocity::Variant::AccelerationDt { factor, a_ref, .. } => {
+                        Term {
+                            factor,
+                            symbol: Symbol::Acceleration(a_ref.index()),
+                            dt_fraction,
+                            dt_power: 1,
+                        }
+                    }
+                })
+                .collect();
+            Assignment {
+                target: Symbol::Velocity(v_ref.index()),
+                expression: Expression::Sum(terms),
+            }
+        }
+        Computed::Acceleration(a_ref) => Assignment {
+            target: Symbol::Acceleration(a_ref.index()),
+            expression: Expression::AccelerationAt(Symbol::Position(
+                step[a_ref].sampling_position.index(),
+            )),
+        },
+    }
+}
+
+/// Formats the absolute value of a factor, including the separating space.
+fn format_factor(factor: f32, notation: Notation) -> String {
+    #[allow(clippy::float_cmp)]
+    if factor == 1. {
+        String::new()
+    } else if factor == 0.5 {
+        match notation {
+            Notation::Unicode => "½ ".to_string(),
+            Notation::Latex => "\\tfrac{1}{2}\\,".to_string(),
+        }
+    } else {
+        match notation {
+            Notation::Unicode => format!("{} ", factor),
+            Notation::Latex => format!("{}\\,", factor),
+        }
+    }
+}
+
+/// Formats `(dt_fraction · dt)^power`, including the separating space.
+fn format_dt(dt_fraction: Fraction, power: u8, notation: Notation) -> String {
+    let dt = match (notation, dt_fraction.numerator(), dt_fraction.denominator()) {
+        (Notation::Unicode, 1, 1) => "dt".to_string(),
+        (Notation::Unicode, 1, 2) => "½dt".to_string(),
+        (Notation::Unicode, n, 1) => format!("{}dt", n),
+        (Notation::Unicode, n, d) => format!("({}/{})dt", n, d),
+        (Notation::Latex, 1, 1) => "\\mathrm{d}t".to_string(),
+        (Notation::Latex, n, 1) => format!("{}\\,\\mathrm{{d}}t", n),
+        (Notation::Latex, n, d) => format!("\\tfrac{{{}}}{{{}}}\\mathrm{{d}}t", n, d),
+    };
+    let whole = dt_fraction.numerator() == 1 && dt_fraction.denominator() == 1;
+    match (notation, power) {
+        (_, 0) => String::new(),
+        (Notation::Unicode, 1) => format!(" {}", dt),
+        (Notation::Latex, 1) => format!("\\,{}", dt),
+        (Notation::Unicode, 2) if whole => format!(" {}²", dt),
+        (Notation::Unicode, 2) => format!(" ({})²", dt),
+        (Notation::Unicode, power) => format!(" ({})^{}", dt, power),
+        (Notation::Latex, power) if whole => format!("\\,{}^{}", dt, power),
+        (Notation::Latex, power) => format!("\\,({})^{}", dt, power),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrators::{euler, exact_for_const, mid_point};
+
+    /// The lines which define a quantity, with normalized white space.  Continuation lines (e.g.
+    /// `    = s + v dt + a dt²`) are ignored, as they merely expand a definition.
+    fn definitions(description: &str) -> Vec<String> {
+        description
+            .lines()
+            .filter(|line| !line.starts_with(char::is_whitespace))
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn hand_written_descriptions_agree() {
+        let integrators: [&dyn Integrator; 5] = [
+            &euler::Broken,
+            &euler::Euler,
+            &exact_for_const::ExactForConst,
+            &mid_point::Euler,
+            &mid_point::SecondOrder,
+        ];
+        for integrator in integrators {
+            assert_eq!(
+                definitions(&integrator.description()),
+                definitions(&Trace::of(integrator).to_unicode()),
+                "hand-written description of '{}' disagrees with its implementation",
+                integrator.label()
+            );
+        }
+    }
+
+    #[test]
+    fn second_order_unicode() {
+        assert_eq!(
+            Trace::of(&mid_point::SecondOrder).to_unicode(),
+            "s₁ = s + v ½dt + ½ a (½dt)²\n\
+             a₁ = a(s₁)\n\
+             s' = s + v dt + ½ a₁ dt²\n\
+             v' = v + a₁ dt"
+        );
+    }
+
+    #[test]
+    fn mid_point_euler_latex() {
+        assert_eq!(
+            Trace::of(&mid_point::Euler).to_latex(),
+            "\\begin{aligned}\n\
+             v_{1} &= v + a\\,\\tfrac{1}{2}\\mathrm{d}t \\\\\n\
+             s_{1} &= s + v_{1}\\,\\tfrac{1}{2}\\mathrm{d}t \\\\\n\
+             a_{1} &= a(s_{1}) \\\\\n\
+             v' &= v + a_{1}\\,\\mathrm{d}t \\\\\n\
+             s' &= s + v'\\,\\mathrm{d}t\n\
+             \\end{aligned}"
+        );
+    }
+}
@@ -1,11 +1,60 @@
 use super::integration_step::builders;
 use ::std::{any::TypeId, collections::hash_map::DefaultHasher, fmt::Debug, hash::Hash};
 
+/// Structured properties of an integrator, e.g. for grouping and sorting.  `None` means unknown.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metadata {
+    /// The order `p` of the integrator, as claimed by it: the global error after a fixed time is
+    /// `O(dtᵖ)`, as the local error of each step is `O(dtᵖ⁺¹)`.  This is checked against
+    /// `truncation::Analysis::order()`.
+    pub order: Option<usize>,
+    /// Samples of the acceleration field per step, including the one at the start position (which
+    /// is shared with the previous step).
+    pub samples_per_step: usize,
+    pub explicit: bool,
+    pub symplectic: Option<bool>,
+    pub time_reversible: Option<bool>,
+    pub reference: Option<&'static str>,
+}
+
+impl ::std::fmt::Display for Metadata {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        let yes_no = |property: Option<bool>| match property {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "unknown",
+        };
+        match self.order {
+            Some(order) => writeln!(f, "Order: {}", crate::truncation::format_order(order))?,
+            None => writeln!(f, "Order: unknown")?,
+        }
+        writeln!(f, "Samples per step: {}", self.samples_per_step)?;
+        writeln!(f, "Explicit: {}", yes_no(Some(self.explicit)))?;
+        writeln!(f, "Symplectic: {}", yes_no(self.symplectic))?;
+        write!(f, "Time-reversible: {}", yes_no(self.time_reversible))?;
+        if let Some(reference) = self.reference {
+            write!(f, "\nReference: {}", reference)?;
+        }
+        Ok(())
+    }
+}
+
 pub trait Integrator: Debug + Send + Sync + 'static {
     fn label(&self) -> String;
 
     fn description(&self) -> String;
 
+    fn metadata(&self) -> Metadata;
+
+    /// Checks if the integrator is well-defined.  Only valid integrators may be integrated.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first error found.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
     fn integrate_step(
         &self,
         s0: builders::Position,
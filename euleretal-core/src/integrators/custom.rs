@@ -0,0 +1,428 @@
+use crate::{
+    integration_step::{builders, symbolic::Trace},
+    Fraction, Integrator, Metadata,
+};
+use ::std::{
+    any::TypeId,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// An integrator which is composed at runtime (e.g. by the user) from an ordered list of stages.
+///
+/// Quantities are referred to by number: `0` is the start value of the step (`s`, `v`, or `a`),
+/// and `n` is the result of the `n`-th stage which computes that kind of quantity.  The last
+/// computed position and velocity are the result of the step.
+#[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+pub struct Custom {
+    pub label: String,
+    pub stages: Vec<Stage>,
+}
+
+#[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+pub enum Stage {
+    /// `aₙ = a(s_position)`
+    Acceleration { position: usize },
+    /// `sₙ = s_base + Σ factor·v·(dt_fraction·dt) + Σ factor·a·(dt_fraction·dt)²`
+    Position {
+        dt_fraction: (usize, usize),
+        base: usize,
+        velocity_terms: Vec<Term>,
+        acceleration_terms: Vec<Term>,
+    },
+    /// `vₙ = v_base + Σ factor·a·(dt_fraction·dt)`
+    Velocity {
+        dt_fraction: (usize, usize),
+        base: usize,
+        acceleration_terms: Vec<Term>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+pub struct Term {
+    pub factor: f32,
+    pub quantity: usize,
+}
+
+impl Default for Custom {
+    /// Explicit Euler, as a starting point for editing.
+    fn default() -> Self {
+        Self {
+            label: "Custom".to_string(),
+            stages: vec![
+                Stage::Position {
+                    dt_fraction: (1, 1),
+                    base: 0,
+                    velocity_terms: vec![Term::new(0)],
+                    acceleration_terms: Vec::new(),
+                },
+                Stage::Velocity {
+                    dt_fraction: (1, 1),
+                    base: 0,
+                    acceleration_terms: vec![Term::new(0)],
+                },
+            ],
+        }
+    }
+}
+
+impl Term {
+    #[must_use]
+    pub fn new(quantity: usize) -> Self {
+        Self {
+            factor: 1.,
+            quantity,
+        }
+    }
+}
+
+impl Hash for Term {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.factor.to_bits().hash(state);
+        self.quantity.hash(state);
+    }
+}
+
+impl Hash for Stage {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ::std::mem::discriminant(self).hash(state);
+        match self {
+            Stage::Acceleration { position } => position.hash(state),
+            Stage::Position {
+                dt_fraction,
+                base,
+                velocity_terms,
+                acceleration_terms,
+            } => {
+                dt_fraction.hash(state);
+                base.hash(state);
+                velocity_terms.hash(state);
+                acceleration_terms.hash(state);
+            }
+            Stage::Velocity {
+                dt_fraction,
+                base,
+                acceleration_terms,
+            } => {
+                dt_fraction.hash(state);
+                base.hash(state);
+                acceleration_terms.hash(state);
+            }
+        }
+    }
+}
+
+/// Names of all quantities, as used in the description: `s`, `s₁`, `s₂`, …, and `s'` for the
+/// resulting position (accordingly for velocities and accelerations).
+pub struct QuantityNames {
+    pub positions: Vec<String>,
+    pub velocities: Vec<String>,
+    pub accelerations: Vec<String>,
+}
+
+/// Numbers of the quantities available at some stage (including the start values).
+#[derive(Clone, Copy)]
+pub struct QuantityCounts {
+    pub positions: usize,
+    pub velocities: usize,
+    pub accelerations: usize,
+}
+
+impl Custom {
+    /// Returns the numbers of quantities available to each stage, and after the last stage.
+    #[must_use]
+    pub fn quantity_counts(&self) -> Vec<QuantityCounts> {
+        let mut counts = QuantityCounts {
+            positions: 1,
+            velocities: 1,
+            accelerations: 1,
+        };
+        let mut result = vec![counts];
+        for stage in &self.stages {
+            match stage {
+                Stage::Acceleration { .. } => counts.accelerations += 1,
+                Stage::Position { .. } => counts.positions += 1,
+                Stage::Velocity { .. } => counts.velocities += 1,
+            }
+            result.push(counts);
+        }
+        result
+    }
+
+    #[allow(clippy::missing_panics_doc)] // not expected to panic because `counts` is never empty
+    #[must_use]
+    pub fn quantity_names(&self) -> QuantityNames {
+        let total = *self.quantity_counts().last().unwrap();
+        let names = |symbol, count: usize, last| {
+            (0..count)
+                .map(|quantity| quantity_name(symbol, quantity, last))
+                .collect()
+        };
+        QuantityNames {
+            positions: names("s", total.positions, total.positions - 1),
+            velocities: names("v", total.velocities, total.velocities - 1),
+            accelerations: names("a", total.accelerations, usize::MAX),
+        }
+    }
+}
+
+impl Integrator for Custom {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    /// The validation error, if the stages are invalid.
+    fn description(&self) -> String {
+        self.validate()
+            .map_or_else(|err| err, |()| Trace::of(self).to_unicode())
+    }
+
+    /// Only the properties which are obvious from the stages are known.
+    fn metadata(&self) -> Metadata {
+        let acceleration_stages = self
+            .stages
+            .iter()
+            .filter(|stage| matches!(stage, Stage::Acceleration { .. }))
+            .count();
+        Metadata {
+            order: None,
+            samples_per_step: 1 + acceleration_stages,
+            explicit: true, // stages can only refer to previous stages
+            symplectic: None,
+            time_reversible: None,
+            reference: None,
+        }
+    }
+
+    /// Checks that all stages refer to quantities computed by previous stages only, and that the
+    /// step computes a position and a velocity.
+    fn validate(&self) -> Result<(), String> {
+        let counts = self.quantity_counts();
+        for (n, (stage, available)) in self.stages.iter().zip(&counts).enumerate() {
+            let check = |quantity: usize, count: usize, name: &str| {
+                if quantity < count {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Stage {}: {} is not computed by a previous stage.",
+                        n + 1,
+                        quantity_name(name, quantity, usize::MAX)
+                    ))
+                }
+            };
+            let check_denominator = |(_, denominator): (usize, usize)| {
+                if denominator == 0 {
+                    Err(format!(
+                        "Stage {}: dt fraction must not divide by 0.",
+                        n + 1
+                    ))
+                } else {
+                    Ok(())
+                }
+            };
+            match stage {
+                Stage::Acceleration { position } => check(*position, available.positions, "s")?,
+                Stage::Position {
+                    dt_fraction,
+                    base,
+                    velocity_terms,
+                    acceleration_terms,
+                } => {
+                    check_denominator(*dt_fraction)?;
+                    check(*base, available.positions, "s")?;
+                    for term in velocity_terms {
+                        check(term.quantity, available.velocities, "v")?;
+                    }
+                    for term in acceleration_terms {
+                        check(term.quantity, available.accelerations, "a")?;
+                    }
+                }
+                Stage::Velocity {
+                    dt_fraction,
+                    base,
+                    acceleration_terms,
+                } => {
+                    check_denominator(*dt_fraction)?;
+                    check(*base, available.velocities, "v")?;
+                    for term in acceleration_terms {
+                        check(term.quantity, available.accelerations, "a")?;
+                    }
+                }
+            }
+        }
+        let total = counts.last().unwrap();
+        if total.positions < 2 {
+            Err("No stage computes a position.".to_string())
+        } else if total.velocities < 2 {
+            Err("No stage computes a velocity.".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn integrate_step(
+        &self,
+        s0: builders::Position,
+        v0: builders::Velocity,
+        a0: builders::Acceleration,
+        _dt: builders::DtFraction<1, 1>,
+        step: &mut builders::Step,
+    ) {
+        let (mut s, mut v, mut a) = (vec![s0], vec![v0], vec![a0]);
+        for stage in &self.stages {
+            match stage {
+                Stage::Acceleration { position } => {
+                    a.push(step.acceleration_at(get(&s, *position)));
+                }
+                Stage::Position {
+                    dt_fraction,
+                    base,
+                    velocity_terms,
+                    acceleration_terms,
+                } => {
+                    let velocity_terms = velocity_terms
+                        .iter()
+                        .map(|term| (term.factor, get(&v, term.quantity)))
+                        .collect::<Vec<_>>();
+                    let acceleration_terms = acceleration_terms
+                        .iter()
+                        .map(|term| (term.factor, get(&a, term.quantity)))
+                        .collect::<Vec<_>>();
+                    s.push(step.compute_position(
+                        fraction(*dt_fraction),
+                        get(&s, *base),
+                        &velocity_terms,
+                        &acceleration_terms,
+                    ));
+                }
+                Stage::Velocity {
+                    dt_fraction,
+                    base,
+                    acceleration_terms,
+                } => {
+                    let acceleration_terms = acceleration_terms
+                        .iter()
+                        .map(|term| (term.factor, get(&a, term.quantity)))
+                        .collect::<Vec<_>>();
+                    v.push(step.compute_velocity(
+                        fraction(*dt_fraction),
+                        get(&v, *base),
+                        &acceleration_terms,
+                    ));
+                }
+            }
+        }
+    }
+
+    fn hash(&self, state: &mut DefaultHasher) {
+        TypeId::of::<Self>().hash(state);
+        self.stages.hash(state);
+    }
+
+    fn to_concrete_type(&self) -> crate::integrators::serde_box_dyn_integrator::IntegratorSerDe {
+        crate::integrators::serde_box_dyn_integrator::IntegratorSerDe::Custom(self.clone())
+    }
+}
+
+/// Panics on invalid references, which `Custom::validate()` rejects.
+fn get<T: Copy>(refs: &[T], quantity: usize) -> T {
+    refs[quantity]
+}
+
+fn fraction((numerator, denominator): (usize, usize)) -> Fraction {
+    Fraction::new(numerator, denominator.max(1))
+}
+
+/// `s`, `s₁`, `s₂`, … and `s'` for the result of the step (`last`).
+fn quantity_name(symbol: &str, quantity: usize, last: usize) -> String {
+    const SUBSCRIPTS: [char; 10] = ['₀', '₁', '₂', '₃', '₄', '₅', '₆', '₇', '₈', '₉'];
+    if quantity == 0 {
+        symbol.to_string()
+    } else if quantity == last {
+        format!("{}'", symbol)
+    } else {
+        let subscript = quantity
+            .to_string()
+            .chars()
+            .map(|digit| SUBSCRIPTS[digit.to_digit(10).unwrap() as usize])
+            .collect::<String>();
+        format!("{}{}", symbol, subscript)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrators::test_util::TestSetup;
+
+    fn mid_point_euler() -> Custom {
+        Custom {
+            label: "Midpoint".to_string(),
+            stages: vec![
+                Stage::Velocity {
+                    dt_fraction: (1, 2),
+                    base: 0,
+                    acceleration_terms: vec![Term::new(0)],
+                },
+                Stage::Position {
+                    dt_fraction: (1, 2),
+                    base: 0,
+                    velocity_terms: vec![Term::new(1)],
+                    acceleration_terms: Vec::new(),
+                },
+                Stage::Acceleration { position: 1 },
+                Stage::Velocity {
+                    dt_fraction: (1, 1),
+                    base: 0,
+                    acceleration_terms: vec![Term::new(1)],
+                },
+                Stage::Position {
+                    dt_fraction: (1, 1),
+                    base: 0,
+                    velocity_terms: vec![Term::new(2)],
+                    acceleration_terms: Vec::new(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn custom_mid_point_euler() {
+        let ctx = TestSetup::default();
+        ctx.assert_first_step(&mid_point_euler(), |s0, v0, a0, a, dt| {
+            let dt_half = 0.5 * dt;
+            let v_mid = v0 + a0 * dt_half;
+            let a_mid = a.value_at(s0 + v_mid * dt_half);
+            let v1 = v0 + a_mid * dt;
+            let s1 = s0 + v1 * dt;
+            (s1, v1)
+        });
+    }
+
+    #[test]
+    fn description() {
+        assert_eq!(
+            mid_point_euler().description(),
+            "v₁ = v + a ½dt\n\
+             s₁ = s + v₁ ½dt\n\
+             a₁ = a(s₁)\n\
+             v' = v + a₁ dt\n\
+             s' = s + v' dt"
+        );
+    }
+
+    #[test]
+    fn validate() {
+        assert!(Custom::default().validate().is_ok());
+        assert!(mid_point_euler().validate().is_ok());
+
+        let mut forward_reference = mid_point_euler();
+        forward_reference.stages[2] = Stage::Acceleration { position: 2 };
+        assert!(forward_reference.validate().is_err());
+
+        let mut no_velocity = Custom::default();
+        no_velocity.stages.pop();
+        assert!(no_velocity.validate().is_err());
+        assert_eq!(no_velocity.description(), "No stage computes a velocity.");
+    }
+}
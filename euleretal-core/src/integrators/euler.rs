@@ -1,6 +1,6 @@
 use crate::{
     integration_step::builders::{self, Collector},
-    Integrator,
+    Integrator, Metadata,
 };
 
 #[derive(Clone, Copy, Debug, Default, ::serde::Deserialize, ::serde::Serialize)]
@@ -12,11 +12,22 @@ impl Integrator for Broken {
     }
 
     fn description(&self) -> String {
-        "v' = v + a dt\n\
-         s' = s + v dt"
+        "s' = s + v dt\n\
+         v' = v + a dt"
             .to_string()
     }
 
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            order: Some(1),
+            samples_per_step: 1,
+            explicit: true,
+            symplectic: Some(false),
+            time_reversible: Some(false),
+            reference: Some("Euler: Institutionum calculi integralis (1768)"),
+        }
+    }
+
     fn integrate_step(
         &self,
         s: builders::Position,
@@ -49,6 +60,19 @@ impl Integrator for Euler {
             .to_string()
     }
 
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            order: Some(1),
+            samples_per_step: 1,
+            explicit: true,
+            symplectic: Some(true),
+            time_reversible: Some(false),
+            reference: Some(
+                "Hairer, Lubich, Wanner: Geometric Numerical Integration (2006), ch. I.1",
+            ),
+        }
+    }
+
     fn integrate_step(
         &self,
         s: builders::Position,
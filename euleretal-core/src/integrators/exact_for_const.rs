@@ -1,6 +1,6 @@
 use crate::{
     integration_step::builders::{self, Collector},
-    Integrator,
+    Integrator, Metadata,
 };
 
 #[derive(Clone, Copy, Debug, Default, ::serde::Deserialize, ::serde::Serialize)]
@@ -17,6 +17,17 @@ impl Integrator for ExactForConst {
             .to_string()
     }
 
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            order: Some(1),
+            samples_per_step: 1,
+            explicit: true,
+            symplectic: Some(false),
+            time_reversible: Some(false),
+            reference: None,
+        }
+    }
+
     fn integrate_step(
         &self,
         s: builders::Position,
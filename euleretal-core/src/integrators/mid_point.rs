@@ -1,6 +1,6 @@
 use crate::{
     integration_step::builders::{self, Collector},
-    Integrator,
+    Integrator, Metadata,
 };
 
 #[derive(Clone, Copy, Debug, Default, ::serde::Deserialize, ::serde::Serialize)]
@@ -21,6 +21,17 @@ impl Integrator for Euler {
             .to_string()
     }
 
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            order: Some(1),
+            samples_per_step: 2,
+            explicit: true,
+            symplectic: Some(false),
+            time_reversible: Some(false),
+            reference: None,
+        }
+    }
+
     fn integrate_step(
         &self,
         s0: builders::Position,
@@ -54,11 +65,22 @@ impl Integrator for SecondOrder {
     fn description(&self) -> String {
         "s₁ = s + v ½dt + ½ a (½dt)²\n\
          a₁ = a(s₁)\n\
-         v' = v + a₁ dt\n\
-         s' = s + v dt + ½ a₁ dt²" // !! string contains non-breakable spaces
+         s' = s + v dt + ½ a₁ dt²\n\
+         v' = v + a₁ dt"
             .to_string()
     }
 
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            order: Some(2),
+            samples_per_step: 2,
+            explicit: true,
+            symplectic: Some(false),
+            time_reversible: Some(false),
+            reference: None,
+        }
+    }
+
     fn integrate_step(
         &self,
         s0: builders::Position,
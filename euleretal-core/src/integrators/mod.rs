@@ -1,3 +1,4 @@
+pub mod custom;
 pub mod euler;
 pub mod exact_for_const;
 pub mod mid_point;
@@ -7,19 +8,34 @@ mod test_util;
 /// Use this mod in `#[serde(with="<path_to_this_mod>")]` if you need to serialize an attribute of
 /// type `Box<dyn Integrator>`
 pub mod serde_box_dyn_integrator {
-    use super::{euler, exact_for_const, mid_point};
+    use super::{custom, euler, exact_for_const, mid_point};
     use crate::Integrator;
     use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
 
     #[derive(Deserialize, Serialize)]
     pub enum IntegratorSerDe {
         BrokenEuler(#[serde(skip)] euler::Broken),
+        Custom(custom::Custom),
         Euler(#[serde(skip)] euler::Euler),
         ExactForConst(#[serde(skip)] exact_for_const::ExactForConst),
         MidPointEuler(#[serde(skip)] mid_point::Euler),
         MidPointSecondOrder(#[serde(skip)] mid_point::SecondOrder),
     }
 
+    impl IntegratorSerDe {
+        #[must_use]
+        pub fn into_box(self) -> Box<dyn Integrator> {
+            match self {
+                Self::BrokenEuler(integrator) => Box::new(integrator),
+                Self::Custom(integrator) => Box::new(integrator),
+                Self::Euler(integrator) => Box::new(integrator),
+                Self::ExactForConst(integrator) => Box::new(integrator),
+                Self::MidPointEuler(integrator) => Box::new(integrator),
+                Self::MidPointSecondOrder(integrator) => Box::new(integrator),
+            }
+        }
+    }
+
     #[allow(clippy::borrowed_box)]
     #[allow(clippy::missing_errors_doc)]
     pub fn serialize<S>(integrator: &Box<dyn Integrator>, serializer: S) -> Result<S::Ok, S::Error>
@@ -34,18 +50,88 @@ pub mod serde_box_dyn_integrator {
     where
         D: Deserializer<'de>,
     {
-        Ok(match IntegratorSerDe::deserialize(deserializer)? {
-            IntegratorSerDe::BrokenEuler(integrator) => Box::new(integrator) as Box<dyn Integrator>,
-            IntegratorSerDe::Euler(integrator) => Box::new(integrator) as Box<dyn Integrator>,
-            IntegratorSerDe::ExactForConst(integrator) => {
-                Box::new(integrator) as Box<dyn Integrator>
-            }
-            IntegratorSerDe::MidPointEuler(integrator) => {
-                Box::new(integrator) as Box<dyn Integrator>
-            }
-            IntegratorSerDe::MidPointSecondOrder(integrator) => {
-                Box::new(integrator) as Box<dyn Integrator>
+        Ok(IntegratorSerDe::deserialize(deserializer)?.into_box())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{custom, euler, exact_for_const, mid_point, test_util::step_1d};
+    use crate::{integration_step::symbolic::Trace, truncation::Analysis, Integrator};
+
+    fn built_in() -> [&'static dyn Integrator; 5] {
+        [
+            &euler::Broken,
+            &euler::Euler,
+            &exact_for_const::ExactForConst,
+            &mid_point::Euler,
+            &mid_point::SecondOrder,
+        ]
+    }
+
+    #[test]
+    fn metadata_agrees_with_implementation() {
+        for integrator in built_in()
+            .into_iter()
+            .chain([&custom::Custom::default() as &dyn Integrator])
+        {
+            let metadata = integrator.metadata();
+            let label = integrator.label();
+            let accelerations = Trace::of(integrator)
+                .assignments
+                .iter()
+                .filter(|assignment| {
+                    matches!(
+                        assignment.target,
+                        crate::integration_step::symbolic::Symbol::Acceleration(_)
+                    )
+                })
+                .count();
+            assert_eq!(metadata.samples_per_step, 1 + accelerations, "{}", label);
+            if let Some(order) = metadata.order {
+                assert_eq!(Analysis::of(integrator).order(), Some(order), "{}", label);
             }
-        })
+        }
+    }
+
+    /// In one dimension, a symplectic step preserves the area of the phase space, i.e. the
+    /// determinant of its Jacobian is 1.
+    #[test]
+    fn symplectic() {
+        const EPSILON: f32 = 1e-3;
+        let (s, v, dt) = (1., 0.5, 0.5);
+        for integrator in built_in() {
+            let ds = |s, v| step_1d(integrator, s, v, dt);
+            let (s_plus, s_minus) = (ds(s + EPSILON, v), ds(s - EPSILON, v));
+            let (v_plus, v_minus) = (ds(s, v + EPSILON), ds(s, v - EPSILON));
+            let determinant = ((s_plus.0 - s_minus.0) * (v_plus.1 - v_minus.1)
+                - (v_plus.0 - v_minus.0) * (s_plus.1 - s_minus.1))
+                / (4. * EPSILON * EPSILON);
+            assert_eq!(
+                integrator.metadata().symplectic,
+                Some((determinant - 1.).abs() < 1e-2),
+                "{}: determinant {}",
+                integrator.label(),
+                determinant
+            );
+        }
+    }
+
+    /// A time-reversible step returns to its start if it is repeated with reversed velocity.
+    #[test]
+    fn time_reversible() {
+        let (s, v, dt) = (1., 0.5, 0.5);
+        for integrator in built_in() {
+            let (s1, v1) = step_1d(integrator, s, v, dt);
+            let (s2, v2) = step_1d(integrator, s1, -v1, dt);
+            let deviation = (s2 - s).abs() + (v2 + v).abs();
+            assert_eq!(
+                integrator.metadata().time_reversible,
+                Some(deviation < 1e-4),
+                "{}: deviation {}",
+                integrator.label(),
+                deviation
+            );
+        }
     }
 }
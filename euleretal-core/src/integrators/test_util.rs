@@ -45,11 +45,11 @@ impl Default for TestSetup {
 }
 
 impl TestSetup {
-    fn new_builder_for<'a>(&'a self, step: &'a mut Step) -> builders::Step<'a> {
+    fn new_builder_for<'a>(&'a self, step: &'a mut Step<'_>) -> builders::Step<'a> {
         builders::Step::new(&self.acceleration_field, step)
     }
 
-    fn new_step(&self) -> Step {
+    fn new_step(&self) -> Step<'static> {
         Step::new(&self.start_condition, self.dt)
     }
 
@@ -80,3 +80,36 @@ impl TestSetup {
         assert_eq!(step.last_v(), exp_v);
     }
 }
+
+/// `a(s) = -s³` along the x axis, which is neither constant nor linear.
+#[derive(Clone, Copy)]
+pub struct Cubic;
+
+impl AccelerationField for Cubic {
+    fn value_at(&self, pos: Position) -> Acceleration {
+        let x = pos.as_point().x;
+        Acceleration::new(-x * x * x, 0., 0.)
+    }
+
+    fn label(&self) -> String {
+        "Cubic".to_string()
+    }
+
+    fn to_concrete_type(
+        &self,
+    ) -> crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe {
+        unimplemented!() // not required for test helpers
+    }
+}
+
+/// Computes a single step along the x axis, in the `Cubic` acceleration field.
+pub fn step_1d(integrator: &dyn Integrator, s: f32, v: f32, dt: f32) -> (f32, f32) {
+    let s = Position::new(s, 0., 0.);
+    let start_condition = StartCondition::new(s, Velocity::new(v, 0., 0.), Cubic.value_at(s));
+    let mut step = Step::new(&start_condition, dt.into());
+    let mut builder = builders::Step::new(&Cubic, &mut step);
+    let ((s, v, a), dt) = (builder.start_values(), builder.dt());
+    integrator.integrate_step(s, v, a, dt, &mut builder);
+    builder.finalize();
+    (step.last_s().as_point().x, step.last_v().as_vector().x)
+}
@@ -0,0 +1,203 @@
+//! Long-running computations, run by a fixed pool of worker threads on native builds and
+//! synchronously on wasm.
+
+use ::std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+type Task = Box<dyn FnOnce() + Send>;
+
+/// Shared between a [`Job`] and its computation: the computation reports its progress, the job
+/// requests cancellation.
+#[derive(Debug, Default)]
+pub struct Progress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    /// Announces `units` more units of work.
+    pub fn expect(&self, units: usize) {
+        self.total.fetch_add(units, Ordering::Relaxed);
+    }
+
+    /// Reports one unit of work as done.  Returns `false` if the computation should be abandoned.
+    pub fn advance(&self) -> bool {
+        self.done.fetch_add(1, Ordering::Relaxed);
+        !self.is_cancelled()
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Between 0 and 1
+    #[must_use]
+    pub fn fraction(&self) -> f32 {
+        #![allow(clippy::cast_precision_loss)]
+        let total = self.total.load(Ordering::Relaxed);
+        if total == 0 {
+            0.
+        } else {
+            (self.done.load(Ordering::Relaxed) as f32 / total as f32).min(1.)
+        }
+    }
+}
+
+pub enum Status<T> {
+    Running,
+    Done(T),
+    /// The computation panicked or has already been taken
+    Failed,
+}
+
+/// A computation producing a `T`.  Dropping the job cancels the computation.
+pub struct Job<T> {
+    progress: Arc<Progress>,
+    receiver: mpsc::Receiver<T>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    /// Queues `work`, which is expected to call [`Progress::advance()`] regularly and to return
+    /// `None` once it has been cancelled.  Jobs which are dropped before a worker is free are
+    /// skipped.
+    pub fn spawn<F>(name: &str, work: F) -> Self
+    where
+        F: FnOnce(&Progress) -> Option<T> + Send + 'static,
+    {
+        let progress = Arc::new(Progress::default());
+        let (sender, receiver) = mpsc::channel();
+        let run = {
+            let progress = Arc::clone(&progress);
+            let name = name.to_string();
+            move || {
+                if progress.is_cancelled() {
+                    return;
+                }
+                match panic::catch_unwind(AssertUnwindSafe(|| work(&progress))) {
+                    // the receiver is gone if the job has been dropped in the meantime
+                    Ok(Some(result)) => drop(sender.send(result)),
+                    Ok(None) => (),
+                    Err(_) => log::error!("{} failed", name),
+                }
+            }
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(mpsc::SendError(run)) = workers().send(Box::new(run)) {
+            // no worker could be started:
+            run();
+        }
+        #[cfg(target_arch = "wasm32")]
+        run();
+
+        Self { progress, receiver }
+    }
+}
+
+/// The queue of the worker threads, one per CPU core.  They are started with the first job.
+#[cfg(not(target_arch = "wasm32"))]
+fn workers() -> &'static mpsc::Sender<Task> {
+    static QUEUE: ::std::sync::OnceLock<mpsc::Sender<Task>> = ::std::sync::OnceLock::new();
+    QUEUE.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let receiver = Arc::new(::std::sync::Mutex::new(receiver));
+        let num_workers = ::std::thread::available_parallelism().map_or(2, usize::from);
+        for n in 0..num_workers {
+            let receiver = Arc::clone(&receiver);
+            if let Err(err) = ::std::thread::Builder::new()
+                .name(format!("worker {}", n))
+                .spawn(move || loop {
+                    let task = receiver
+                        .lock()
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner)
+                        .recv();
+                    match task {
+                        Ok(task) => task(),
+                        Err(mpsc::RecvError) => break,
+                    }
+                })
+            {
+                log::error!("Cannot start worker {}: {}", n, err);
+            }
+        }
+        sender
+    })
+}
+
+impl<T> Job<T> {
+    /// Does not block.
+    #[must_use]
+    pub fn poll(&self) -> Status<T> {
+        match self.receiver.try_recv() {
+            Ok(result) => Status::Done(result),
+            Err(mpsc::TryRecvError::Empty) => Status::Running,
+            Err(mpsc::TryRecvError::Disconnected) => Status::Failed,
+        }
+    }
+
+    /// Blocks until the computation has finished.  Returns `None` if it failed.
+    #[must_use]
+    pub fn wait(&self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+
+    /// Between 0 and 1
+    #[must_use]
+    pub fn progress(&self) -> f32 {
+        self.progress.fraction()
+    }
+}
+
+impl<T> Drop for Job<T> {
+    fn drop(&mut self) {
+        self.progress.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Job, Status};
+
+    #[test]
+    fn result_and_cancellation() {
+        let job = Job::spawn("test job", |progress| {
+            progress.expect(10);
+            (0..10).all(|_| progress.advance()).then_some(42)
+        });
+        assert_eq!(job.wait(), Some(42));
+        assert!((job.progress() - 1.).abs() < f32::EPSILON);
+        assert!(!matches!(job.poll(), Status::Done(_)));
+
+        let job = Job::spawn("endless test job", |progress| {
+            while progress.advance() {}
+            None::<()>
+        });
+        job.progress.cancel();
+        assert_eq!(job.wait(), None);
+    }
+
+    #[test]
+    fn workers_survive_panics() {
+        let failing = Job::spawn("failing test job", |_| -> Option<()> { panic!("expected") });
+        assert_eq!(failing.wait(), None);
+        assert!(matches!(failing.poll(), Status::Failed));
+
+        // more jobs than workers:
+        let jobs = (0..64)
+            .map(|n| Job::spawn("test job", move |_| Some(n)))
+            .collect::<Vec<_>>();
+        assert!(jobs.iter().map(Job::wait).eq((0..64).map(Some)));
+    }
+}
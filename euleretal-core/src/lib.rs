@@ -6,7 +6,6 @@
 #![feature(generic_const_exprs)] // used by DtFraction
 
 mod import {
-    pub use ::parry3d::{query::PointQuery, shape};
     pub type OrderedF32 = ::ordered_float::OrderedFloat<f32>;
     pub type Point3 = ::parry3d::math::Point<f32>;
     pub type Vec3 = ::parry3d::math::Vector<f32>;
@@ -14,30 +13,43 @@ mod import {
 
 mod acceleration;
 mod acceleration_field;
+pub mod bodies;
+mod chunked_vec;
+pub mod dense_output;
+pub mod divergence;
 mod duration;
+pub mod event;
 mod fraction;
 mod integration;
 pub mod integration_step;
 mod integrator;
 pub mod integrators;
+pub mod job;
 mod r#move;
+pub mod obstacle;
+pub mod orbit;
+mod point_index;
 mod position;
+mod reference_cache;
 pub mod samples;
 mod scenario;
 pub mod scenarios;
+pub mod truncation;
 mod vector_quantity;
 mod velocity;
 
 pub use acceleration::Acceleration;
-pub use acceleration_field::AccelerationField;
+pub use acceleration_field::{AccelerationField, AccelerationSource};
 pub use duration::Duration;
 pub use fraction::Fraction;
 pub use import::{Point3, Vec3};
-pub use integration::Integration;
+pub use integration::{Cost, Integration, Recording};
 pub use integration_step::{Contribution, StartCondition, Step};
-pub use integrator::Integrator;
+pub use integrator::{Integrator, Metadata};
+pub use point_index::PointIndex;
 pub use position::Position;
 pub use r#move::Move;
+pub use reference_cache::{ReferenceCache, ReferenceSamples, Track, Trajectory};
 pub use samples::Samples;
 pub use scenario::Scenario;
 use vector_quantity::VectorQuantity;
@@ -0,0 +1,370 @@
+//! Static obstacles which particles bounce off.
+//!
+//! A particle moving from one position to the next crosses the surface of an obstacle if a ray
+//! cast along its path hits the shape.  The particle is then reflected at the surface: the
+//! normal component of its velocity is reversed and scaled by the restitution of the obstacle.
+//! A particle on the surface (e.g. resting on it after an inelastic bounce) hits it again as soon
+//! as it moves inward.
+
+use super::{
+    import::{OrderedF32, Point3, Vec3},
+    Duration, Position, Velocity,
+};
+use ::parry3d::{
+    math::Isometry,
+    na::Unit,
+    query::{PointQuery, Ray, RayCast},
+    shape,
+};
+use ::std::hash::{Hash, Hasher};
+
+/// The shape of an obstacle, relative to its position
+#[derive(Clone, Copy, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+pub enum Shape {
+    /// An infinite plane.  Particles move on the side which `normal` points to.
+    Wall {
+        normal: Vec3,
+    },
+    Sphere {
+        radius: f32,
+    },
+    /// An axis-aligned box
+    Cuboid {
+        half_extents: Vec3,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+pub struct Obstacle {
+    pub shape: Shape,
+    /// A point on the wall, or the center of the sphere or box
+    pub position: Position,
+    /// The ratio of the normal velocities after and before a bounce: 1 for elastic bounces, 0
+    /// for perfectly inelastic ones
+    pub restitution: f32,
+}
+
+/// The number of bisections used to locate a collision within a step
+const BISECTIONS: usize = 30;
+
+/// The first obstacle on the path of a particle
+#[derive(Clone, Copy, Debug)]
+pub struct Crossing {
+    pub obstacle: Obstacle,
+    /// The fraction of the path after which the obstacle is hit
+    pub fraction: f32,
+}
+
+/// Where and when a particle bounced off an obstacle
+#[derive(Clone, Copy, Debug)]
+pub struct Collision {
+    pub t: Duration,
+    pub s: Position,
+}
+
+impl Shape {
+    #[must_use]
+    pub fn variants() -> [Self; 3] {
+        [
+            Self::Wall {
+                normal: Vec3::new(0., 1., 0.),
+            },
+            Self::Sphere { radius: 0.5 },
+            Self::Cuboid {
+                half_extents: Vec3::new(0.5, 0.5, 0.5),
+            },
+        ]
+    }
+
+    #[must_use]
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Wall { .. } => "Wall",
+            Self::Sphere { .. } => "Sphere",
+            Self::Cuboid { .. } => "Box",
+        }
+    }
+}
+
+impl Obstacle {
+    #[must_use]
+    pub fn new(shape: Shape, position: Position) -> Self {
+        Self {
+            shape,
+            position,
+            restitution: 1.,
+        }
+    }
+
+    /// Checks if the obstacle is well-defined.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first invalid attribute.
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_shape = match self.shape {
+            Shape::Wall { normal } => normal.iter().all(|c| c.is_finite()) && normal.norm() > 0.,
+            Shape::Sphere { radius } => radius.is_finite() && radius > 0.,
+            Shape::Cuboid { half_extents } => half_extents.iter().all(|&c| c.is_finite() && c > 0.),
+        };
+        if !valid_shape {
+            return Err(format!("{} has an invalid size.", self.shape.label()));
+        }
+        if !self.position.as_vector().iter().all(|c| c.is_finite()) {
+            return Err(format!("{} position must be finite.", self.shape.label()));
+        }
+        if !(0. ..=1.).contains(&self.restitution) {
+            return Err("Restitution must be between 0 and 1.".to_string());
+        }
+        Ok(())
+    }
+
+    /// The obstacle which is hit first on the straight path from `from` to `to`, if any.  Paths
+    /// which start on the surface of an obstacle (or inside) hit it at their start if they lead
+    /// inward, so that particles resting on a surface do not fall through, and do not hit it
+    /// otherwise, so that particles can always escape.  Paths with non-finite ends hit nothing.
+    #[must_use]
+    pub fn first_crossing(obstacles: &[Self], from: Position, to: Position) -> Option<Crossing> {
+        let ray = Ray::new(*from.as_point(), to.as_point() - from.as_point());
+        let length_squared = ray.dir.norm_squared();
+        if length_squared == 0. || !length_squared.is_finite() {
+            return None;
+        }
+        obstacles
+            .iter()
+            .filter_map(|obstacle| {
+                obstacle.time_of_impact(&ray).map(|fraction| Crossing {
+                    obstacle: *obstacle,
+                    fraction,
+                })
+            })
+            .min_by(|a, b| a.fraction.total_cmp(&b.fraction))
+    }
+
+    #[must_use]
+    pub fn contains(&self, s: Position) -> bool {
+        let isometry = self.isometry();
+        match self.shape {
+            Shape::Wall { normal } => {
+                Self::half_space(normal).contains_point(&isometry, s.as_point())
+            }
+            Shape::Sphere { radius } => {
+                shape::Ball::new(radius).contains_point(&isometry, s.as_point())
+            }
+            Shape::Cuboid { half_extents } => {
+                shape::Cuboid::new(half_extents).contains_point(&isometry, s.as_point())
+            }
+        }
+    }
+
+    /// The outward unit normal of the surface at (or near) `s`
+    #[must_use]
+    pub fn normal_at(&self, s: Position) -> Vec3 {
+        let local = s.as_point() - self.position.as_point();
+        match self.shape {
+            Shape::Wall { normal } => normal.normalize(),
+            Shape::Sphere { .. } => local.try_normalize(0.).unwrap_or_else(Vec3::y),
+            Shape::Cuboid { half_extents } => {
+                // the axis of the face which is closest to `s`:
+                let axis = local.component_div(&half_extents).iamax();
+                let mut normal = Vec3::zeros();
+                normal[axis] = local[axis].signum();
+                normal
+            }
+        }
+    }
+
+    /// The velocity after bouncing off the surface with the outward unit `normal`.  Particles
+    /// which already move away from the surface are not affected.
+    #[must_use]
+    pub fn bounce(&self, v: Velocity, normal: &Vec3) -> Velocity {
+        let v = *v.as_vector();
+        let normal_speed = v.dot(normal);
+        if normal_speed < 0. {
+            (v - (1. + self.restitution) * normal_speed * normal).into()
+        } else {
+            v.into()
+        }
+    }
+
+    /// Reflects a position `s` which has penetrated the surface with the outward unit `normal` at
+    /// `contact`.
+    #[must_use]
+    pub fn reflect(&self, s: Position, contact: Position, normal: &Vec3) -> Position {
+        let depth = (s.as_point() - contact.as_point()).dot(normal);
+        if depth < 0. {
+            Point3::from(s.as_point() - (1. + self.restitution) * depth * normal).into()
+        } else {
+            s
+        }
+    }
+
+    fn isometry(&self) -> Isometry<f32> {
+        Isometry::translation(
+            self.position.as_point().x,
+            self.position.as_point().y,
+            self.position.as_point().z,
+        )
+    }
+
+    /// A half space whose interior is the solid side of a wall
+    fn half_space(normal: Vec3) -> shape::HalfSpace {
+        shape::HalfSpace::new(Unit::new_normalize(normal))
+    }
+
+    /// The fraction of `ray` after which it enters the obstacle
+    fn time_of_impact(&self, ray: &Ray) -> Option<f32> {
+        // `contains()` includes the surface:
+        if self.contains(ray.origin.into()) {
+            let normal = self.normal_at(ray.origin.into());
+            return (ray.dir.dot(&normal) < 0.).then_some(0.);
+        }
+        let isometry = self.isometry();
+        match self.shape {
+            Shape::Wall { normal } => Self::half_space(normal).cast_ray(&isometry, ray, 1., true),
+            Shape::Sphere { radius } => shape::Ball::new(radius).cast_ray(&isometry, ray, 1., true),
+            Shape::Cuboid { half_extents } => {
+                shape::Cuboid::new(half_extents).cast_ray(&isometry, ray, 1., true)
+            }
+        }
+    }
+}
+
+impl Crossing {
+    /// The fraction at which a curved path enters the obstacle, where `position_at(fraction)`
+    /// interpolates the path from its start (0) to its end (1).  If the end is inside the
+    /// obstacle, the fraction is found by bisection.  Otherwise (e.g. if the straight path only
+    /// cuts through an edge of the obstacle) the fraction of the straight path is kept.
+    pub(crate) fn refined_fraction(&self, position_at: impl Fn(f32) -> Position) -> f32 {
+        if !self.obstacle.contains(position_at(1.)) {
+            return self.fraction;
+        }
+        let (mut outside, mut inside) = (0_f32, 1_f32);
+        for _ in 0..BISECTIONS {
+            let mid = 0.5 * (outside + inside);
+            if self.obstacle.contains(position_at(mid)) {
+                inside = mid;
+            } else {
+                outside = mid;
+            }
+        }
+        outside
+    }
+}
+
+impl Hash for Obstacle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ::std::mem::discriminant(&self.shape).hash(state);
+        match self.shape {
+            Shape::Wall { normal: vector }
+            | Shape::Cuboid {
+                half_extents: vector,
+            } => {
+                for coordinate in &vector {
+                    OrderedF32::from(*coordinate).hash(state);
+                }
+            }
+            Shape::Sphere { radius } => OrderedF32::from(radius).hash(state),
+        }
+        self.position.hash(state);
+        OrderedF32::from(self.restitution).hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Obstacle, Shape};
+    use crate::{Position, Vec3, Velocity};
+
+    fn ground() -> Obstacle {
+        Obstacle::new(
+            Shape::Wall {
+                normal: Vec3::new(0., 2., 0.),
+            },
+            Position::new(0., -1., 0.),
+        )
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // a path from the surface is hit at its very start
+    fn crossing() {
+        let obstacles = [
+            ground(),
+            Obstacle::new(Shape::Sphere { radius: 0.5 }, Position::new(2., -0.3, 0.)),
+        ];
+        let crossing = Obstacle::first_crossing(
+            &obstacles,
+            Position::new(0., 0., 0.),
+            Position::new(0., -2., 0.),
+        )
+        .unwrap();
+        assert!((crossing.fraction - 0.5).abs() < 1e-6);
+        assert!(matches!(crossing.obstacle.shape, Shape::Wall { .. }));
+        // the sphere is hit before the wall:
+        let crossing = Obstacle::first_crossing(
+            &obstacles,
+            Position::new(0., 0., 0.),
+            Position::new(4., -1.2, 0.),
+        )
+        .unwrap();
+        assert!(matches!(crossing.obstacle.shape, Shape::Sphere { .. }));
+        // paths from inside escape:
+        assert!(Obstacle::first_crossing(
+            &obstacles,
+            Position::new(0., -2., 0.),
+            Position::new(0., 0., 0.)
+        )
+        .is_none());
+        assert!(Obstacle::first_crossing(
+            &obstacles,
+            Position::new(0., 0., 0.),
+            Position::new(1., 0., 0.)
+        )
+        .is_none());
+        // paths from the surface only hit it if they lead inward:
+        let crossing = Obstacle::first_crossing(
+            &obstacles,
+            Position::new(0., -1., 0.),
+            Position::new(1., -1.5, 0.),
+        )
+        .unwrap();
+        assert_eq!(crossing.fraction, 0.);
+        assert!(Obstacle::first_crossing(
+            &obstacles,
+            Position::new(0., -1., 0.),
+            Position::new(1., -0.5, 0.)
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn bounce() {
+        let mut obstacle = ground();
+        let normal = obstacle.normal_at(Position::new(3., -1., 0.));
+        assert_eq!(normal, Vec3::new(0., 1., 0.));
+        let v = Velocity::new(1., -2., 0.);
+        assert_eq!(obstacle.bounce(v, &normal), Velocity::new(1., 2., 0.));
+        let contact = Position::new(0., -1., 0.);
+        assert_eq!(
+            obstacle.reflect(Position::new(1., -1.5, 0.), contact, &normal),
+            Position::new(1., -0.5, 0.)
+        );
+        obstacle.restitution = 0.5;
+        assert_eq!(obstacle.bounce(v, &normal), Velocity::new(1., 1., 0.));
+        // moving away:
+        let away = Velocity::new(1., 2., 0.);
+        assert_eq!(obstacle.bounce(away, &normal), away);
+
+        let cuboid = Obstacle::new(
+            Shape::Cuboid {
+                half_extents: Vec3::new(2., 1., 1.),
+            },
+            Position::new(0., 0., 0.),
+        );
+        assert!(cuboid.contains(Position::new(1.5, 0.5, 0.)));
+        assert_eq!(
+            cuboid.normal_at(Position::new(1.5, -1., 0.)),
+            Vec3::new(0., -1., 0.)
+        );
+    }
+}
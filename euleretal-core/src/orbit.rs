@@ -0,0 +1,311 @@
+//! Kepler orbits around a [`PointMass`], and how well integrations keep them.
+//!
+//! An [`Orbit`] is given by its orbital elements and turned into a scenario which starts at the
+//! perihelion and lasts for a number of periods.  Integrators typically distort such orbits in
+//! two ways: the perihelion drifts around the center (precession), and the period changes.  Both
+//! are measured from the perihelion passages of the samples by [`OrbitMetrics::of()`].
+
+use super::{
+    import::Point3, scenarios::PointMass, Duration, Position, Samples, Scenario, Velocity,
+};
+use ::std::f32::consts::{PI, TAU};
+
+/// The orbital elements of a Kepler orbit in the x/y plane, with the perihelion on the positive
+/// y axis
+#[derive(Clone, Copy, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+pub struct Orbit {
+    pub semi_major_axis: f32,
+    /// 0 for circles, up to (but excluding) 1
+    pub eccentricity: f32,
+    pub central_mass: f32,
+    /// The duration of the scenario, in orbital periods
+    pub periods: f32,
+    /// The Plummer softening length of the central mass, see [`PointMass`].  Softened orbits
+    /// precess even without integration errors.
+    pub softening: f32,
+}
+
+/// The motion of an orbiting particle, averaged over its perihelion passages
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitMetrics {
+    /// The number of complete orbits between the first and the last perihelion passage
+    pub orbits: usize,
+    /// The mean duration between two perihelion passages
+    pub period: Duration,
+    /// The mean angle by which the perihelion advances per orbit (counter-clockwise, in
+    /// radians)
+    pub precession: f32,
+}
+
+/// How much an integration deviates from the [`OrbitMetrics`] of its reference
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitErrors {
+    /// in radians per orbit
+    pub precession: f32,
+    /// relative to the period of the reference
+    pub period: f32,
+}
+
+/// Orbits whose distance from the center varies less than this (relative to the maximum
+/// distance) are considered circular, i.e. they have no perihelion.
+const CIRCULARITY: f32 = 1e-3;
+
+impl Default for Orbit {
+    /// The unit circle
+    fn default() -> Self {
+        Self {
+            semi_major_axis: 1.,
+            eccentricity: 0.,
+            central_mass: 1.,
+            periods: 1.,
+            softening: 0.,
+        }
+    }
+}
+
+impl Orbit {
+    /// Checks if the elements describe a closed orbit.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first invalid element.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.semi_major_axis.is_finite() || self.semi_major_axis <= 0. {
+            return Err(format!(
+                "Semi-major axis must be positive (is {}).",
+                self.semi_major_axis
+            ));
+        }
+        if !(0. ..1.).contains(&self.eccentricity) {
+            return Err(format!(
+                "Eccentricity must be at least 0 and less than 1 (is {}).",
+                self.eccentricity
+            ));
+        }
+        if !self.central_mass.is_finite() || self.central_mass <= 0. {
+            return Err(format!(
+                "Central mass must be positive (is {}).",
+                self.central_mass
+            ));
+        }
+        if !self.periods.is_finite() || self.periods <= 0. {
+            return Err(format!(
+                "Number of periods must be positive (is {}).",
+                self.periods
+            ));
+        }
+        if !self.softening.is_finite() || self.softening < 0. {
+            return Err(format!(
+                "Softening length must not be negative (is {}).",
+                self.softening
+            ));
+        }
+        Ok(())
+    }
+
+    /// The period of the unsoftened orbit, by Kepler's third law
+    #[must_use]
+    pub fn period(&self) -> Duration {
+        (TAU * (self.semi_major_axis.powi(3) / self.central_mass).sqrt()).into()
+    }
+
+    #[must_use]
+    pub fn perihelion(&self) -> Position {
+        Position::new(0., self.semi_major_axis * (1. - self.eccentricity), 0.)
+    }
+
+    /// The velocity at the perihelion, by the vis-viva equation.  The orbit is clockwise, like
+    /// the circular orbit of the default scenario.
+    #[must_use]
+    pub fn perihelion_velocity(&self) -> Velocity {
+        let e = self.eccentricity;
+        let speed = (self.central_mass * (1. + e) / (self.semi_major_axis * (1. - e))).sqrt();
+        Velocity::new(speed, 0., 0.)
+    }
+
+    /// A scenario which starts at the perihelion and lasts for `periods`.
+    #[must_use]
+    pub fn scenario(&self) -> Scenario {
+        Scenario {
+            acceleration: Box::new(PointMass {
+                mass: self.central_mass,
+                softening: self.softening,
+            }),
+            start_position: self.perihelion(),
+            start_velocity: self.perihelion_velocity(),
+            duration: self.period() * self.periods,
+            event: None,
+            obstacles: Vec::new(),
+            mass: 1.,
+            bodies: Vec::new(),
+        }
+    }
+}
+
+impl OrbitMetrics {
+    /// Measures the orbit around the origin which is described by `samples`.  The start counts
+    /// as a perihelion passage if the distance increases from there.  Returns `None` unless at
+    /// least one complete orbit has been sampled.
+    #[must_use]
+    pub fn of(samples: &Samples) -> Option<Self> {
+        #![allow(clippy::cast_precision_loss)]
+        let first_step = (!samples.is_empty()).then(|| samples.at(0))?;
+        let dt = f32::from(first_step.dt());
+        let positions = ::std::iter::once(first_step.get_start_condition().position())
+            .chain(samples.step_positions())
+            .collect::<Vec<_>>();
+        let distances = positions
+            .iter()
+            .map(|s| s.as_vector().norm())
+            .collect::<Vec<_>>();
+        let max_distance = distances.iter().copied().fold(0., f32::max);
+        let min_distance = distances.iter().copied().fold(f32::INFINITY, f32::min);
+        if max_distance - min_distance < CIRCULARITY * max_distance {
+            return None;
+        }
+
+        let mut perihelia = Vec::new();
+        if let [r0, r1, ..] = distances[..] {
+            if r1 > r0 {
+                perihelia.push((0., positions[0]));
+            }
+        }
+        for k in 1..distances.len().saturating_sub(1) {
+            let (r_before, r, r_after) = (distances[k - 1], distances[k], distances[k + 1]);
+            if r_before > r && r <= r_after {
+                // the vertex of the parabola through the three samples:
+                let curvature = r_before - 2. * r + r_after;
+                let offset = (0.5 * (r_before - r_after) / curvature).clamp(-1., 1.);
+                let (s_before, s, s_after) = (
+                    positions[k - 1].as_vector(),
+                    positions[k].as_vector(),
+                    positions[k + 1].as_vector(),
+                );
+                let interpolated = s
+                    + offset * 0.5 * (s_after - s_before)
+                    + offset * offset * 0.5 * (s_before - 2. * s + s_after);
+                let t = (k as f32 + offset) * dt;
+                perihelia.push((t, Position::from(Point3::from(interpolated))));
+            }
+        }
+
+        let orbits = perihelia
+            .len()
+            .checked_sub(1)
+            .filter(|&orbits| orbits > 0)?;
+        let (first, last) = (perihelia.first()?, perihelia.last()?);
+        let angle = |s: &Position| s.as_point().y.atan2(s.as_point().x);
+        let precession = perihelia
+            .windows(2)
+            .map(|pair| {
+                let advance = angle(&pair[1].1) - angle(&pair[0].1);
+                // within (-π, π]:
+                advance - TAU * ((advance + PI) / TAU).floor()
+            })
+            .sum::<f32>()
+            / orbits as f32;
+        Some(Self {
+            orbits,
+            period: ((last.0 - first.0) / orbits as f32).into(),
+            precession,
+        })
+    }
+
+    /// The deviation of `self` from the metrics of the `reference`
+    #[must_use]
+    pub fn errors(&self, reference: &Self) -> OrbitErrors {
+        OrbitErrors {
+            precession: self.precession - reference.precession,
+            period: (f32::from(self.period) - f32::from(reference.period))
+                / f32::from(reference.period),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Orbit, OrbitMetrics};
+    use crate::{integrators::mid_point, Integration};
+    use ::std::sync::Arc;
+
+    #[test]
+    fn kepler_orbit() {
+        let orbit = Orbit {
+            semi_major_axis: 2.,
+            eccentricity: 0.5,
+            central_mass: 2.,
+            periods: 3.,
+            softening: 0.,
+        };
+        assert!(orbit.validate().is_ok());
+        let scenario = orbit.scenario();
+        let (s, v) = (scenario.start_position, scenario.start_velocity);
+        // the energy and the angular momentum of the orbit:
+        let energy = 0.5 * v.as_vector().norm_squared() - orbit.central_mass / s.as_vector().norm();
+        assert!((energy + orbit.central_mass / (2. * orbit.semi_major_axis)).abs() < 1e-6);
+        let angular_momentum = s.as_vector().cross(v.as_vector()).norm();
+        let semi_latus_rectum = orbit.semi_major_axis * (1. - orbit.eccentricity.powi(2));
+        assert!((angular_momentum - (orbit.central_mass * semi_latus_rectum).sqrt()).abs() < 1e-6);
+        assert!((f32::from(scenario.duration) - 3. * ::std::f32::consts::TAU * 2.).abs() < 1e-4);
+        assert!(Orbit {
+            eccentricity: 1.,
+            ..orbit
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn metrics() {
+        let orbit = Orbit {
+            eccentricity: 0.6,
+            periods: 3.,
+            ..Orbit::default()
+        };
+        let scenario = orbit.scenario();
+        let mut integration = Integration::new();
+        integration.update(
+            &scenario,
+            &mid_point::SecondOrder,
+            0.01.into(),
+            &Arc::default(),
+        );
+        integration.wait();
+        let reference = OrbitMetrics::of(integration.reference_samples().unwrap()).unwrap();
+        assert!(reference.orbits >= 2);
+        assert!((f32::from(reference.period) - f32::from(orbit.period())).abs() < 1e-3);
+        assert!(reference.precession.abs() < 1e-3);
+        let (metrics, errors) = integration.orbit_metrics().unwrap();
+        assert!(errors.precession.abs() > 1e-5);
+        assert!(errors.precession.abs() < 0.1);
+        assert!(errors.period.abs() < 0.01);
+        assert_eq!(metrics.orbits, reference.orbits);
+
+        // circular orbits have no perihelion:
+        let mut circle = Integration::new();
+        circle.update(
+            &Orbit::default().scenario(),
+            &mid_point::SecondOrder,
+            0.01.into(),
+            &Arc::default(),
+        );
+        circle.wait();
+        assert!(circle.orbit_metrics().is_none());
+
+        // softening lets the perihelion advance:
+        let softened = Orbit {
+            softening: 0.1,
+            ..orbit
+        };
+        let mut integration = Integration::new();
+        integration.update(
+            &softened.scenario(),
+            &mid_point::SecondOrder,
+            0.01.into(),
+            &Arc::default(),
+        );
+        integration.wait();
+        let reference = OrbitMetrics::of(integration.reference_samples().unwrap()).unwrap();
+        assert!(reference.precession.abs() > 0.01);
+    }
+}
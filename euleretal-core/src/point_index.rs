@@ -0,0 +1,313 @@
+//! A bounding volume hierarchy over the points of a `Samples`, for picking the step which
+//! computed the point closest to the pointer.
+//!
+//! The points are grouped into blocks of consecutive steps, each with a hierarchy of its own, so
+//! that an integration which is continued only rebuilds its last block.  A hierarchy is built by
+//! recursively splitting the points at the median of the axis with the largest extent.  Queries
+//! descend into the nearer child first and skip every node (and block) whose bounding box is
+//! farther away than the closest point found so far.
+//!
+//! Every integration (and every reference) keeps an index of its own instead of sharing one index
+//! per canvas: Each integration of a canvas focusses its own closest step when the pointer is
+//! pressed, and the indices are continued and shared along with the samples they index.  For the
+//! same reason the hierarchy is written here rather than taken from parry3d, whose bounding volume
+//! hierarchy can neither share parts between clones nor rebuild only the part of new points.
+
+use super::{
+    import::{Point3, Vec3},
+    Position,
+};
+use ::std::sync::Arc;
+
+/// The number of points below which a node is not split any further
+const LEAF_SIZE: usize = 8;
+
+/// The number of points per block
+const BLOCK_LEN: usize = 1024;
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    position: Point3,
+    step_idx: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    min: Point3,
+    max: Point3,
+    /// The entries of this node
+    start: usize,
+    end: usize,
+    /// Indices of the child nodes, or `None` for leaves
+    children: Option<(usize, usize)>,
+}
+
+#[derive(Debug)]
+struct Block {
+    /// ordered such that the entries of each node are contiguous
+    entries: Vec<Entry>,
+    /// The root is the first node.
+    nodes: Vec<Node>,
+    /// The smallest and the largest step index of the entries
+    first_step: usize,
+    last_step: usize,
+}
+
+/// Clones share their blocks.
+#[derive(Clone, Debug, Default)]
+pub struct PointIndex {
+    /// All blocks but the last one are full, and none is empty.
+    blocks: Vec<Arc<Block>>,
+}
+
+impl PointIndex {
+    /// Indexes `points`, each tagged with the index of the step it belongs to.  The step indices
+    /// must not decrease.
+    pub fn new(points: impl IntoIterator<Item = (Position, usize)>) -> Self {
+        let mut index = Self::default();
+        index.extend(points);
+        index
+    }
+
+    /// Indexes further `points`, whose step indices must not be smaller than the indexed ones.
+    /// Only the last block is rebuilt.
+    pub fn extend(&mut self, points: impl IntoIterator<Item = (Position, usize)>) {
+        let mut points = points
+            .into_iter()
+            .map(|(position, step_idx)| Entry {
+                position: *position.as_point(),
+                step_idx,
+            })
+            .peekable();
+        while points.peek().is_some() {
+            let mut entries = match self.blocks.pop() {
+                Some(last) if last.entries.len() < BLOCK_LEN => Arc::try_unwrap(last)
+                    .map_or_else(|shared| shared.entries.clone(), |block| block.entries),
+                full => {
+                    self.blocks.extend(full);
+                    Vec::with_capacity(BLOCK_LEN)
+                }
+            };
+            entries.extend(points.by_ref().take(BLOCK_LEN - entries.len()));
+            self.blocks.push(Arc::new(Block::new(entries)));
+        }
+    }
+
+    /// Discards all points from the step `num_steps` on.
+    pub fn truncate(&mut self, num_steps: usize) {
+        while let Some(last) = self.blocks.last() {
+            if last.first_step >= num_steps {
+                self.blocks.pop();
+            } else {
+                if last.last_step >= num_steps {
+                    let entries = last
+                        .entries
+                        .iter()
+                        .filter(|entry| entry.step_idx < num_steps)
+                        .copied()
+                        .collect();
+                    self.blocks.pop();
+                    self.blocks.push(Arc::new(Block::new(entries)));
+                }
+                break;
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.blocks.iter().map(|block| block.entries.len()).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// All indexed points with their step indices (in no particular order)
+    pub fn points(&self) -> impl Iterator<Item = (Position, usize)> + '_ {
+        self.blocks.iter().flat_map(|block| {
+            block
+                .entries
+                .iter()
+                .map(|entry| (entry.position.into(), entry.step_idx))
+        })
+    }
+
+    /// The step index of the point closest to `pos`, and the distance to that point
+    #[must_use]
+    pub fn closest(&self, pos: &Position) -> Option<(usize, f32)> {
+        let pos = pos.as_point();
+        let mut best: Option<(usize, f32)> = None; // (step_idx, squared distance)
+        for block in &self.blocks {
+            block.closest(pos, &mut best);
+        }
+        best.map(|(step_idx, dist)| (step_idx, dist.sqrt()))
+    }
+}
+
+impl Block {
+    /// `entries` must not be empty.
+    fn new(entries: Vec<Entry>) -> Self {
+        let (first_step, last_step) = entries
+            .iter()
+            .fold((usize::MAX, 0), |(first, last), entry| {
+                (first.min(entry.step_idx), last.max(entry.step_idx))
+            });
+        let mut block = Self {
+            nodes: Vec::with_capacity(2 * entries.len() / LEAF_SIZE + 1),
+            entries,
+            first_step,
+            last_step,
+        };
+        block.build(0, block.entries.len());
+        block
+    }
+
+    /// Replaces `best` (the step index and the squared distance) by the closest point of this
+    /// block if that is closer to `pos`.
+    fn closest(&self, pos: &Point3, best: &mut Option<(usize, f32)>) {
+        let mut stack = vec![0];
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx];
+            if best.is_some_and(|(_, best_dist)| node.distance_squared(pos) >= best_dist) {
+                continue;
+            }
+            match node.children {
+                Some((left, right)) => {
+                    let (near, far) = if self.nodes[left].distance_squared(pos)
+                        <= self.nodes[right].distance_squared(pos)
+                    {
+                        (left, right)
+                    } else {
+                        (right, left)
+                    };
+                    // the near child is popped (and thus visited) first:
+                    stack.push(far);
+                    stack.push(near);
+                }
+                None => {
+                    for entry in &self.entries[node.start..node.end] {
+                        let dist = (entry.position - pos).norm_squared();
+                        if best.is_none_or(|(_, best_dist)| dist < best_dist) {
+                            *best = Some((entry.step_idx, dist));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Builds the node for the entries `start..end` and its descendants.  Returns the index of
+    /// the node.
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let entries = &mut self.entries[start..end];
+        let (min, max) = entries.iter().skip(1).fold(
+            (entries[0].position, entries[0].position),
+            |(min, max), entry| (min.inf(&entry.position), max.sup(&entry.position)),
+        );
+        let node_idx = self.nodes.len();
+        self.nodes.push(Node {
+            min,
+            max,
+            start,
+            end,
+            children: None,
+        });
+        if entries.len() > LEAF_SIZE {
+            let axis = (max - min).imax();
+            let mid = entries.len() / 2;
+            entries
+                .select_nth_unstable_by(mid, |a, b| a.position[axis].total_cmp(&b.position[axis]));
+            let left = self.build(start, start + mid);
+            let right = self.build(start + mid, end);
+            self.nodes[node_idx].children = Some((left, right));
+        }
+        node_idx
+    }
+}
+
+impl Node {
+    /// The squared distance of `pos` from the bounding box of the node (0 if inside)
+    fn distance_squared(&self, pos: &Point3) -> f32 {
+        Vec3::zeros()
+            .sup(&(self.min - pos))
+            .sup(&(pos - self.max))
+            .norm_squared()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PointIndex;
+    use crate::Position;
+
+    #[test]
+    fn closest_agrees_with_linear_search() {
+        #![allow(clippy::cast_precision_loss)]
+        // points on a spiral, several per step:
+        let points = (0..1_000)
+            .map(|i| {
+                let t = i as f32 * 0.05;
+                (Position::new(t.sin() * t, t.cos() * t, 0.), i / 3)
+            })
+            .collect::<Vec<_>>();
+        let index = PointIndex::new(points.iter().copied());
+        assert_eq!(index.len(), points.len());
+
+        for i in 0..200 {
+            let t = i as f32 * 0.37;
+            let pos = Position::new(t.cos() * 20. - 10., (t * 1.3).sin() * 30., 0.);
+            let expected = points
+                .iter()
+                .map(|(point, step_idx)| (point.distance_squared(pos).sqrt(), *step_idx))
+                .reduce(|a, b| if b.0 < a.0 { b } else { a })
+                .unwrap();
+            let (step_idx, distance) = index.closest(&pos).unwrap();
+            assert_eq!((distance, step_idx), expected, "pos = {:?}", pos);
+        }
+    }
+
+    #[test]
+    fn extended_and_truncated() {
+        #![allow(clippy::cast_precision_loss)]
+        let points = (0..3_000)
+            .map(|i| {
+                let t = i as f32 * 0.01;
+                (Position::new(t.cos() * t, t.sin(), 0.), i / 2)
+            })
+            .collect::<Vec<_>>();
+        let mut index = PointIndex::new(points[..100].iter().copied());
+        let shared = index.clone();
+        for chunk in points[100..].chunks(700) {
+            index.extend(chunk.iter().copied());
+        }
+        index.truncate(1_234);
+        let expected = PointIndex::new(
+            points
+                .iter()
+                .copied()
+                .filter(|&(_, step_idx)| step_idx < 1_234),
+        );
+        assert_eq!(index.len(), expected.len());
+        assert_eq!(shared.len(), 100);
+        for i in 0..100 {
+            let t = i as f32 * 0.29;
+            let pos = Position::new(t.cos() * 30., (t * 0.7).sin(), 0.);
+            assert_eq!(
+                index.closest(&pos),
+                expected.closest(&pos),
+                "pos = {:?}",
+                pos
+            );
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert!(PointIndex::default()
+            .closest(&Position::new(0., 0., 0.))
+            .is_none());
+        assert!(PointIndex::new(None).is_empty());
+    }
+}
@@ -0,0 +1,398 @@
+use super::{dense_output::DenseOutput, job::Progress, Duration, PointIndex, Samples, Scenario};
+use ::std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+};
+
+/// A lazily calculated value.  Its mutex is held during the calculation, so that concurrent
+/// requests for the same value wait for the first one instead of repeating the calculation.
+type Entry<T> = Arc<Mutex<Option<Arc<T>>>>;
+
+/// The exact trajectory, with a resolution suitable for step durations down to `min_dt`.  It may
+/// be longer than the duration of the scenario it has been requested for.
+#[derive(Default)]
+pub struct Trajectory {
+    pub min_dt: Duration,
+    /// Shared by a trajectory and its extensions, which start with the same nodes
+    lineage: u64,
+    /// of the particle
+    pub dense_output: DenseOutput,
+    /// of the further bodies of the scenario
+    pub bodies: Vec<DenseOutput>,
+}
+
+/// The samples of one body
+pub struct Track {
+    pub samples: Samples,
+    /// The positions of `samples`, for picking
+    pub point_index: PointIndex,
+    /// The indices of the steps which have bounced off an obstacle, in ascending order (only
+    /// for integrations)
+    pub bounces: Vec<usize>,
+}
+
+/// The reference samples for one step duration, and the trajectory they have been taken from.
+pub struct ReferenceSamples {
+    /// of the particle and the further bodies, in this order
+    pub tracks: Vec<Track>,
+    pub trajectory: Arc<Trajectory>,
+}
+
+impl Trajectory {
+    fn new(min_dt: Duration, lineage: u64, mut dense_outputs: Vec<DenseOutput>) -> Self {
+        let bodies = dense_outputs.split_off(1.min(dense_outputs.len()));
+        Self {
+            min_dt,
+            lineage,
+            dense_output: dense_outputs.pop().unwrap_or_default(),
+            bodies,
+        }
+    }
+
+    /// The dense output of the particle (0) or a further body
+    #[must_use]
+    pub fn body(&self, body: usize) -> Option<&DenseOutput> {
+        match body {
+            0 => Some(&self.dense_output),
+            _ => self.bodies.get(body - 1),
+        }
+    }
+
+    /// The dense outputs of the particle and the further bodies, in this order
+    pub fn dense_outputs(&self) -> impl Iterator<Item = &DenseOutput> {
+        ::std::iter::once(&self.dense_output).chain(&self.bodies)
+    }
+}
+
+impl Track {
+    #[must_use]
+    pub fn new(samples: Samples) -> Self {
+        Self {
+            point_index: PointIndex::new(samples.indexed_positions()),
+            samples,
+            bounces: Vec::new(),
+        }
+    }
+
+    /// The samples of `dense_output` like `new()` would take them, but continuing the reference
+    /// samples of `previous`, which have been taken with the same `dt` from a shorter version of
+    /// `dense_output`.  The last step of `previous` is taken again, as its end may have been
+    /// clamped to the end of the shorter version.
+    fn continued(
+        previous: &Self,
+        dense_output: &DenseOutput,
+        dt: Duration,
+        num_steps: usize,
+    ) -> Self {
+        let kept = previous.samples.len().saturating_sub(1);
+        let samples = dense_output.continued_samples(
+            previous.samples.first_steps(kept, num_steps),
+            dt,
+            num_steps,
+        );
+        let mut point_index = previous.point_index.clone();
+        point_index.truncate(kept);
+        point_index.extend(samples.indexed_positions_from(kept));
+        Self {
+            samples,
+            point_index,
+            bounces: Vec::new(),
+        }
+    }
+}
+
+impl ReferenceSamples {
+    /// The reference samples of the particle
+    ///
+    /// # Panics
+    ///
+    /// Never, as there is a track for the particle.
+    #[must_use]
+    pub fn samples(&self) -> &Samples {
+        &self.tracks[0].samples
+    }
+}
+
+/// Shares exact trajectories and reference samples between all integrations (and canvases) of
+/// the same scenario.  Safe to be used by concurrent [`crate::job::Job`]s.
+///
+/// Scenarios are identified by their `Scenario::hash_without_duration()`, so that changing the
+/// duration only extends the trajectory (if at all).
+#[derive(Default)]
+pub struct ReferenceCache {
+    /// keyed by scenario hash, step duration and number of steps
+    samples: Mutex<HashMap<(u64, Duration, usize), Entry<ReferenceSamples>>>,
+    trajectories: Mutex<HashMap<u64, Entry<Trajectory>>>,
+}
+
+impl ::std::fmt::Debug for ReferenceCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReferenceCache")
+            .field("samples", &lock(&self.samples).len())
+            .field("trajectories", &lock(&self.trajectories).len())
+            .finish()
+    }
+}
+
+impl ReferenceCache {
+    /// The samples are taken from the `trajectory()`.  Returns `None` if cancelled via
+    /// `progress`.
+    pub fn reference_samples(
+        &self,
+        scenario: &Scenario,
+        scenario_hash: u64,
+        dt: Duration,
+        progress: &Progress,
+    ) -> Option<Arc<ReferenceSamples>> {
+        #[allow(clippy::cast_sign_loss)]
+        #[allow(clippy::cast_possible_truncation)]
+        let num_steps = (scenario.duration / dt) as usize;
+        self.first_reference_samples(scenario, scenario_hash, dt, num_steps, progress)
+    }
+
+    /// Like `reference_samples()`, but only the first `num_steps` samples, e.g. for scenarios
+    /// which have been terminated by an event.
+    pub fn first_reference_samples(
+        &self,
+        scenario: &Scenario,
+        scenario_hash: u64,
+        dt: Duration,
+        num_steps: usize,
+        progress: &Progress,
+    ) -> Option<Arc<ReferenceSamples>> {
+        let entry = Arc::clone(
+            lock(&self.samples)
+                .entry((scenario_hash, dt, num_steps))
+                .or_default(),
+        );
+        let mut value = lock(&entry);
+        if value.is_none() {
+            let trajectory = self.trajectory(scenario, scenario_hash, dt, progress)?;
+            let previous =
+                self.shorter_reference_samples(scenario_hash, dt, num_steps, &trajectory);
+            let previous_tracks = previous.iter().flat_map(|previous| previous.tracks.iter());
+            *value = Some(Arc::new(ReferenceSamples {
+                tracks: trajectory
+                    .dense_outputs()
+                    .zip(previous_tracks.map(Some).chain(::std::iter::repeat(None)))
+                    .map(|(dense_output, previous)| match previous {
+                        Some(previous) => Track::continued(previous, dense_output, dt, num_steps),
+                        None => Track::new(dense_output.samples(dt, num_steps)),
+                    })
+                    .collect(),
+                trajectory,
+            }));
+        }
+        value.clone()
+    }
+
+    /// The cached reference samples with the most steps below `num_steps`, which have been
+    /// taken with the same `dt` from `trajectory` or a shorter version of it, e.g. before the
+    /// duration of the scenario was increased.  Entries which are being calculated are skipped,
+    /// so that this never waits for (nor deadlocks with) another calculation.
+    fn shorter_reference_samples(
+        &self,
+        scenario_hash: u64,
+        dt: Duration,
+        num_steps: usize,
+        trajectory: &Trajectory,
+    ) -> Option<Arc<ReferenceSamples>> {
+        let candidates = lock(&self.samples)
+            .iter()
+            .filter(|((hash, entry_dt, len), _)| {
+                *hash == scenario_hash && *entry_dt == dt && *len < num_steps
+            })
+            .map(|(&(_, _, len), entry)| (len, Arc::clone(entry)))
+            .collect::<Vec<_>>();
+        candidates
+            .into_iter()
+            .filter_map(|(len, entry)| {
+                let samples = entry.try_lock().ok()?.clone()?;
+                (samples.trajectory.lineage == trajectory.lineage).then_some((len, samples))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, samples)| samples)
+    }
+
+    /// Returns a trajectory with a resolution of at least `min_dt`, which covers the duration of
+    /// `scenario`, or `None` if cancelled via `progress`.  A cached trajectory which is too short
+    /// is extended instead of being recomputed.
+    pub fn trajectory(
+        &self,
+        scenario: &Scenario,
+        scenario_hash: u64,
+        min_dt: Duration,
+        progress: &Progress,
+    ) -> Option<Arc<Trajectory>> {
+        let entry = Arc::clone(lock(&self.trajectories).entry(scenario_hash).or_default());
+        let mut value = lock(&entry);
+        match &*value {
+            Some(cached)
+                if cached.min_dt <= min_dt && cached.dense_output.covers(scenario.duration) => {}
+            Some(cached) if cached.min_dt <= min_dt => {
+                let dense_outputs = cached.dense_outputs().collect::<Vec<_>>();
+                *value = Some(Arc::new(Trajectory::new(
+                    cached.min_dt,
+                    cached.lineage,
+                    scenario.extend_dense_outputs(&dense_outputs, progress)?,
+                )));
+            }
+            _ => {
+                static NEXT_LINEAGE: AtomicU64 = AtomicU64::new(1);
+                *value = Some(Arc::new(Trajectory::new(
+                    min_dt,
+                    NEXT_LINEAGE.fetch_add(1, Ordering::Relaxed),
+                    scenario.calculate_dense_outputs(min_dt, progress)?,
+                )));
+            }
+        }
+        value.clone()
+    }
+
+    /// Evicts all values of scenarios which are not in `scenario_hashes`, and all reference
+    /// samples which are not used anymore.
+    pub fn retain_scenarios(&self, scenario_hashes: &[u64]) {
+        lock(&self.samples).retain(|(scenario_hash, _, _), entry| {
+            scenario_hashes.contains(scenario_hash)
+                // the entry is only locked while somebody holds it, so this does not block:
+                && (Arc::strong_count(entry) > 1
+                    || lock(entry)
+                        .as_ref()
+                        .is_some_and(|samples| Arc::strong_count(samples) > 1))
+        });
+        lock(&self.trajectories).retain(|scenario_hash, _| scenario_hashes.contains(scenario_hash));
+    }
+}
+
+/// The cached values stay valid even if a calculation panicked.
+fn lock<T>(mutex: &Mutex<T>) -> ::std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::scenarios::CenterMass, Progress, ReferenceCache, Scenario};
+    use crate::{Position, Velocity};
+    use ::std::{collections::hash_map::DefaultHasher, hash::Hasher, sync::Arc};
+
+    #[test]
+    fn shared_and_evicted() {
+        let scenario = Scenario {
+            acceleration: Box::new(CenterMass),
+            start_position: Position::new(0., 1., 0.),
+            start_velocity: Velocity::new(1., 0., 0.),
+            duration: 1.0.into(),
+            event: None,
+            obstacles: Vec::new(),
+            mass: 1.,
+            bodies: Vec::new(),
+        };
+        let mut hasher = DefaultHasher::new();
+        scenario.hash_without_duration(&mut hasher);
+        let scenario_hash = hasher.finish();
+        let cache = ReferenceCache::default();
+        let progress = Progress::default();
+
+        let coarse = cache
+            .trajectory(&scenario, scenario_hash, 0.5.into(), &progress)
+            .unwrap();
+        let fine = cache
+            .trajectory(&scenario, scenario_hash, 0.25.into(), &progress)
+            .unwrap();
+        assert!(
+            fine.dense_output.node_positions(scenario.duration).count()
+                > coarse
+                    .dense_output
+                    .node_positions(scenario.duration)
+                    .count()
+        );
+        let reused = cache
+            .trajectory(&scenario, scenario_hash, 0.5.into(), &progress)
+            .unwrap();
+        assert!(Arc::ptr_eq(&fine, &reused));
+
+        let first = cache
+            .reference_samples(&scenario, scenario_hash, 0.25.into(), &progress)
+            .unwrap();
+        let second = cache
+            .reference_samples(&scenario, scenario_hash, 0.25.into(), &progress)
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(Arc::ptr_eq(&first.trajectory, &fine));
+        assert_eq!(first.samples().len(), 4);
+
+        cache.retain_scenarios(&[scenario_hash]);
+        let third = cache
+            .reference_samples(&scenario, scenario_hash, 0.25.into(), &progress)
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &third));
+
+        drop((first, second, third));
+        cache.retain_scenarios(&[scenario_hash]);
+        assert!(cache.samples.lock().unwrap().is_empty());
+        cache.retain_scenarios(&[]);
+        assert!(cache.trajectories.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn extended_with_duration() {
+        let mut scenario = Scenario {
+            acceleration: Box::new(CenterMass),
+            start_position: Position::new(0., 1., 0.),
+            start_velocity: Velocity::new(1., 0., 0.),
+            duration: 1.0.into(),
+            event: None,
+            obstacles: Vec::new(),
+            mass: 1.,
+            bodies: Vec::new(),
+        };
+        let mut hasher = DefaultHasher::new();
+        scenario.hash_without_duration(&mut hasher);
+        let scenario_hash = hasher.finish();
+        let cache = ReferenceCache::default();
+        let progress = Progress::default();
+
+        let short = cache
+            .trajectory(&scenario, scenario_hash, 0.25.into(), &progress)
+            .unwrap();
+        scenario.duration = 2.0.into();
+        let long = cache
+            .trajectory(&scenario, scenario_hash, 0.25.into(), &progress)
+            .unwrap();
+        assert!(!Arc::ptr_eq(&short, &long));
+        assert!(long.dense_output.covers(scenario.duration));
+        let nodes = short.dense_output.node_positions(1.0.into());
+        assert!(nodes
+            .zip(long.dense_output.node_positions(1.0.into()))
+            .all(|(a, b)| a == b));
+
+        scenario.duration = 1.5.into();
+        let reused = cache
+            .trajectory(&scenario, scenario_hash, 0.25.into(), &progress)
+            .unwrap();
+        assert!(Arc::ptr_eq(&long, &reused));
+        let samples = cache
+            .reference_samples(&scenario, scenario_hash, 0.25.into(), &progress)
+            .unwrap();
+        assert_eq!(samples.samples().len(), 6);
+
+        scenario.duration = 3.0.into();
+        let continued = cache
+            .reference_samples(&scenario, scenario_hash, 0.25.into(), &progress)
+            .unwrap();
+        let expected = continued.trajectory.dense_output.samples(0.25.into(), 12);
+        assert_eq!(continued.samples().len(), 12);
+        assert!(continued
+            .samples()
+            .steps_iter()
+            .zip(expected.steps_iter())
+            .all(|(a, b)| a.next_condition() == b.next_condition()));
+        assert_eq!(
+            continued.tracks[0].point_index.len(),
+            expected.indexed_positions().count()
+        );
+    }
+}
@@ -1,19 +1,28 @@
 mod type_state {
     pub trait TypeState {}
 
+    #[derive(Clone)]
     pub struct Finalized {}
+    #[derive(Clone)]
     pub struct NonFinalized {}
 
     impl TypeState for Finalized {}
     impl TypeState for NonFinalized {}
 }
 
-use super::{Position, Step};
+use super::{
+    integration_step::{Arena, Header, StepMut},
+    Duration, Position, StartCondition, Step,
+};
 use ::std::marker::PhantomData;
-use type_state::{Finalized, NonFinalized, TypeState};
+pub(crate) use type_state::NonFinalized;
+use type_state::{Finalized, TypeState};
 
+/// All quantities of all steps are stored in a single arena, which is freed at once when the
+/// samples are dropped.
 pub struct Samples<TS: TypeState = Finalized> {
-    steps: Vec<Step>,
+    arena: Arena,
+    steps: Vec<Header>,
     type_state: PhantomData<TS>,
 }
 
@@ -23,20 +32,56 @@ impl Samples {
     #[must_use]
     pub fn new(sample_capacity: usize) -> Samples<NonFinalized> {
         Samples::<NonFinalized> {
+            arena: Arena::default(),
             steps: Vec::with_capacity(sample_capacity),
             type_state: PhantomData::<NonFinalized>,
         }
     }
 }
 
+impl<TS: TypeState> Samples<TS> {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    #[must_use]
+    pub fn at(&self, idx: usize) -> Step<'_> {
+        Step::in_arena(&self.arena, self.steps[idx])
+    }
+
+    /// Like `at()`, but `None` if there is no step at `idx`
+    #[must_use]
+    pub fn get(&self, idx: usize) -> Option<Step<'_>> {
+        Some(Step::in_arena(&self.arena, *self.steps.get(idx)?))
+    }
+}
+
 impl Samples<NonFinalized> {
-    pub fn push_sample(&mut self, step: Step) {
-        self.steps.push(step);
+    /// Appends a new step, to be computed e.g. by an `integration_step::builders::Step`.
+    pub fn push_step(&mut self, start_condition: &StartCondition, dt: Duration) -> StepMut<'_> {
+        self.steps.push(Header::next_in(&self.arena, dt));
+        let last = self.steps.len() - 1;
+        StepMut::start(&mut self.arena, &mut self.steps[last], start_condition)
+    }
+
+    /// Discards all steps from `len` on, e.g. to continue the integration from an earlier step.
+    pub fn truncate(&mut self, len: usize) {
+        if let Some(&first_discarded) = self.steps.get(len) {
+            first_discarded.truncate_from(&mut self.arena);
+            self.steps.truncate(len);
+        }
     }
 
     #[must_use]
     pub fn finalized(self) -> Samples<Finalized> {
         Samples {
+            arena: self.arena,
             steps: self.steps,
             type_state: PhantomData::<Finalized>,
         }
@@ -44,40 +89,113 @@ impl Samples<NonFinalized> {
 }
 
 impl Samples<Finalized> {
+    /// Allows to append further steps.
     #[must_use]
-    pub fn len(&self) -> usize {
-        self.steps.len()
+    pub fn reopened(self) -> Samples<NonFinalized> {
+        Samples {
+            arena: self.arena,
+            steps: self.steps,
+            type_state: PhantomData::<NonFinalized>,
+        }
     }
 
+    /// A copy of the first `len` steps, e.g. to be continued (when the duration of the scenario
+    /// is increased) while these samples are still in use.  As the arena is contiguous, only its
+    /// prefix up to step `len` is copied.
     #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.steps.is_empty()
+    pub fn first_steps(&self, len: usize, sample_capacity: usize) -> Samples<NonFinalized> {
+        let len = len.min(self.len());
+        let mut steps = Vec::with_capacity(sample_capacity.max(len));
+        steps.extend_from_slice(&self.steps[..len]);
+        Samples {
+            arena: self.steps.get(len).map_or_else(
+                || self.arena.clone(),
+                |header| header.preceding_in(&self.arena),
+            ),
+            steps,
+            type_state: PhantomData::<NonFinalized>,
+        }
     }
 
     pub fn step_positions(&self) -> impl Iterator<Item = Position> + '_ {
-        self.steps.iter().map(Step::last_s)
+        self.steps_iter().map(|step| step.last_s())
     }
 
-    #[must_use]
-    pub fn at(&self, idx: usize) -> &Step {
-        &self.steps[idx]
+    pub fn steps_iter(&self) -> impl Iterator<Item = Step<'_>> {
+        self.steps
+            .iter()
+            .map(|&header| Step::in_arena(&self.arena, header))
     }
 
-    #[must_use]
-    pub fn closest(&self, pos: &Position) -> Option<SampleIdxWithDistance> {
+    /// All computed positions, each tagged with the index of its step
+    pub fn indexed_positions(&self) -> impl Iterator<Item = (Position, usize)> + '_ {
+        self.indexed_positions_from(0)
+    }
+
+    /// Like `indexed_positions()`, but only of the steps from `first` on
+    pub fn indexed_positions_from(
+        &self,
+        first: usize,
+    ) -> impl Iterator<Item = (Position, usize)> + '_ {
         self.steps
             .iter()
             .enumerate()
-            .map(|(index, step)| SampleIdxWithDistance {
-                distance: step.distance_to(pos),
-                index,
+            .skip(first)
+            .flat_map(|(index, header)| {
+                header
+                    .positions_in(&self.arena)
+                    .map(move |position| (position, index))
             })
-            .reduce(|a, b| if a.distance < b.distance { a } else { b })
     }
 }
 
-#[derive(Clone, Copy)]
-pub struct SampleIdxWithDistance {
-    pub distance: f32,
-    pub index: usize,
+#[cfg(test)]
+mod tests {
+    use super::Samples;
+    use crate::{
+        integration_step::builders, integrators::mid_point, scenarios::CenterMass,
+        AccelerationField, Integrator, Position, StartCondition, Velocity,
+    };
+
+    #[test]
+    fn steps_share_one_arena() {
+        let position = Position::new(0., 1., 0.);
+        let mut start_condition = StartCondition::new(
+            position,
+            Velocity::new(1., 0., 0.),
+            CenterMass.value_at(position),
+        );
+        let mut samples = Samples::new(2);
+        for _ in 0..2 {
+            let mut step = samples.push_step(&start_condition, 0.1.into());
+            let mut builder = builders::Step::new(&CenterMass, &mut step);
+            let ((s, v, a), dt) = (builder.start_values(), builder.dt());
+            mid_point::SecondOrder.integrate_step(s, v, a, dt, &mut builder);
+            builder.finalize();
+            start_condition = step.next_condition();
+        }
+        let samples = samples.finalized();
+
+        let (first, second) = (samples.at(0), samples.at(1));
+        assert_eq!(second.get_start_condition(), first.next_condition());
+        assert_eq!(
+            first.positions_iter().count(),
+            second.positions_iter().count()
+        );
+        for step in [first, second] {
+            let contributions = step.last_computed_position().contributions_iter().count();
+            assert_eq!(contributions, 3); // s + v dt + ½ a dt²
+        }
+
+        let continued = samples.first_steps(1, 2).finalized();
+        assert_eq!(continued.len(), 1);
+        assert!(continued.get(1).is_none());
+        assert_eq!(
+            continued.at(0).next_condition(),
+            samples.at(0).next_condition()
+        );
+        assert!(continued.indexed_positions().eq(samples
+            .indexed_positions()
+            .take_while(|&(_, index)| index == 0)));
+    }
 }
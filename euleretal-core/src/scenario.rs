@@ -1,5 +1,13 @@
 use super::scenarios;
-use super::{AccelerationField, Duration, Position, Samples, StartCondition, Step, Velocity};
+use super::{
+    bodies::{Body, System},
+    dense_output::DenseOutput,
+    event::Event,
+    import::OrderedF32,
+    job::Progress,
+    obstacle::Obstacle,
+    AccelerationField, Duration, Position, StartCondition, Velocity,
+};
 use ::std::{collections::hash_map::DefaultHasher, hash::Hash};
 
 #[derive(::serde::Serialize, ::serde::Deserialize)]
@@ -9,6 +17,18 @@ pub struct Scenario {
     pub start_position: Position,
     pub start_velocity: Velocity,
     pub duration: Duration,
+    /// If terminal, the scenario ends with the first occurrence of the event (or at `duration`
+    /// if it does not occur before).
+    #[serde(default)]
+    pub event: Option<Event>,
+    #[serde(default)]
+    pub obstacles: Vec<Obstacle>,
+    /// The mass of the particle, which only matters for its attraction on the `bodies`
+    #[serde(default = "Scenario::default_mass")]
+    pub mass: f32,
+    /// Further bodies, which attract each other and the particle
+    #[serde(default)]
+    pub bodies: Vec<Body>,
 }
 
 impl ::std::fmt::Debug for Scenario {
@@ -18,150 +38,252 @@ impl ::std::fmt::Debug for Scenario {
             .field("start_position", &self.start_position)
             .field("start_velocity", &self.start_velocity)
             .field("duration", &self.duration)
+            .field("event", &self.event)
+            .field("obstacles", &self.obstacles)
+            .field("mass", &self.mass)
+            .field("bodies", &self.bodies)
             .finish()
     }
 }
 
+impl Clone for Scenario {
+    fn clone(&self) -> Self {
+        Self {
+            acceleration: self.acceleration.to_concrete_type().into_box(),
+            start_position: self.start_position,
+            start_velocity: self.start_velocity,
+            duration: self.duration,
+            event: self.event,
+            obstacles: self.obstacles.clone(),
+            mass: self.mass,
+            bodies: self.bodies.clone(),
+        }
+    }
+}
+
 const STEPS_PER_DT: usize = 40;
 
 impl Scenario {
+    fn default_mass() -> f32 {
+        1.
+    }
+
     #[must_use]
     pub fn label(&self) -> String {
-        self.acceleration.label()
+        if self.bodies.is_empty() {
+            self.acceleration.label()
+        } else {
+            format!(
+                "{} ({} Bodies)",
+                self.acceleration.label(),
+                self.num_bodies()
+            )
+        }
+    }
+
+    /// The particle and the further bodies
+    #[must_use]
+    pub fn num_bodies(&self) -> usize {
+        1 + self.bodies.len()
+    }
+
+    /// The acceleration field and the masses of the particle and the further bodies, in this
+    /// order
+    #[must_use]
+    pub fn system(&self) -> System<'_> {
+        System::new(
+            &*self.acceleration,
+            ::std::iter::once(self.mass)
+                .chain(self.bodies.iter().map(|body| body.mass))
+                .collect(),
+        )
+    }
+
+    /// The start conditions of the particle and the further bodies, in this order
+    #[must_use]
+    pub fn start_conditions(&self) -> Vec<StartCondition> {
+        self.system().start_conditions(&self.starts())
+    }
+
+    fn starts(&self) -> Vec<(Position, Velocity)> {
+        ::std::iter::once((self.start_position, self.start_velocity))
+            .chain(
+                self.bodies
+                    .iter()
+                    .map(|body| (body.position, body.velocity)),
+            )
+            .collect()
+    }
+
+    /// Checks if the scenario can be integrated.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first invalid attribute.
+    pub fn validate(&self) -> Result<(), String> {
+        let duration = f32::from(self.duration);
+        if !duration.is_finite() || duration <= 0. {
+            return Err(format!("Duration must be positive (is {}).", self.duration));
+        }
+        if !self
+            .start_position
+            .as_vector()
+            .iter()
+            .all(|c| c.is_finite())
+        {
+            return Err("Start position must be finite.".to_string());
+        }
+        if !self
+            .start_velocity
+            .as_vector()
+            .iter()
+            .all(|c| c.is_finite())
+        {
+            return Err("Start velocity must be finite.".to_string());
+        }
+        self.acceleration.validate()?;
+        if !self.mass.is_finite() || self.mass < 0. {
+            return Err(format!("Mass must not be negative (is {}).", self.mass));
+        }
+        for obstacle in &self.obstacles {
+            obstacle.validate()?;
+        }
+        for body in &self.bodies {
+            body.validate()?;
+        }
+        Ok(())
     }
 
     pub fn hash_default(&self, state: &mut DefaultHasher) {
+        self.hash_without_duration(state);
+        self.duration.hash(state);
+        self.event.hash(state);
+    }
+
+    /// Scenarios which only differ in their duration (or event) share the same trajectory, which
+    /// just ends at different times.
+    pub fn hash_without_duration(&self, state: &mut DefaultHasher) {
         self.acceleration.hash(state);
         self.start_position.hash(state);
         self.start_velocity.hash(state);
-        self.duration.hash(state);
+        self.obstacles.hash(state);
+        OrderedF32::from(self.mass).hash(state);
+        self.bodies.hash(state);
     }
 
+    /// The reference solutions of the particle and the further bodies, computed with steps of
+    /// `min_dt / STEPS_PER_DT` at most.  Returns `None` if cancelled via `progress`.
     #[must_use]
-    pub fn calculate_trajectory(&self, min_dt: Duration) -> Vec<Position> {
+    pub fn calculate_dense_outputs(
+        &self,
+        min_dt: Duration,
+        progress: &Progress,
+    ) -> Option<Vec<DenseOutput>> {
         #[cfg(not(target_arch = "wasm32"))]
         let start = ::std::time::Instant::now();
 
         #[allow(clippy::cast_sign_loss)]
         #[allow(clippy::cast_possible_truncation)]
         #[allow(clippy::cast_precision_loss)]
-        let num_steps = (self.duration / min_dt * STEPS_PER_DT as f32) as usize;
-        let (trajectory, _samples) = calculate_trajectory_and_samples(
-            &*self.acceleration,
-            self.start_position,
-            self.start_velocity,
-            1,
+        let num_steps = (self.duration / min_dt * STEPS_PER_DT as f32).ceil() as usize;
+        let dense_outputs = DenseOutput::integrate_ensemble(
+            &self.system(),
+            &self.obstacles,
+            &self.starts(),
             self.duration,
             num_steps,
-        );
+            progress,
+        )?;
         #[cfg(not(target_arch = "wasm32"))]
         log::debug!(
-            "{}: trajectory with {} segments: {}µs",
+            "{}: dense output with {} steps: {}µs",
             self.label(),
-            trajectory.len(),
+            num_steps,
             start.elapsed().as_micros()
         );
-        trajectory
+        Some(dense_outputs)
     }
 
+    /// Continues `dense_outputs` (of this scenario, but possibly of a shorter duration) with the
+    /// same step size until the duration of the scenario is covered.  Returns `None` if
+    /// cancelled via `progress`.
     #[must_use]
-    pub fn calc_intermediate_sample(&self, start_condition: &StartCondition, dt: Duration) -> Step {
-        let (_, samples) = calculate_trajectory_and_samples(
-            &*self.acceleration,
-            start_condition.position(),
-            start_condition.velocity(),
-            1,
-            dt,
-            STEPS_PER_DT,
-        );
-        samples.at(0).clone()
-    }
-
-    #[must_use]
-    pub fn calculate_reference_samples(&self, dt: Duration) -> Samples {
-        #[cfg(not(target_arch = "wasm32"))]
-        let start = ::std::time::Instant::now();
-
-        #[allow(clippy::cast_sign_loss)]
-        #[allow(clippy::cast_possible_truncation)]
-        let num_iterations = (self.duration / dt) as usize;
-        let (_trajectory, samples) = calculate_trajectory_and_samples(
-            &*self.acceleration,
-            self.start_position,
-            self.start_velocity,
-            num_iterations,
-            dt,
-            STEPS_PER_DT,
-        );
-        #[cfg(not(target_arch = "wasm32"))]
-        log::debug!(
-            "{}: {} reference samples: {}µs",
-            self.label(),
-            samples.len(),
-            start.elapsed().as_micros()
-        );
-        samples
+    pub fn extend_dense_outputs(
+        &self,
+        dense_outputs: &[&DenseOutput],
+        progress: &Progress,
+    ) -> Option<Vec<DenseOutput>> {
+        DenseOutput::extended_ensemble(
+            dense_outputs,
+            &self.system(),
+            &self.obstacles,
+            self.duration,
+            progress,
+        )
     }
 }
 
-/// returns (trajectory, samples)
-fn calculate_trajectory_and_samples(
-    acceleration: &dyn AccelerationField,
-    start_position: Position,
-    start_velocity: Velocity,
-    iterations: usize,
-    dt: Duration,
-    steps_per_dt: usize,
-) -> (Vec<Position>, Samples) {
-    #![allow(clippy::cast_precision_loss)]
-
-    let mut t0 = 0.0.into();
-    let mut s0 = start_position;
-    let mut v0 = start_velocity;
-    let mut a0 = acceleration.value_at(s0);
-
-    let mut trajectory = Vec::with_capacity(iterations * steps_per_dt + 1);
-    trajectory.push(s0);
-    let mut samples = Samples::new(iterations);
-
-    let mut step = Step::new(&StartCondition::new(s0, v0, a0), dt);
-    let steps_per_dt_float = steps_per_dt as f32;
-    let div_by_steps_per_dt = 1_f32 / steps_per_dt_float;
-    let div_by_6 = 1_f32 / 6_f32;
-    let mut t1 = Duration::default();
-    for _ in 0..iterations {
-        t1 += dt;
-        // let mut new_step = Step::new_deprecated(step_capacities, dt);
-        // new_step.set_start_condition(&StartCondition::new(s0, v0, a0));
-        let mut ti0 = t0;
-        let mut intermediate_step_count = 0_f32;
-        for _ in 0..steps_per_dt {
-            intermediate_step_count += 1_f32;
-            let ti1 = (t0 * (steps_per_dt_float - intermediate_step_count)
-                + t1 * intermediate_step_count)
-                * div_by_steps_per_dt;
-            let h = ti1 - ti0;
-
-            a0 = acceleration.value_at(s0);
-            // let v1_tmp = v0 + a0 * h;
-            // let s1_tmp = s0 + v0 * h + a0 * h * h; // std. Euler.  Good for circles
-            let s1_tmp = s0 + v0 * h + 0.5 * a0 * h * h; // Exact for uniform acceleration
-            let a1 = acceleration.value_at(s1_tmp);
-            let v1 = v0 + 0.5 * (a0 + a1) * h;
-            let s1 = s0 + v0 * h + (2. * a0 + a1) * div_by_6 * h * h;
-
-            ti0 = ti1;
-            s0 = s1;
-            v0 = v1;
-            a0 = a1;
-            trajectory.push(s0);
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::scenarios::{CenterMass, DoubleWell, HarmonicOscillator, QuarticOscillator},
+        Body, Position, Scenario, Velocity,
+    };
+
+    fn scenario() -> Scenario {
+        Scenario {
+            acceleration: Box::new(CenterMass),
+            start_position: Position::new(0., 1., 0.),
+            start_velocity: Velocity::new(1., 0., 0.),
+            duration: 1.0.into(),
+            event: None,
+            obstacles: Vec::new(),
+            mass: 1.,
+            bodies: Vec::new(),
         }
-        t0 = t1;
-        step.raw_end_condition(s0, v0, a0);
-        let next_step = step.create_next();
-        samples.push_sample(step);
-        step = next_step;
     }
 
-    (trajectory, samples.finalized())
+    #[test]
+    fn validate() {
+        assert!(scenario().validate().is_ok());
+
+        let mut zero_duration = scenario();
+        zero_duration.duration = 0.0.into();
+        assert!(zero_duration.validate().is_err());
+
+        let mut infinite_velocity = scenario();
+        infinite_velocity.start_velocity = Velocity::new(f32::INFINITY, 0., 0.);
+        assert!(infinite_velocity.validate().is_err());
+
+        let mut negative_mass = scenario();
+        negative_mass
+            .bodies
+            .push(Body::new(-1., Position::origin(), Velocity::zeros()));
+        assert!(negative_mass.validate().is_err());
+
+        let mut field = scenario();
+        field.acceleration = Box::new(HarmonicOscillator {
+            omega_x: -1.,
+            omega_y: 1.,
+        });
+        assert!(field.validate().is_err());
+        field.acceleration = Box::new(QuarticOscillator {
+            stiffness: -1.,
+            anharmonicity: 1.,
+        });
+        assert!(field.validate().is_err());
+        // a well within a barrier:
+        field.acceleration = Box::new(QuarticOscillator {
+            stiffness: 1.,
+            anharmonicity: -1.,
+        });
+        assert!(field.validate().is_ok());
+        field.acceleration = Box::new(DoubleWell {
+            separation: 0.,
+            barrier: 1.,
+        });
+        assert!(field.validate().is_err());
+        field.acceleration = Box::new(DoubleWell::default());
+        assert!(field.validate().is_ok());
+    }
 }
@@ -9,6 +9,19 @@ impl AccelerationField for CenterMass {
         (-pos.as_vector() * distance_squared_recip.sqrt() * distance_squared_recip).into()
     }
 
+    fn values_at(&self, positions: &[Position], accelerations: &mut [Acceleration]) {
+        assert_eq!(positions.len(), accelerations.len());
+        for (a, pos) in accelerations.iter_mut().zip(positions) {
+            let s = pos.as_vector();
+            let distance_squared_recip = s.norm_squared().recip();
+            *a = (-s * distance_squared_recip.sqrt() * distance_squared_recip).into();
+        }
+    }
+
+    fn potential_at(&self, pos: Position) -> Option<f32> {
+        Some(-pos.as_vector().norm().recip())
+    }
+
     fn label(&self) -> String {
         "Gravity".to_string()
     }
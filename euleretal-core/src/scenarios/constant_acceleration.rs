@@ -8,6 +8,15 @@ impl AccelerationField for ConstantAcceleration {
         Acceleration::new(0., -1., 0.)
     }
 
+    fn values_at(&self, positions: &[Position], accelerations: &mut [Acceleration]) {
+        assert_eq!(positions.len(), accelerations.len());
+        accelerations.fill(self.value_at(Position::origin()));
+    }
+
+    fn potential_at(&self, pos: Position) -> Option<f32> {
+        Some(pos.as_point().y)
+    }
+
     fn label(&self) -> String {
         "Constant Acceleration".to_string()
     }
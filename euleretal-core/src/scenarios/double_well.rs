@@ -0,0 +1,84 @@
+use crate::{import::OrderedF32, Acceleration, AccelerationField, Position};
+use ::std::{any::TypeId, collections::hash_map::DefaultHasher, hash::Hash};
+
+/// Two wells at `x = ±separation`, divided by a barrier of height `barrier` at `x = 0`:
+/// `V = barrier·((x/separation)² − 1)²`.  Across the x axis, the particle is bound by a spring
+/// which is as stiff as the bottom of the wells.  Particles with about the energy of the barrier
+/// are very sensitive to errors, as these decide in which well they end up.
+#[derive(Clone, Copy, ::serde::Deserialize, ::serde::Serialize)]
+pub struct DoubleWell {
+    pub separation: f32,
+    pub barrier: f32,
+}
+
+impl Default for DoubleWell {
+    fn default() -> Self {
+        Self {
+            separation: 1.,
+            barrier: 0.5,
+        }
+    }
+}
+
+impl DoubleWell {
+    /// The curvature of the potential at the bottom of the wells
+    fn stiffness(self) -> f32 {
+        8. * self.barrier / (self.separation * self.separation)
+    }
+}
+
+impl AccelerationField for DoubleWell {
+    fn value_at(&self, pos: Position) -> Acceleration {
+        let s = pos.as_vector();
+        let a2_recip = (self.separation * self.separation).recip();
+        let k = self.stiffness();
+        Acceleration::new(
+            -4. * self.barrier * a2_recip * s.x * (s.x * s.x * a2_recip - 1.),
+            -k * s.y,
+            -k * s.z,
+        )
+    }
+
+    fn values_at(&self, positions: &[Position], accelerations: &mut [Acceleration]) {
+        assert_eq!(positions.len(), accelerations.len());
+        let a2_recip = (self.separation * self.separation).recip();
+        let (c, k) = (-4. * self.barrier * a2_recip, self.stiffness());
+        for (a, pos) in accelerations.iter_mut().zip(positions) {
+            let s = pos.as_vector();
+            *a = Acceleration::new(c * s.x * (s.x * s.x * a2_recip - 1.), -k * s.y, -k * s.z);
+        }
+    }
+
+    fn potential_at(&self, pos: Position) -> Option<f32> {
+        let s = pos.as_vector();
+        let well = (s.x / self.separation).powi(2) - 1.;
+        Some(self.barrier * well * well + 0.5 * self.stiffness() * (s.y * s.y + s.z * s.z))
+    }
+
+    fn label(&self) -> String {
+        "Double Well".to_string()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (name, value) in [("Separation", self.separation), ("Barrier", self.barrier)] {
+            if !value.is_finite() || value <= 0. {
+                return Err(format!("{} must be positive (is {}).", name, value));
+            }
+        }
+        Ok(())
+    }
+
+    fn hash(&self, state: &mut DefaultHasher) {
+        TypeId::of::<Self>().hash(state);
+        OrderedF32::from(self.separation).hash(state);
+        OrderedF32::from(self.barrier).hash(state);
+    }
+
+    fn to_concrete_type(
+        &self,
+    ) -> crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe {
+        crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe::DoubleWell(
+            *self,
+        )
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{Acceleration, AccelerationField, Position};
+
+/// No acceleration at all, e.g. for bodies which only attract each other.
+#[derive(Clone, Copy, Default, ::serde::Deserialize, ::serde::Serialize)]
+pub struct FreeSpace;
+
+impl AccelerationField for FreeSpace {
+    fn value_at(&self, _pos: Position) -> Acceleration {
+        Acceleration::zeros()
+    }
+
+    fn values_at(&self, positions: &[Position], accelerations: &mut [Acceleration]) {
+        assert_eq!(positions.len(), accelerations.len());
+        accelerations.fill(Acceleration::zeros());
+    }
+
+    fn potential_at(&self, _pos: Position) -> Option<f32> {
+        Some(0.)
+    }
+
+    fn label(&self) -> String {
+        "Free Space".to_string()
+    }
+
+    fn to_concrete_type(
+        &self,
+    ) -> crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe {
+        crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe::FreeSpace(*self)
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{import::OrderedF32, Acceleration, AccelerationField, Duration, Position, Velocity};
+use ::std::{any::TypeId, collections::hash_map::DefaultHasher, hash::Hash};
+
+/// A linear spring which pulls towards the origin, with the angular frequency `omega_x` along
+/// the x (and z) axis and `omega_y` along the y axis.  Isotropic oscillators move on ellipses,
+/// anisotropic ones on Lissajous figures.  The exact motion is known.
+#[derive(Clone, Copy, ::serde::Deserialize, ::serde::Serialize)]
+pub struct HarmonicOscillator {
+    pub omega_x: f32,
+    pub omega_y: f32,
+}
+
+impl Default for HarmonicOscillator {
+    fn default() -> Self {
+        Self {
+            omega_x: 1.,
+            omega_y: 1.,
+        }
+    }
+}
+
+impl HarmonicOscillator {
+    /// An anisotropic oscillator with frequencies in the ratio 2:3
+    #[must_use]
+    pub fn lissajous() -> Self {
+        Self {
+            omega_x: 2.,
+            omega_y: 3.,
+        }
+    }
+
+    #[must_use]
+    #[allow(clippy::float_cmp)]
+    pub fn is_isotropic(self) -> bool {
+        self.omega_x == self.omega_y
+    }
+
+    fn omegas(self) -> [f32; 3] {
+        [self.omega_x, self.omega_y, self.omega_x]
+    }
+}
+
+/// The motion along one axis with angular frequency `omega`, after time `t`
+fn oscillation(omega: f32, (s0, v0): (f32, f32), t: f32) -> (f32, f32) {
+    if omega == 0. {
+        return (s0 + v0 * t, v0);
+    }
+    let (sin, cos) = (omega * t).sin_cos();
+    (s0 * cos + v0 / omega * sin, v0 * cos - s0 * omega * sin)
+}
+
+impl AccelerationField for HarmonicOscillator {
+    fn value_at(&self, pos: Position) -> Acceleration {
+        let s = pos.as_vector();
+        let [wx, wy, wz] = self.omegas();
+        Acceleration::new(-wx * wx * s.x, -wy * wy * s.y, -wz * wz * s.z)
+    }
+
+    fn values_at(&self, positions: &[Position], accelerations: &mut [Acceleration]) {
+        assert_eq!(positions.len(), accelerations.len());
+        let [kx, ky, kz] = self.omegas().map(|omega| -omega * omega);
+        for (a, pos) in accelerations.iter_mut().zip(positions) {
+            let s = pos.as_vector();
+            *a = Acceleration::new(kx * s.x, ky * s.y, kz * s.z);
+        }
+    }
+
+    fn potential_at(&self, pos: Position) -> Option<f32> {
+        Some(
+            0.5 * self
+                .omegas()
+                .iter()
+                .zip(pos.as_vector().iter())
+                .map(|(omega, s)| omega * omega * s * s)
+                .sum::<f32>(),
+        )
+    }
+
+    fn exact_motion(
+        &self,
+        (start_position, start_velocity): (Position, Velocity),
+        t: Duration,
+    ) -> Option<(Position, Velocity)> {
+        let (s0, v0) = (start_position.as_vector(), start_velocity.as_vector());
+        let [x, y, z] =
+            [0, 1, 2].map(|axis| oscillation(self.omegas()[axis], (s0[axis], v0[axis]), t.into()));
+        Some((Position::new(x.0, y.0, z.0), Velocity::new(x.1, y.1, z.1)))
+    }
+
+    fn label(&self) -> String {
+        if self.is_isotropic() {
+            "Harmonic Oscillator".to_string()
+        } else {
+            format!("Lissajous {}:{}", self.omega_x, self.omega_y)
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (name, omega) in [("ωx", self.omega_x), ("ωy", self.omega_y)] {
+            if !omega.is_finite() || omega < 0. {
+                return Err(format!("{} must not be negative (is {}).", name, omega));
+            }
+        }
+        Ok(())
+    }
+
+    fn hash(&self, state: &mut DefaultHasher) {
+        TypeId::of::<Self>().hash(state);
+        OrderedF32::from(self.omega_x).hash(state);
+        OrderedF32::from(self.omega_y).hash(state);
+    }
+
+    fn to_concrete_type(
+        &self,
+    ) -> crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe {
+        crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe::HarmonicOscillator(*self)
+    }
+}
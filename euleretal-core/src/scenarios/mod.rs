@@ -1,13 +1,27 @@
 mod center_mass;
 mod constant_acceleration;
+mod double_well;
+mod free_space;
+mod harmonic_oscillator;
+pub mod n_body;
+mod point_mass;
+mod quartic_oscillator;
 
 pub use center_mass::CenterMass;
 pub use constant_acceleration::ConstantAcceleration;
+pub use double_well::DoubleWell;
+pub use free_space::FreeSpace;
+pub use harmonic_oscillator::HarmonicOscillator;
+pub use point_mass::PointMass;
+pub use quartic_oscillator::QuarticOscillator;
 
 /// Use this mod in `#[serde(with="<path_to_this_mod>")]` if you need to serialize an attribute of
 /// type `Box<dyn AccelerationField>`
 pub mod serde_box_dyn_acceleration_field {
-    use super::{CenterMass, ConstantAcceleration};
+    use super::{
+        CenterMass, ConstantAcceleration, DoubleWell, FreeSpace, HarmonicOscillator, PointMass,
+        QuarticOscillator,
+    };
     use crate::AccelerationField;
     use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -15,6 +29,41 @@ pub mod serde_box_dyn_acceleration_field {
     pub enum AccelerationFieldSerDe {
         CenterMass(#[serde(skip)] CenterMass),
         ConstantAcceleration(#[serde(skip)] ConstantAcceleration),
+        FreeSpace(#[serde(skip)] FreeSpace),
+        PointMass(PointMass),
+        HarmonicOscillator(HarmonicOscillator),
+        QuarticOscillator(QuarticOscillator),
+        DoubleWell(DoubleWell),
+    }
+
+    impl AccelerationFieldSerDe {
+        /// One instance of each kind of acceleration field, e.g. to let the user choose one.
+        #[must_use]
+        pub fn variants() -> Vec<Self> {
+            vec![
+                Self::CenterMass(CenterMass),
+                Self::ConstantAcceleration(ConstantAcceleration),
+                Self::FreeSpace(FreeSpace),
+                Self::PointMass(PointMass::default()),
+                Self::HarmonicOscillator(HarmonicOscillator::default()),
+                Self::HarmonicOscillator(HarmonicOscillator::lissajous()),
+                Self::QuarticOscillator(QuarticOscillator::default()),
+                Self::DoubleWell(DoubleWell::default()),
+            ]
+        }
+
+        #[must_use]
+        pub fn into_box(self) -> Box<dyn AccelerationField> {
+            match self {
+                Self::CenterMass(accel) => Box::new(accel),
+                Self::ConstantAcceleration(accel) => Box::new(accel),
+                Self::FreeSpace(accel) => Box::new(accel),
+                Self::PointMass(accel) => Box::new(accel),
+                Self::HarmonicOscillator(accel) => Box::new(accel),
+                Self::QuarticOscillator(accel) => Box::new(accel),
+                Self::DoubleWell(accel) => Box::new(accel),
+            }
+        }
     }
 
     #[allow(clippy::borrowed_box)]
@@ -34,13 +83,6 @@ pub mod serde_box_dyn_acceleration_field {
     where
         D: Deserializer<'de>,
     {
-        Ok(match AccelerationFieldSerDe::deserialize(deserializer)? {
-            AccelerationFieldSerDe::CenterMass(accel) => {
-                Box::new(accel) as Box<dyn AccelerationField>
-            }
-            AccelerationFieldSerDe::ConstantAcceleration(accel) => {
-                Box::new(accel) as Box<dyn AccelerationField>
-            }
-        })
+        Ok(AccelerationFieldSerDe::deserialize(deserializer)?.into_box())
     }
 }
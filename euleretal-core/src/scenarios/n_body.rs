@@ -0,0 +1,73 @@
+//! Scenarios of bodies in free space which only attract each other (with a gravitational constant
+//! of 1).  The particle is the first body.
+
+use super::FreeSpace;
+use crate::{bodies::Body, Position, Scenario, Velocity};
+
+/// Two bodies of equal mass on eccentric orbits around their common center of mass
+#[must_use]
+pub fn two_bodies() -> Scenario {
+    Scenario {
+        acceleration: Box::new(FreeSpace),
+        start_position: Position::new(0., 1., 0.),
+        start_velocity: Velocity::new(0.4, 0., 0.),
+        duration: 8.0.into(),
+        event: None,
+        obstacles: Vec::new(),
+        mass: 1.,
+        bodies: vec![Body::new(
+            1.,
+            Position::new(0., -1., 0.),
+            Velocity::new(-0.4, 0., 0.),
+        )],
+    }
+}
+
+/// Three bodies of equal mass chasing each other along a figure eight, with the initial
+/// conditions of Chenciner and Montgomery.  The duration is one period.
+#[must_use]
+pub fn figure_eight() -> Scenario {
+    let (x, y) = (0.970_004_4, -0.243_087_53);
+    let (vx, vy) = (-0.932_407_4, -0.864_731_46);
+    Scenario {
+        acceleration: Box::new(FreeSpace),
+        start_position: Position::new(x, y, 0.),
+        start_velocity: Velocity::new(-0.5 * vx, -0.5 * vy, 0.),
+        duration: 6.325_914.into(),
+        event: None,
+        obstacles: Vec::new(),
+        mass: 1.,
+        bodies: vec![
+            Body::new(
+                1.,
+                Position::new(-x, -y, 0.),
+                Velocity::new(-0.5 * vx, -0.5 * vy, 0.),
+            ),
+            Body::new(1., Position::origin(), Velocity::new(vx, vy, 0.)),
+        ],
+    }
+}
+
+/// A small cluster of bodies of different masses, with close encounters
+#[must_use]
+pub fn cluster() -> Scenario {
+    Scenario {
+        acceleration: Box::new(FreeSpace),
+        start_position: Position::new(1., 0., 0.),
+        start_velocity: Velocity::new(0., 0.5, 0.),
+        duration: 3.0.into(),
+        event: None,
+        obstacles: Vec::new(),
+        mass: 1.,
+        bodies: vec![
+            Body::new(1., Position::new(-1., 0., 0.), Velocity::new(0., -0.5, 0.)),
+            Body::new(0.5, Position::new(0., 1.2, 0.), Velocity::new(-0.6, 0., 0.)),
+            Body::new(0.5, Position::new(0., -1.2, 0.), Velocity::new(0.6, 0., 0.)),
+            Body::new(
+                0.2,
+                Position::new(1.6, 1., 0.),
+                Velocity::new(-0.2, 0.3, 0.),
+            ),
+        ],
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{import::OrderedF32, Acceleration, AccelerationField, Position};
+use ::std::{any::TypeId, collections::hash_map::DefaultHasher, hash::Hash};
+
+/// The gravity of a point mass at the origin.  A Plummer softening length `softening` smooths
+/// the singularity at the origin, i.e. the distance `r` is replaced by `√(r² + softening²)`.
+#[derive(Clone, Copy, ::serde::Deserialize, ::serde::Serialize)]
+pub struct PointMass {
+    pub mass: f32,
+    pub softening: f32,
+}
+
+impl Default for PointMass {
+    fn default() -> Self {
+        Self {
+            mass: 1.,
+            softening: 0.,
+        }
+    }
+}
+
+impl AccelerationField for PointMass {
+    fn value_at(&self, pos: Position) -> Acceleration {
+        let distance_squared_recip =
+            (pos.as_vector().norm_squared() + self.softening * self.softening).recip();
+        (-self.mass * pos.as_vector() * distance_squared_recip.sqrt() * distance_squared_recip)
+            .into()
+    }
+
+    fn values_at(&self, positions: &[Position], accelerations: &mut [Acceleration]) {
+        assert_eq!(positions.len(), accelerations.len());
+        let (mass, softening_squared) = (self.mass, self.softening * self.softening);
+        for (a, pos) in accelerations.iter_mut().zip(positions) {
+            let s = pos.as_vector();
+            let distance_squared_recip = (s.norm_squared() + softening_squared).recip();
+            *a = (-mass * s * distance_squared_recip.sqrt() * distance_squared_recip).into();
+        }
+    }
+
+    fn potential_at(&self, pos: Position) -> Option<f32> {
+        Some(
+            -self.mass
+                * (pos.as_vector().norm_squared() + self.softening * self.softening)
+                    .sqrt()
+                    .recip(),
+        )
+    }
+
+    fn label(&self) -> String {
+        "Point Mass".to_string()
+    }
+
+    fn hash(&self, state: &mut DefaultHasher) {
+        TypeId::of::<Self>().hash(state);
+        OrderedF32::from(self.mass).hash(state);
+        OrderedF32::from(self.softening).hash(state);
+    }
+
+    fn to_concrete_type(
+        &self,
+    ) -> crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe {
+        crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe::PointMass(*self)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{import::OrderedF32, Acceleration, AccelerationField, Position};
+use ::std::{any::TypeId, collections::hash_map::DefaultHasher, hash::Hash};
+
+/// An anharmonic oscillator with the potential `stiffness·r²/2 + anharmonicity·r⁴/4`.  Its
+/// frequency changes with the amplitude, so integrators which distort the amplitude also drift in
+/// phase.  With a negative anharmonicity, the well is surrounded by a barrier at
+/// `r² = stiffness/|anharmonicity|`, beyond which the potential falls without bound.
+#[derive(Clone, Copy, ::serde::Deserialize, ::serde::Serialize)]
+pub struct QuarticOscillator {
+    pub stiffness: f32,
+    pub anharmonicity: f32,
+}
+
+impl Default for QuarticOscillator {
+    fn default() -> Self {
+        Self {
+            stiffness: 1.,
+            anharmonicity: 1.,
+        }
+    }
+}
+
+impl AccelerationField for QuarticOscillator {
+    fn value_at(&self, pos: Position) -> Acceleration {
+        let s = pos.as_vector();
+        (-(self.stiffness + self.anharmonicity * s.norm_squared()) * s).into()
+    }
+
+    fn values_at(&self, positions: &[Position], accelerations: &mut [Acceleration]) {
+        assert_eq!(positions.len(), accelerations.len());
+        let (stiffness, anharmonicity) = (self.stiffness, self.anharmonicity);
+        for (a, pos) in accelerations.iter_mut().zip(positions) {
+            let s = pos.as_vector();
+            *a = (-(stiffness + anharmonicity * s.norm_squared()) * s).into();
+        }
+    }
+
+    fn potential_at(&self, pos: Position) -> Option<f32> {
+        let r2 = pos.as_vector().norm_squared();
+        Some(0.5 * self.stiffness * r2 + 0.25 * self.anharmonicity * r2 * r2)
+    }
+
+    fn label(&self) -> String {
+        "Quartic Oscillator".to_string()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !self.stiffness.is_finite() || self.stiffness < 0. {
+            return Err(format!(
+                "Stiffness must not be negative (is {}).",
+                self.stiffness
+            ));
+        }
+        if !self.anharmonicity.is_finite() {
+            return Err(format!(
+                "Anharmonicity must be finite (is {}).",
+                self.anharmonicity
+            ));
+        }
+        Ok(())
+    }
+
+    fn hash(&self, state: &mut DefaultHasher) {
+        TypeId::of::<Self>().hash(state);
+        OrderedF32::from(self.stiffness).hash(state);
+        OrderedF32::from(self.anharmonicity).hash(state);
+    }
+
+    fn to_concrete_type(
+        &self,
+    ) -> crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe {
+        crate::scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe::QuarticOscillator(*self)
+    }
+}
@@ -0,0 +1,514 @@
+//! Local truncation error of integrators for `s'' = a(s)`.
+//!
+//! The symbolic trace of an integrator (see [`Trace`]) is evaluated with Taylor series in `dt`,
+//! truncated after `dt^MAX_POWER`.  Their coefficients are polynomials in the start velocity `v`
+//! and the derivatives `a, a', a'', …` of the acceleration field at the start position.  The
+//! difference to the Taylor series of the exact solution is the local error of the step.
+//!
+//! The analysis is one-dimensional.  Beyond order 4, a few error terms which differ for
+//! vector-valued acceleration fields coincide in one dimension.
+
+use crate::{
+    integration_step::symbolic::{Expression, Symbol, Trace},
+    Integrator,
+};
+use ::std::collections::BTreeMap;
+
+/// Highest power of `dt` which is taken into account.
+pub const MAX_POWER: usize = 6;
+
+/// `v`, `a`, `a'`, … `a⁽ᴹᴬˣ⁾`
+const SYMBOLS: usize = MAX_POWER + 2;
+
+/// The relative precision of the coefficients.  The factors of a trace are `f32`, so coefficients
+/// which are smaller than this (relative to the largest coefficient) are rounding errors.
+const PRECISION: f64 = 16. * f32::EPSILON as f64;
+
+/// Exponents of `v`, `a`, `a'`, `a''`, …
+type Monomial = [u8; SYMBOLS];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Polynomial(BTreeMap<Monomial, f64>);
+
+/// Truncated Taylor series in `dt`: element `k` is the coefficient of `dtᵏ`.
+#[derive(Clone, Debug, PartialEq)]
+struct Series(Vec<Polynomial>);
+
+/// The leading term of the local error of a computed quantity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalError {
+    /// The power of `dt` of the leading term.  `None` if the quantity is exact up to `MAX_POWER`.
+    pub power: Option<usize>,
+    coefficient: Polynomial,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    pub position: LocalError,
+    pub velocity: LocalError,
+}
+
+impl Analysis {
+    #[must_use]
+    pub fn of(integrator: &dyn Integrator) -> Self {
+        let trace = Trace::of(integrator);
+        let (s, v) = evaluate(&trace);
+        let (exact_s, exact_v) = exact_solution();
+        Self {
+            position: LocalError::new(&s, &exact_s),
+            velocity: LocalError::new(&v, &exact_v),
+        }
+    }
+
+    /// The order `p` of the integrator, like [`crate::integrator::Metadata::order`]: the leading
+    /// term of the local error is of power `p + 1`, so the global error is `O(dtᵖ)`.  `None` if
+    /// the order is higher than `MAX_POWER - 1`.
+    #[must_use]
+    pub fn order(&self) -> Option<usize> {
+        match (self.position.power, self.velocity.power) {
+            (Some(s_power), Some(v_power)) => Some(s_power.min(v_power) - 1),
+            (Some(power), None) | (None, Some(power)) => Some(power - 1),
+            (None, None) => None,
+        }
+    }
+}
+
+impl ::std::fmt::Display for Analysis {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match self.order() {
+            Some(order) => writeln!(f, "Order: {}", format_order(order))?,
+            None => writeln!(f, "Order: ≥ {}", MAX_POWER)?,
+        }
+        writeln!(f, "Local error:")?;
+        writeln!(f, "Δs = {}", self.position)?;
+        write!(f, "Δv = {}", self.velocity)
+    }
+}
+
+impl LocalError {
+    fn new(computed: &Series, exact: &Series) -> Self {
+        let tolerance = PRECISION * computed.max_coefficient().max(exact.max_coefficient());
+        let mut error = computed.clone();
+        error.add_scaled(exact, -1.);
+        error
+            .0
+            .into_iter()
+            .enumerate()
+            .map(|(power, mut coefficient)| {
+                coefficient.0.retain(|_, value| value.abs() >= tolerance);
+                (power, coefficient)
+            })
+            .find(|(_, coefficient)| !coefficient.is_zero())
+            .map_or(
+                Self {
+                    power: None,
+                    coefficient: Polynomial::default(),
+                },
+                |(power, coefficient)| Self {
+                    power: Some(power),
+                    coefficient,
+                },
+            )
+    }
+}
+
+impl ::std::fmt::Display for LocalError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match self.power {
+            Some(power) => write!(
+                f,
+                "({}) dt{} + O(dt{})",
+                self.coefficient,
+                superscript(power),
+                superscript(power + 1)
+            ),
+            None => write!(f, "O(dt{})", superscript(MAX_POWER + 1)),
+        }
+    }
+}
+
+impl Polynomial {
+    fn symbol(index: usize) -> Self {
+        let mut monomial = Monomial::default();
+        monomial[index] = 1;
+        Self([(monomial, 1.)].into_iter().collect())
+    }
+
+    fn velocity() -> Self {
+        Self::symbol(0)
+    }
+
+    /// The `n`-th derivative of the acceleration field
+    fn acceleration(n: usize) -> Self {
+        Self::symbol(n + 1)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The largest absolute value of the coefficients
+    fn max_coefficient(&self) -> f64 {
+        self.0
+            .values()
+            .fold(0., |max, coefficient| max.max(coefficient.abs()))
+    }
+
+    fn add_scaled(&mut self, other: &Self, factor: f64) {
+        for (monomial, coefficient) in &other.0 {
+            *self.0.entry(*monomial).or_default() += factor * coefficient;
+        }
+        self.0.retain(|_, coefficient| *coefficient != 0.);
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let mut result = Self::default();
+        for (lhs, lhs_coefficient) in &self.0 {
+            for (rhs, rhs_coefficient) in &other.0 {
+                let mut monomial = *lhs;
+                for (exponent, rhs_exponent) in monomial.iter_mut().zip(rhs) {
+                    *exponent += rhs_exponent;
+                }
+                *result.0.entry(monomial).or_default() += lhs_coefficient * rhs_coefficient;
+            }
+        }
+        result
+    }
+
+    /// The time derivative along the exact solution: `d/dt v = a`, `d/dt a⁽ⁿ⁾ = a⁽ⁿ⁺¹⁾ v`.
+    fn derivative(&self) -> Self {
+        let mut result = Self::default();
+        for (monomial, coefficient) in &self.0 {
+            for (index, &exponent) in monomial.iter().enumerate() {
+                if exponent == 0 {
+                    continue;
+                }
+                let mut reduced = *monomial;
+                reduced[index] -= 1;
+                let reduced = Self([(reduced, coefficient * f64::from(exponent))].into());
+                let derivative = if index == 0 {
+                    Self::acceleration(0)
+                } else if index + 1 < SYMBOLS {
+                    Self::symbol(index + 1).mul(&Self::velocity())
+                } else {
+                    continue; // beyond the truncation
+                };
+                result.add_scaled(&reduced.mul(&derivative), 1.);
+            }
+        }
+        result
+    }
+}
+
+impl ::std::fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        for (n, (monomial, coefficient)) in self.0.iter().rev().enumerate() {
+            let sign = match (n, *coefficient < 0.) {
+                (0, false) => "",
+                (0, true) => "-",
+                (_, false) => " + ",
+                (_, true) => " - ",
+            };
+            write!(f, "{}{}", sign, format_coefficient(coefficient.abs()))?;
+            // the derivatives of the acceleration first, the velocity last:
+            for index in (1..SYMBOLS).chain(Some(0)) {
+                let exponent = monomial[index];
+                if exponent > 0 {
+                    let name = if index == 0 {
+                        "v".to_string()
+                    } else {
+                        acceleration_name(index - 1)
+                    };
+                    write!(f, " {}", name)?;
+                    if exponent > 1 {
+                        write!(f, "{}", superscript(usize::from(exponent)))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Series {
+    fn zero() -> Self {
+        Self(vec![Polynomial::default(); MAX_POWER + 1])
+    }
+
+    fn constant(polynomial: Polynomial) -> Self {
+        let mut result = Self::zero();
+        result.0[0] = polynomial;
+        result
+    }
+
+    /// The largest absolute value of the coefficients of all powers
+    fn max_coefficient(&self) -> f64 {
+        self.0
+            .iter()
+            .map(Polynomial::max_coefficient)
+            .fold(0., f64::max)
+    }
+
+    fn add_scaled(&mut self, other: &Self, factor: f64) {
+        for (lhs, rhs) in self.0.iter_mut().zip(&other.0) {
+            lhs.add_scaled(rhs, factor);
+        }
+    }
+
+    /// Multiplies by `dtᵖᵒʷᵉʳ`.
+    fn shift(&self, power: usize) -> Self {
+        let mut result = Self::zero();
+        for (k, coefficient) in self.0.iter().enumerate() {
+            if k + power <= MAX_POWER {
+                result.0[k + power] = coefficient.clone();
+            }
+        }
+        result
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let mut result = Self::zero();
+        for (i, lhs) in self.0.iter().enumerate() {
+            for (j, rhs) in other.0.iter().enumerate().take(MAX_POWER + 1 - i) {
+                result.0[i + j].add_scaled(&lhs.mul(rhs), 1.);
+            }
+        }
+        result
+    }
+}
+
+/// The displacement `s(dt) - s` and the velocity `v(dt)` of the exact solution.
+fn exact_solution() -> (Series, Series) {
+    let (mut s, mut v) = (Series::zero(), Series::zero());
+    // the k-th derivative of s
+    let mut derivative = Polynomial::velocity();
+    let mut factorial = 1.;
+    for k in 1..=MAX_POWER + 1 {
+        // factorial of k - 1:
+        v.0[k - 1].add_scaled(&derivative, 1. / factorial);
+        #[allow(clippy::cast_precision_loss)]
+        {
+            factorial *= k as f64;
+        }
+        if k <= MAX_POWER {
+            s.0[k].add_scaled(&derivative, 1. / factorial);
+        }
+        derivative = derivative.derivative();
+    }
+    (s, v)
+}
+
+/// The acceleration field at the start position plus `displacement`:
+/// `Σ a⁽ⁿ⁾ displacementⁿ / n!`
+fn acceleration_at(displacement: &Series) -> Series {
+    let mut result = Series::zero();
+    let mut power = Series::constant(Polynomial([(Monomial::default(), 1.)].into()));
+    let mut factorial = 1.;
+    for n in 0..=MAX_POWER {
+        if n > 0 {
+            power = power.mul(displacement);
+            #[allow(clippy::cast_precision_loss)]
+            {
+                factorial *= n as f64;
+            }
+        }
+        result.add_scaled(
+            &power.mul(&Series::constant(Polynomial::acceleration(n))),
+            1. / factorial,
+        );
+    }
+    result
+}
+
+/// Returns the displacement and the velocity computed by the step.
+fn evaluate(trace: &Trace) -> (Series, Series) {
+    let mut positions = vec![Series::zero()];
+    let mut velocities = vec![Series::constant(Polynomial::velocity())];
+    let mut accelerations = vec![Series::constant(Polynomial::acceleration(0))];
+    let get = |positions: &[Series], velocities: &[Series], accelerations: &[Series], symbol| {
+        match symbol {
+            Symbol::Position(n) => positions[n].clone(),
+            Symbol::Velocity(n) => velocities[n].clone(),
+            Symbol::Acceleration(n) => accelerations[n].clone(),
+        }
+    };
+    for assignment in &trace.assignments {
+        let value = match &assignment.expression {
+            Expression::AccelerationAt(position) => {
+                acceleration_at(&get(&positions, &velocities, &accelerations, *position))
+            }
+            Expression::Sum(terms) => {
+                let mut sum = Series::zero();
+                for term in terms {
+                    let quantity = get(&positions, &velocities, &accelerations, term.symbol);
+                    let dt_fraction = f64::from(f32::from(term.dt_fraction));
+                    sum.add_scaled(
+                        &quantity.shift(usize::from(term.dt_power)),
+                        f64::from(term.factor) * dt_fraction.powi(i32::from(term.dt_power)),
+                    );
+                }
+                sum
+            }
+        };
+        match assignment.target {
+            Symbol::Position(_) => positions.push(value),
+            Symbol::Velocity(_) => velocities.push(value),
+            Symbol::Acceleration(_) => accelerations.push(value),
+        }
+    }
+    (
+        get(
+            &positions,
+            &velocities,
+            &accelerations,
+            trace.result_position(),
+        ),
+        get(
+            &positions,
+            &velocities,
+            &accelerations,
+            trace.result_velocity(),
+        ),
+    )
+}
+
+fn acceleration_name(derivative: usize) -> String {
+    match derivative {
+        0..=3 => format!("a{}", "'".repeat(derivative)),
+        _ => format!("a⁽{}⁾", superscript(derivative)),
+    }
+}
+
+/// `order` together with the errors it stands for, e.g. `2 (global error O(dt²), local O(dt³))`
+pub(crate) fn format_order(order: usize) -> String {
+    format!(
+        "{} (global error O(dt{}), local O(dt{}))",
+        order,
+        superscript(order),
+        superscript(order + 1)
+    )
+}
+
+fn superscript(number: usize) -> String {
+    const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    match number {
+        1 => String::new(),
+        _ => number
+            .to_string()
+            .chars()
+            .filter_map(|digit| digit.to_digit(10))
+            .map(|digit| SUPERSCRIPTS[digit as usize])
+            .collect(),
+    }
+}
+
+/// Formats as a fraction with a small denominator, if possible.
+fn format_coefficient(coefficient: f64) -> String {
+    (1..=5040)
+        .map(f64::from)
+        .find(|denominator| {
+            let numerator = coefficient * denominator;
+            (numerator - numerator.round()).abs() < PRECISION * denominator
+        })
+        .map_or_else(
+            || format!("{:.4}", coefficient),
+            |denominator| {
+                let numerator = (coefficient * denominator).round();
+                #[allow(clippy::float_cmp)]
+                if denominator == 1. {
+                    format!("{}", numerator)
+                } else {
+                    format!("{}/{}", numerator, denominator)
+                }
+            },
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrators::{
+        custom::{Custom, Stage, Term},
+        euler, exact_for_const, mid_point,
+    };
+
+    #[test]
+    fn exact_solution() {
+        let (s, v) = super::exact_solution();
+        assert_eq!(format!("{}", s.0[1]), "1 v");
+        assert_eq!(format!("{}", s.0[2]), "1/2 a");
+        assert_eq!(format!("{}", s.0[3]), "1/6 a' v");
+        assert_eq!(format!("{}", s.0[4]), "1/24 a'' v² + 1/24 a a'");
+        assert_eq!(format!("{}", v.0[3]), "1/6 a'' v² + 1/6 a a'");
+    }
+
+    #[test]
+    fn order_is_described_like_the_metadata() {
+        let integrator = mid_point::SecondOrder;
+        let order_line = "Order: 2 (global error O(dt²), local O(dt³))";
+        assert_eq!(
+            Analysis::of(&integrator).to_string().lines().next(),
+            Some(order_line)
+        );
+        assert!(integrator
+            .metadata()
+            .to_string()
+            .lines()
+            .any(|line| line == order_line));
+    }
+
+    #[test]
+    fn orders() {
+        let order = |integrator: &dyn Integrator| Analysis::of(integrator).order();
+        assert_eq!(order(&euler::Broken), Some(1));
+        assert_eq!(order(&euler::Euler), Some(1));
+        assert_eq!(order(&exact_for_const::ExactForConst), Some(1));
+        // the position uses the end velocity for the whole step: `s' = s + v dt + a₁ dt²`
+        assert_eq!(order(&mid_point::Euler), Some(1));
+        assert_eq!(order(&mid_point::SecondOrder), Some(2));
+        // factors which are not exact in `f32` only cause rounding errors:
+        let thirds = || {
+            (0..3)
+                .map(|_| Term {
+                    factor: 1. / 3.,
+                    quantity: 0,
+                })
+                .collect::<Vec<_>>()
+        };
+        let euler_in_thirds = Custom {
+            label: "Euler in thirds".to_string(),
+            stages: vec![
+                Stage::Position {
+                    dt_fraction: (1, 1),
+                    base: 0,
+                    velocity_terms: thirds(),
+                    acceleration_terms: Vec::new(),
+                },
+                Stage::Velocity {
+                    dt_fraction: (1, 1),
+                    base: 0,
+                    acceleration_terms: thirds(),
+                },
+            ],
+        };
+        assert_eq!(order(&euler_in_thirds), Some(1));
+        assert_eq!(
+            format!("{}", Analysis::of(&euler_in_thirds).velocity),
+            "(-1/2 a' v) dt² + O(dt³)"
+        );
+    }
+
+    #[test]
+    fn leading_error_terms() {
+        let broken = Analysis::of(&euler::Broken);
+        assert_eq!(broken.position.power, Some(2));
+        assert_eq!(format!("{}", broken.position), "(-1/2 a) dt² + O(dt³)");
+
+        let mid_point = Analysis::of(&mid_point::Euler);
+        assert_eq!(mid_point.velocity.power, Some(3));
+        assert_eq!(
+            format!("{}", mid_point.velocity),
+            "(-1/24 a'' v² + 1/12 a a') dt³ + O(dt⁴)"
+        );
+    }
+}
@@ -1,13 +1,13 @@
 use super::{
     containers,
-    core::{integrators, scenarios, Position, Scenario, Velocity},
+    core::{integrators, orbit::Orbit, scenarios, Position, Scenario, Velocity},
     entities::{Canvas, Integration, Integrator, StepSize},
     misc::UserLabel,
     ui_import::{
         egui::{self, CentralPanel, SidePanel},
         epi, Color32, Hsva, Rgba, Stroke, Vec2,
     },
-    World,
+    History, World,
 };
 #[cfg(not(target_arch = "wasm32"))]
 use ::std::time::Instant;
@@ -15,6 +15,7 @@ use ::std::time::Instant;
 #[derive(Default)]
 pub struct Euleretal {
     world: World,
+    history: History,
 }
 
 impl epi::App for Euleretal {
@@ -48,14 +49,26 @@ impl epi::App for Euleretal {
     fn update(&mut self, ctx: &egui::CtxRef, frame: &mut epi::Frame<'_>) {
         Self::log_frame_rate(|| {
             SidePanel::left("side_panel").show(ctx, |ui| {
+                containers::history::show(ui, &mut self.history, &mut self.world);
                 containers::controls::show(ui, &mut self.world);
-                containers::settings::show(ui, &mut self.world.settings);
+                self.world
+                    .edit_settings(|settings| containers::settings::show(ui, settings));
             });
             CentralPanel::default().show(ctx, |ui| {
                 containers::canvas::grid::show(ui, &mut self.world);
             });
         });
-        Self::global_control(ctx, frame); // quits the app on user's request
+        let integrating_further = self.world.integrate_further();
+        if integrating_further {
+            ctx.request_repaint();
+        }
+        self.world.evict_references();
+        self.global_control(ctx, frame); // quits the app on user's request
+        if !ctx.input().pointer.any_down() && !integrating_further {
+            // record edits only when completed, e.g. after releasing a slider or after
+            // integrating further
+            self.history.record(&mut self.world);
+        }
     }
 
     fn max_size_points(&self) -> Vec2 {
@@ -125,25 +138,36 @@ impl Euleretal {
             stroke: Stroke::new(1., Hsva::from(Color32::RED)),
         });
 
-        let scenario_center_mass = self.world.add_scenario(Scenario {
-            acceleration: Box::new(scenarios::CenterMass),
-            start_position: Position::new(0., 1., 0.),
-            start_velocity: Velocity::new(1., 0., 0.),
-            duration: std::f32::consts::TAU.into(),
-        });
+        let scenario_circular_orbit = self.world.add_scenario(Orbit::default().scenario());
+        let _scenario_eccentric_orbit = self.world.add_scenario(
+            Orbit {
+                eccentricity: 0.9,
+                periods: 3.,
+                ..Orbit::default()
+            }
+            .scenario(),
+        );
 
         let _scenario_constant_acceleration = self.world.add_scenario(Scenario {
             acceleration: Box::new(scenarios::ConstantAcceleration),
             start_position: Position::origin(),
             start_velocity: Velocity::new(1., 0., 0.),
             duration: 2_f32.into(),
+            event: None,
+            obstacles: Vec::new(),
+            mass: 1.,
+            bodies: Vec::new(),
         });
 
-        let canvas_center_mass = self.world.add_canvas(Canvas::new(scenario_center_mass));
+        let _scenario_two_bodies = self.world.add_scenario(scenarios::n_body::two_bodies());
+        let _scenario_figure_eight = self.world.add_scenario(scenarios::n_body::figure_eight());
+        let _scenario_cluster = self.world.add_scenario(scenarios::n_body::cluster());
 
-        canvas_center_mass
-            .borrow_mut()
-            .add_integration(Integration::new(mid_point_euler, step_size));
+        let mut canvas_circular_orbit = Canvas::new(scenario_circular_orbit);
+        canvas_circular_orbit.add_integration(Integration::new(mid_point_euler, step_size));
+        self.world.add_canvas(canvas_circular_orbit);
+        // the initial state is no edit:
+        self.world.take_journal();
     }
 
     fn init_display_style(ctx: &egui::CtxRef) {
@@ -198,9 +222,19 @@ impl Euleretal {
     }
 
     /// interprets hotkeys or other commands not covered locally by UI controls
-    fn global_control(ctx: &egui::CtxRef, frame: &mut epi::Frame) {
-        if ctx.input().key_pressed(egui::Key::Q) {
+    fn global_control(&mut self, ctx: &egui::CtxRef, frame: &mut epi::Frame) {
+        let input = ctx.input();
+        if input.key_pressed(egui::Key::Q) {
             frame.quit();
         }
+        // text edits have their own undo:
+        if input.modifiers.command && input.key_pressed(egui::Key::Z) && !ctx.wants_keyboard_input()
+        {
+            if input.modifiers.shift {
+                self.history.redo(&mut self.world);
+            } else {
+                self.history.undo(&mut self.world);
+            }
+        }
     }
 }
@@ -0,0 +1,119 @@
+use super::{
+    core::Scenario,
+    entities::{Canvas, Integration, Integrator, StepSize, StepSizeGroup},
+    misc::{entity_store::Index, Settings},
+};
+
+/// A reversible edit of the `World`.  All edits of the model are applied as commands, and the
+/// world journals the commands which revert them (see `World::take_journal()`), so that they can
+/// be undone by the [`crate::History`].
+pub enum Command {
+    /// Replaces the scenario at the index.  It is inserted if there is none, and removed if
+    /// `None`.
+    Scenario(Index<Scenario>, Option<Scenario>),
+    /// like `Scenario`
+    Integrator(Index<Integrator>, Option<Integrator>),
+    /// like `Scenario`
+    StepSize(Index<StepSize>, Option<StepSize>),
+    /// like `Scenario`
+    StepSizeGroup(Index<StepSizeGroup>, Option<StepSizeGroup>),
+    Settings(Box<Settings>),
+    /// Inserts the canvas at the position, or removes the canvas at the position if `None`.
+    /// Removed canvases keep their computations, so that they are shown right away when the
+    /// removal is reverted.
+    Canvas(usize, Option<Box<Canvas>>),
+    EditCanvas(usize, CanvasCommand),
+}
+
+/// A reversible edit of a `Canvas`
+pub enum CanvasCommand {
+    SetScenario(Index<Scenario>),
+    /// Inserts the integration at the position, or removes the integration at the position if
+    /// `None`.
+    Integration(usize, Option<Box<Integration>>),
+    SetIntegrator(usize, Index<Integrator>),
+    SetStepSize(usize, Index<StepSize>),
+}
+
+/// The commands which revert the edits of the `World`, in the order of the edits
+#[derive(Default)]
+pub struct Journal {
+    commands: Vec<Command>,
+}
+
+impl ::std::fmt::Debug for Journal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Journal")
+            .field("len", &self.commands.len())
+            .finish()
+    }
+}
+
+impl Command {
+    /// `true` if `later` replaces the same entity as this, so that applying `later` before this
+    /// makes no difference.
+    fn supersedes(&self, later: &Self) -> bool {
+        match (self, later) {
+            (Self::Scenario(idx, _), Self::Scenario(later_idx, Some(_))) => idx == later_idx,
+            (Self::Integrator(idx, _), Self::Integrator(later_idx, Some(_))) => idx == later_idx,
+            (Self::StepSize(idx, _), Self::StepSize(later_idx, Some(_))) => idx == later_idx,
+            (Self::StepSizeGroup(idx, _), Self::StepSizeGroup(later_idx, Some(_))) => {
+                idx == later_idx
+            }
+            (Self::Settings(_), Self::Settings(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl CanvasCommand {
+    /// Applies this command to `canvas`.  Returns the command which reverts it.
+    pub fn apply(self, canvas: &mut Canvas) -> Self {
+        match self {
+            Self::SetScenario(scenario_idx) => {
+                let previous = canvas.scenario_idx();
+                canvas.set_scenario(scenario_idx);
+                Self::SetScenario(previous)
+            }
+            Self::Integration(integration_idx, Some(integration)) => {
+                canvas.insert_integration(integration_idx, *integration);
+                Self::Integration(integration_idx, None)
+            }
+            Self::Integration(integration_idx, None) => Self::Integration(
+                integration_idx,
+                Some(Box::new(canvas.remove_integration(integration_idx))),
+            ),
+            Self::SetIntegrator(integration_idx, integrator_idx) => {
+                let mut integration = canvas.integration_at(integration_idx).borrow_mut();
+                let previous = integration.integrator_idx();
+                integration.set_integrator(integrator_idx);
+                Self::SetIntegrator(integration_idx, previous)
+            }
+            Self::SetStepSize(integration_idx, step_size_idx) => {
+                let mut integration = canvas.integration_at(integration_idx).borrow_mut();
+                let previous = integration.step_size_idx();
+                integration.set_step_size(step_size_idx);
+                Self::SetStepSize(integration_idx, previous)
+            }
+        }
+    }
+}
+
+impl Journal {
+    /// Appends `command`, unless an earlier command replaces the same entity, e.g. while a value
+    /// is being dragged.  As the commands are applied in reverse order, the earlier one determines
+    /// the result anyway.
+    pub fn push(&mut self, command: Command) {
+        if !self
+            .commands
+            .iter()
+            .any(|earlier| earlier.supersedes(&command))
+        {
+            self.commands.push(command);
+        }
+    }
+
+    pub fn take(&mut self) -> Vec<Command> {
+        ::std::mem::take(&mut self.commands)
+    }
+}
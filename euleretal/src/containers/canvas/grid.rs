@@ -1,4 +1,5 @@
 use super::{
+    core::Scenario,
     entities::Canvas,
     ui_import::{Ui, Vec2},
     view::{self, CanvasOperation},
@@ -13,22 +14,31 @@ pub fn show(ui: &mut Ui, world: &mut World) {
     let can_close = canvas_count > 1;
     let can_create = canvas_count < 4;
     let mut operation = CanvasOperation::Noop;
+    let mut start_condition = None;
 
-    world.canvases().for_each(|canvas| {
-        let header_bar = view::show_header_bar(ui, canvas, world, can_close, can_create);
+    world.canvases().enumerate().for_each(|(position, canvas)| {
+        let header_bar = view::show_header_bar(ui, position, canvas, world, can_close, can_create);
         if let CanvasOperation::Noop = header_bar.inner {
         } else {
             operation = header_bar.inner;
         }
         let inner_size = Vec2::new(view_size.x, view_size.y - header_bar.response.rect.height());
-        view::show_canvas(ui, canvas, inner_size, world);
+        if let Some(dragged) = view::show_canvas(ui, canvas, inner_size, world) {
+            start_condition = Some((canvas.borrow().scenario_idx(), dragged));
+        }
     });
 
+    if let Some((scenario_idx, (start_position, start_velocity))) = start_condition {
+        world.edit(scenario_idx, |scenario: &mut Scenario| {
+            scenario.start_position = start_position;
+            scenario.start_velocity = start_velocity;
+        });
+    }
     match operation {
         CanvasOperation::Create { source_canvas } => {
             let mut new_canvas;
             {
-                let source_canvas = source_canvas.borrow();
+                let source_canvas = world.canvases().nth(source_canvas).unwrap().borrow();
                 new_canvas = Canvas::new(source_canvas.scenario_idx());
                 // copy canvas integrations:
                 source_canvas.integrations().for_each(|integration| {
@@ -38,10 +48,8 @@ pub fn show(ui: &mut Ui, world: &mut World) {
             }
             world.add_canvas(new_canvas);
         }
-
-        CanvasOperation::Close { canvas } => {
-            world.remove_canvas(canvas);
-        }
+        CanvasOperation::Close { canvas } => world.remove_canvas(canvas),
+        CanvasOperation::Edit { canvas, command } => world.edit_canvas(canvas, command),
         CanvasOperation::Noop => (),
     }
 }
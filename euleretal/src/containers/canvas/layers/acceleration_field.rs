@@ -1,4 +1,9 @@
-use super::{core::Position, entities::CanvasPainter, import::Vec3, World};
+use super::{
+    core::{Acceleration, Position},
+    entities::CanvasPainter,
+    import::Vec3,
+    World,
+};
 
 pub fn render(canvas: &CanvasPainter, world: &World) {
     #![allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
@@ -10,12 +15,16 @@ pub fn render(canvas: &CanvasPainter, world: &World) {
 
     let min = canvas.rect_min();
     let max = canvas.rect_max();
-    for x in ((min.x - 1.) as i32)..=((max.x + 1.) as i32) {
-        for y in ((min.y - 1.) as i32)..=((max.y + 1.) as i32) {
-            let pos = Position::new(x as f32, y as f32, 0.);
-            let a = acceleration.value_at(pos);
-            canvas.draw_vector(pos, a, world.settings.strokes.acceleration);
-        }
+    let positions = (((min.x - 1.) as i32)..=((max.x + 1.) as i32))
+        .flat_map(|x| {
+            (((min.y - 1.) as i32)..=((max.y + 1.) as i32))
+                .map(move |y| Position::new(x as f32, y as f32, 0.))
+        })
+        .collect::<Vec<_>>();
+    let mut accelerations = vec![Acceleration::new(0., 0., 0.); positions.len()];
+    acceleration.values_at(&positions, &mut accelerations);
+    for (&pos, &a) in positions.iter().zip(&accelerations) {
+        canvas.draw_vector(pos, a, world.settings().strokes.acceleration);
     }
 
     canvas.on_hover_ui(|ui, mouse_pos| {
@@ -24,8 +33,8 @@ pub fn render(canvas: &CanvasPainter, world: &World) {
         ui.separator();
         ui.label(format!(
             "|a| = {}",
-            world.settings.format_f32(Vec3::from(a).norm())
+            world.settings().format_f32(Vec3::from(a).norm())
         ));
-        canvas.draw_vector(mouse_pos, a, world.settings.strokes.acceleration);
+        canvas.draw_vector(mouse_pos, a, world.settings().strokes.acceleration);
     });
 }
@@ -0,0 +1,19 @@
+use super::{
+    entities::CanvasPainter,
+    misc::{PointFormat, PointShape},
+    World,
+};
+
+/// Marks the last position of each integration which stopped because it diverged.
+pub fn render(canvas: &CanvasPainter, world: &World) {
+    let format = PointFormat {
+        shape: PointShape::CrossHair,
+        size: 12.,
+        stroke: world.settings().strokes.divergence,
+    };
+    canvas.for_each_integration(|integration| {
+        if let Some(diverged) = integration.divergence() {
+            canvas.draw_sample_point(diverged.s, &format);
+        }
+    });
+}
@@ -0,0 +1,22 @@
+use super::{entities::CanvasPainter, World};
+
+/// Marks where each integration detected the event of the scenario, connected to where it occurs
+/// on the reference solution.
+pub fn render(canvas: &CanvasPainter, world: &World) {
+    let point_formats = &world.settings().point_formats;
+    canvas.for_each_integration(|integration| {
+        let reference = integration.reference_event();
+        if let Some(reference) = reference {
+            canvas.draw_sample_point(reference.s, &point_formats.reference_position);
+        }
+        if let Some(detected) = integration.detected_event() {
+            let stroke = world[integration.integrator_idx()].borrow().stroke;
+            if let Some(reference) = reference {
+                canvas.draw_line_segment(detected.s, reference.s, stroke);
+            }
+            let mut format = point_formats.other_position.clone();
+            format.stroke.color = stroke.color;
+            canvas.draw_sample_point(detected.s, &format);
+        }
+    });
+}
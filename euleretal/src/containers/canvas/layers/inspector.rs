@@ -1,5 +1,8 @@
 use super::{
-    core::{integration_step::computed, Contribution, Duration, PhysicalQuantityKind, Step},
+    core::{
+        integration_step::computed, obstacle::Collision, Contribution, Duration,
+        PhysicalQuantityKind, Position, Step, Velocity,
+    },
     entities::CanvasPainter,
     misc::{Settings, StrokeExt},
     World,
@@ -9,19 +12,36 @@ pub fn render(canvas: &CanvasPainter, world: &World) {
     let mut pointer_position = None;
     canvas.on_hover(|pointer_pos| {
         pointer_position = Some(pointer_pos);
-        if canvas.input().pointer.primary_down() {
+        if canvas.input().pointer.primary_down() && !canvas.is_dragging_handle() {
             canvas.for_each_integration_mut(|mut integration| {
                 integration.focus_closest_sample(&pointer_pos.into());
             });
         }
     });
     let show_velocity = canvas.input().modifiers.alt;
+    let scenario = world[canvas.scenario_idx()].borrow();
+    canvas.for_each_integration_mut(|mut integration| {
+        integration.record_focussed_steps(&scenario, world);
+    });
 
     canvas.for_each_integration(|integration| {
-        if let Some((ref_sample, calc_sample)) = integration.focussed_sample() {
+        for (body, ref_sample, calc_sample) in integration.focussed_samples() {
+            // Only the picked body follows the pointer, the others explain their last position:
+            let pointer_position = pointer_position.filter(|_| body == integration.focussed_body());
             // Draw all sample points. Highlighted points will be re-painted below.
             for position in calc_sample.positions_iter() {
-                canvas.draw_sample_point(position, &world.settings.point_formats.other_position);
+                canvas.draw_sample_point(position, &world.settings().point_formats.other_position);
+            }
+            if let (0, Some((collision, reference_collisions))) =
+                (body, integration.focussed_collisions())
+            {
+                show_bounce(
+                    canvas,
+                    &calc_sample,
+                    collision.s,
+                    &reference_collisions,
+                    world,
+                );
             }
 
             if show_velocity {
@@ -30,24 +50,30 @@ pub fn render(canvas: &CanvasPainter, world: &World) {
                     |pos| calc_sample.closest_computed_velocity(pos),
                 );
                 if velocity_to_explain == calc_sample.last_computed_velocity() {
-                    highlight_reference_velocity(canvas, ref_sample, &world.settings);
-                } else {
-                    let scenario = world.scenarios()[canvas.scenario_idx()].borrow();
                     highlight_reference_velocity(
                         canvas,
-                        &scenario.calc_intermediate_sample(
-                            &ref_sample.get_start_condition(),
-                            velocity_to_explain.sampling_position().dt_fraction()
-                                * calc_sample.dt(),
-                        ),
-                        &world.settings,
+                        ref_sample.last_s(),
+                        ref_sample.last_v(),
+                        ref_sample.dt(),
+                        world.settings(),
                     );
+                } else {
+                    let dt_fraction = velocity_to_explain.sampling_position().dt_fraction();
+                    if let Some(reference) = integration.focussed_reference_at(body, dt_fraction) {
+                        highlight_reference_velocity(
+                            canvas,
+                            reference.s,
+                            reference.v,
+                            dt_fraction * calc_sample.dt(),
+                            world.settings(),
+                        );
+                    }
                 }
                 explain_derived_velocity(
                     &velocity_to_explain,
                     calc_sample.dt(),
                     canvas,
-                    &world.settings,
+                    world.settings(),
                 );
             } else {
                 let position_to_explain = pointer_position.map_or_else(
@@ -56,26 +82,40 @@ pub fn render(canvas: &CanvasPainter, world: &World) {
                 );
                 // highlight the ref. position that corresponds to `position_to_explain`
                 if position_to_explain == calc_sample.last_computed_position() {
-                    highlight_reference_position(canvas, ref_sample, &world.settings);
-                } else {
-                    let scenario = world.scenarios()[canvas.scenario_idx()].borrow();
-                    highlight_reference_position(
-                        canvas,
-                        // calculate reference sample corresponding to position_to_explain:
-                        &scenario.calc_intermediate_sample(
-                            &ref_sample.get_start_condition(),
-                            position_to_explain.dt_fraction() * calc_sample.dt(),
-                        ),
-                        &world.settings,
-                    );
+                    highlight_reference_position(canvas, ref_sample.last_s(), world.settings());
+                } else if let Some(reference) =
+                    integration.focussed_reference_at(body, position_to_explain.dt_fraction())
+                {
+                    highlight_reference_position(canvas, reference.s, world.settings());
                 };
                 // draw contributing vectors
-                explain_derived_position(&position_to_explain, canvas, &world.settings);
+                explain_derived_position(&position_to_explain, canvas, world.settings());
             }
         }
     });
 }
 
+/// Shows where the focussed step hit an obstacle and where the reference solution did, and how
+/// the position computed by the integrator has been reflected at the surface.
+fn show_bounce(
+    canvas: &CanvasPainter,
+    calc_sample: &Step,
+    contact: Position,
+    reference_collisions: &[Collision],
+    world: &World,
+) {
+    let settings = world.settings();
+    // the position computed by the integrator precedes the reflected one:
+    if let [.., computed, _] = calc_sample.positions_iter().collect::<Vec<_>>()[..] {
+        canvas.draw_line_segment(contact, computed, settings.strokes.obstacle);
+    }
+    canvas.draw_line_segment(contact, calc_sample.last_s(), settings.strokes.obstacle);
+    for reference in reference_collisions {
+        canvas.draw_sample_point(reference.s, &settings.point_formats.reference_position);
+    }
+    canvas.draw_sample_point(contact, &settings.point_formats.start_position);
+}
+
 fn explain_derived_position(
     position: &computed::position::Abstraction,
     canvas: &CanvasPainter,
@@ -160,21 +200,16 @@ fn explain_derived_velocity(
     );
 }
 
-fn highlight_reference_position(
+fn highlight_reference_position(canvas: &CanvasPainter, s: Position, settings: &Settings) {
+    canvas.draw_sample_point(s, &settings.point_formats.reference_position);
+}
+
+fn highlight_reference_velocity(
     canvas: &CanvasPainter,
-    reference_sample: &Step,
+    s: Position,
+    v: Velocity,
+    dt: Duration,
     settings: &Settings,
 ) {
-    canvas.draw_sample_point(
-        reference_sample.last_computed_position().s(),
-        &settings.point_formats.reference_position,
-    );
-}
-
-fn highlight_reference_velocity(canvas: &CanvasPainter, ref_sample: &Step, settings: &Settings) {
-    canvas.draw_vector(
-        ref_sample.last_s(),
-        ref_sample.last_v() * ref_sample.dt(),
-        settings.strokes.reference_velocity,
-    );
+    canvas.draw_vector(s, v * dt, settings.strokes.reference_velocity);
 }
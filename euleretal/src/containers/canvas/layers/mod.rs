@@ -1,6 +1,10 @@
 pub mod acceleration_field;
 pub mod coordinates;
+pub mod divergences;
+pub mod events;
 pub mod inspector;
 pub mod integrations;
+pub mod obstacles;
+pub mod start_condition;
 
-use super::{core, entities, import, misc, World};
+use super::{core, entities, import, misc, ui_import, World};
@@ -0,0 +1,51 @@
+use super::{
+    core::obstacle::{Obstacle, Shape},
+    entities::CanvasPainter,
+    import::{Point3, Vec3},
+    ui_import::Stroke,
+    World,
+};
+
+/// Draws the obstacles of the canvas' scenario, and marks where the reference solution and each
+/// integration bounced off them.
+pub fn render(canvas: &CanvasPainter, world: &World) {
+    let settings = world.settings();
+    for obstacle in &world[canvas.scenario_idx()].borrow().obstacles {
+        draw_obstacle(canvas, obstacle, settings.strokes.obstacle);
+    }
+    canvas.for_each_integration(|integration| {
+        for collision in integration.reference_collisions() {
+            canvas.draw_sample_point(collision.s, &settings.point_formats.reference_position);
+        }
+        let mut format = settings.point_formats.other_position.clone();
+        format.stroke.color = world[integration.integrator_idx()].borrow().stroke.color;
+        for collision in integration.collisions() {
+            canvas.draw_sample_point(collision.s, &format);
+        }
+    });
+}
+
+/// Draws the intersection of `obstacle` with the x/y plane.
+fn draw_obstacle(canvas: &CanvasPainter, obstacle: &Obstacle, stroke: Stroke) {
+    let position = *obstacle.position.as_point();
+    match obstacle.shape {
+        Shape::Wall { normal } => {
+            // long enough to cross the visible area from anywhere:
+            let (min, max) = (canvas.rect_min(), canvas.rect_max());
+            let length = (max - min).norm()
+                + (position - Point3::from((min.coords + max.coords) * 0.5)).norm();
+            let along = Vec3::new(-normal.y, normal.x, 0.).normalize() * length;
+            canvas.draw_line_segment(position - along, position + along, stroke);
+        }
+        Shape::Sphere { radius } => {
+            canvas.draw_circle(position, radius, stroke);
+        }
+        Shape::Cuboid { half_extents } => {
+            let corners = [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)]
+                .map(|(x, y)| position + Vec3::new(x * half_extents.x, y * half_extents.y, 0.));
+            for (idx, &corner) in corners.iter().enumerate() {
+                canvas.draw_line_segment(corner, corners[(idx + 1) % corners.len()], stroke);
+            }
+        }
+    }
+}
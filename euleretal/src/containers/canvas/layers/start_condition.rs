@@ -0,0 +1,56 @@
+use super::{
+    core::{Duration, Position, Velocity},
+    entities::CanvasPainter,
+    import::Vec3,
+    World,
+};
+
+/// The start velocity is shown as the move it would cause during this duration.
+const VELOCITY_HANDLE_DURATION: f32 = 1.;
+
+/// Shows the start position and velocity of the canvas' scenario as handles, which can be dragged
+/// to edit them.  The start conditions of further bodies are only shown.
+///
+/// Returns the edited start position and velocity while a handle is dragged.
+pub fn render(canvas: &CanvasPainter, world: &World) -> Option<(Position, Velocity)> {
+    let scenario = &world.scenarios()[canvas.scenario_idx()];
+    let (start_position, start_velocity, bodies) = {
+        let scenario = scenario.borrow();
+        (
+            scenario.start_position,
+            scenario.start_velocity,
+            scenario.bodies.clone(),
+        )
+    };
+    let velocity_tip = start_position + start_velocity * Duration::from(VELOCITY_HANDLE_DURATION);
+
+    let edited = if let Some(pointer_pos) = canvas.drag_handle("start position", start_position) {
+        // keep the z coordinate, as the canvas shows the x/y plane only
+        let position = Position::new(pointer_pos.x, pointer_pos.y, start_position.as_point().z);
+        Some((position, start_velocity))
+    } else if let Some(pointer_pos) = canvas.drag_handle("start velocity", velocity_tip) {
+        let tip = Position::new(pointer_pos.x, pointer_pos.y, velocity_tip.as_point().z);
+        let velocity = Velocity::from(start_position.vector_to(tip) / VELOCITY_HANDLE_DURATION);
+        Some((start_position, velocity))
+    } else {
+        None
+    };
+
+    let settings = world.settings();
+    for body in bodies {
+        canvas.draw_vector(
+            body.position,
+            Vec3::from(body.velocity) * VELOCITY_HANDLE_DURATION,
+            settings.strokes.start_velocity,
+        );
+        canvas.draw_sample_point(body.position, &settings.point_formats.start_position);
+    }
+    canvas.draw_vector(
+        start_position,
+        Vec3::from(start_velocity) * VELOCITY_HANDLE_DURATION,
+        settings.strokes.start_velocity,
+    );
+    canvas.draw_sample_point(velocity_tip, &settings.point_formats.other_position);
+    canvas.draw_sample_point(start_position, &settings.point_formats.start_position);
+    edited
+}
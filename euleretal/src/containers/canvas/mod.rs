@@ -2,4 +2,4 @@ pub mod grid;
 mod layers;
 mod view;
 
-use super::{constants, core, entities, import, misc, ui_import, World};
+use super::{command, constants, core, entities, import, misc, ui_import, World};
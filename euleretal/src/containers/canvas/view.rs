@@ -1,8 +1,10 @@
 use super::{
+    command::CanvasCommand,
     constants,
+    core::{Position, Velocity},
     entities::{Canvas, Integration, Integrator, ObjExtras, StepSize},
     layers,
-    misc::{entity_store, my_stroke_preview},
+    misc::{divergence_badge, entity_store, my_stroke_preview},
     ui_import::{
         egui::{self, Layout},
         Pos2, Ui, Vec2,
@@ -27,49 +29,81 @@ enum IntegrationOperation {
     },
 }
 
-pub enum CanvasOperation<'a> {
+/// Canvases are identified by their position in the `World`.
+pub enum CanvasOperation {
     Noop,
-    Create { source_canvas: &'a RefCell<Canvas> },
-    Close { canvas: *const RefCell<Canvas> },
+    Create {
+        source_canvas: usize,
+    },
+    Close {
+        canvas: usize,
+    },
+    Edit {
+        canvas: usize,
+        command: CanvasCommand,
+    },
 }
 
-pub fn show_canvas(ui: &mut Ui, canvas: &RefCell<Canvas>, size: Vec2, world: &World) {
+/// Returns the start position and velocity of the scenario if they have been dragged.
+pub fn show_canvas(
+    ui: &mut Ui,
+    canvas: &RefCell<Canvas>,
+    size: Vec2,
+    world: &World,
+) -> Option<(Position, Velocity)> {
     canvas.borrow_mut().update_model(world);
     let mut canvas_painter = canvas.allocate_painter(ui, size);
     canvas_painter.pan_and_zoom();
-    if world.settings.layerflags.coordinates {
-        layers::coordinates::render(&canvas_painter, &world.settings.strokes);
+    if world.settings().layerflags.coordinates {
+        layers::coordinates::render(&canvas_painter, &world.settings().strokes);
     }
-    if world.settings.layerflags.acceleration_field {
+    if world.settings().layerflags.acceleration_field {
         layers::acceleration_field::render(&canvas_painter, world);
     }
+    layers::obstacles::render(&canvas_painter, world);
     layers::integrations::render(&mut canvas_painter, world);
-    if world.settings.layerflags.inspector {
+    layers::events::render(&canvas_painter, world);
+    layers::divergences::render(&canvas_painter, world);
+    let start_condition = layers::start_condition::render(&canvas_painter, world);
+    if world.settings().layerflags.inspector {
         layers::inspector::render(&canvas_painter, world);
     }
+    canvas_painter.draw_progress();
+    start_condition
 }
 
 /// returns the `CanvasOperation` as `inner`
-pub fn show_header_bar<'a>(
+pub fn show_header_bar(
     ui: &mut Ui,
-    canvas: &'a RefCell<Canvas>,
+    position: usize,
+    canvas: &RefCell<Canvas>,
     world: &World,
     can_close: bool,
     can_create: bool,
-) -> egui::InnerResponse<CanvasOperation<'a>> {
+) -> egui::InnerResponse<CanvasOperation> {
     ui.horizontal(|ui| {
-        ui.with_layout(Layout::left_to_right(), |ui| {
-            show_scenario_selector(ui, canvas, world);
-            show_integration_selector(ui, canvas, world);
-        });
+        let command = ui
+            .with_layout(Layout::left_to_right(), |ui| {
+                let scenario_command = show_scenario_selector(ui, canvas, world);
+                let integration_command = show_integration_selector(ui, canvas, world);
+                show_divergences(ui, &canvas.borrow(), world);
+                scenario_command.or(integration_command)
+            })
+            .inner;
         ui.with_layout(Layout::right_to_left(), |ui| {
-            let mut operation = CanvasOperation::Noop;
+            let mut operation = match command {
+                Some(command) => CanvasOperation::Edit {
+                    canvas: position,
+                    command,
+                },
+                None => CanvasOperation::Noop,
+            };
             if can_close && ui.small_button(constants::BUTTON_GLYPH_DELETE).clicked() {
-                operation = CanvasOperation::Close { canvas };
+                operation = CanvasOperation::Close { canvas: position };
             }
             if can_create && ui.small_button(constants::BUTTON_GLYPH_ADD).clicked() {
                 operation = CanvasOperation::Create {
-                    source_canvas: canvas,
+                    source_canvas: position,
                 };
             }
             operation
@@ -78,7 +112,11 @@ pub fn show_header_bar<'a>(
     })
 }
 
-fn show_scenario_selector(ui: &mut Ui, canvas: &RefCell<Canvas>, world: &World) {
+fn show_scenario_selector(
+    ui: &mut Ui,
+    canvas: &RefCell<Canvas>,
+    world: &World,
+) -> Option<CanvasCommand> {
     let selector_id = ui.make_persistent_id(format!("scenario_selector_{:?}", canvas.as_ptr()));
     let canvas_scenario_idx = canvas.borrow().scenario_idx();
     let canvas_scenario = &world.scenarios()[canvas_scenario_idx];
@@ -97,12 +135,15 @@ fn show_scenario_selector(ui: &mut Ui, canvas: &RefCell<Canvas>, world: &World)
                     );
                 });
         });
-    if selected_scenario_idx != canvas_scenario_idx {
-        canvas.borrow_mut().set_scenario(selected_scenario_idx);
-    }
+    (selected_scenario_idx != canvas_scenario_idx)
+        .then(|| CanvasCommand::SetScenario(selected_scenario_idx))
 }
 
-fn show_integration_selector(ui: &mut Ui, canvas: &RefCell<Canvas>, world: &World) {
+fn show_integration_selector(
+    ui: &mut Ui,
+    canvas: &RefCell<Canvas>,
+    world: &World,
+) -> Option<CanvasCommand> {
     let mut window_is_open = canvas.borrow().ui_integrations_window_is_open;
     let button_response = ui.add(egui::Button::new("Integrations"));
     if button_response.clicked() {
@@ -119,39 +160,42 @@ fn show_integration_selector(ui: &mut Ui, canvas: &RefCell<Canvas>, world: &Worl
     canvas.borrow_mut().ui_integrations_window_is_open = window_is_open;
 
     match operation {
-        IntegrationOperation::Create => {
-            canvas.borrow_mut().add_integration(Integration::new(
+        IntegrationOperation::Create => Some(CanvasCommand::Integration(
+            canvas.borrow().integrations().len(),
+            Some(Box::new(Integration::new(
                 world.integrators().enumerate().next().unwrap().0,
                 world.step_sizes().enumerate().next().unwrap().0,
-            ));
-        }
+            ))),
+        )),
         IntegrationOperation::Delete { integration_idx } => {
-            canvas.borrow_mut().remove_integration(integration_idx);
+            Some(CanvasCommand::Integration(integration_idx, None))
         }
         IntegrationOperation::SetIntegrator {
             integration_idx,
             integrator_idx,
-        } => {
-            canvas
-                .borrow()
-                .integration_at(integration_idx)
-                .borrow_mut()
-                .set_integrator(integrator_idx);
-        }
+        } => Some(CanvasCommand::SetIntegrator(
+            integration_idx,
+            integrator_idx,
+        )),
         IntegrationOperation::SetStepSize {
             integration_idx,
             step_size_idx,
-        } => {
-            canvas
-                .borrow()
-                .integration_at(integration_idx)
-                .borrow_mut()
-                .set_step_size(step_size_idx);
-        }
-        IntegrationOperation::Noop => (),
+        } => Some(CanvasCommand::SetStepSize(integration_idx, step_size_idx)),
+        IntegrationOperation::Noop => None,
     }
 }
 
+/// A badge for the integrations of the canvas which diverged
+fn show_divergences(ui: &mut Ui, canvas: &Canvas, world: &World) {
+    divergence_badge(
+        ui,
+        canvas
+            .integrations()
+            .filter_map(|integration| integration.borrow().labelled_divergence(world)),
+        world.settings().format_precision,
+    );
+}
+
 fn show_integrations_pop_up(
     ui: &mut Ui,
     id: egui::Id,
@@ -178,6 +222,7 @@ fn show_integrations_pop_up(
                     ui.label("Line");
                     ui.label("Integrator");
                     ui.label("Step Size");
+                    ui.label("");
                     ui.end_row();
 
                     // table body:
@@ -195,7 +240,7 @@ fn show_integrations_pop_up(
                             ui,
                             world[integration.borrow().integrator_idx()].borrow().stroke,
                             Some((
-                                &world.settings.point_formats.derived_position,
+                                &world.settings().point_formats.derived_position,
                                 world[integration.borrow().step_size_idx()].borrow().color,
                             )),
                         );
@@ -217,6 +262,24 @@ fn show_integrations_pop_up(
                                 step_size_idx,
                             };
                         }
+                        match integration.borrow().progress() {
+                            Some(progress) => {
+                                ui.add(
+                                    egui::ProgressBar::new(progress)
+                                        .desired_width(60.)
+                                        .show_percentage(),
+                                );
+                            }
+                            None => {
+                                ui.horizontal(|ui| {
+                                    divergence_badge(
+                                        ui,
+                                        integration.borrow().labelled_divergence(world),
+                                        world.settings().format_precision,
+                                    );
+                                });
+                            }
+                        }
                         ui.end_row();
                     }
                 });
@@ -240,18 +303,22 @@ fn show_integrator_selector(
     )
     .selected_text(current_integrator.borrow().core.label())
     .show_ui(ui, |ui| {
-        world
-            .integrators()
-            .enumerate()
-            .for_each(|(each_idx, each_integrator)| {
-                let each_core_integrator = &each_integrator.borrow().core;
+        for (samples_per_step, members) in world.integrators_by_samples() {
+            ui.label(Integrator::group_label(samples_per_step));
+            for each_idx in members {
+                let each_core_integrator = &world[each_idx].borrow().core;
                 ui.selectable_value(
                     &mut selected_integrator_idx,
                     each_idx,
                     each_core_integrator.label(),
                 )
-                .on_hover_text(each_core_integrator.description());
-            });
+                .on_hover_text(format!(
+                    "{}\n\n{}",
+                    each_core_integrator.description(),
+                    each_core_integrator.metadata()
+                ));
+            }
+        }
     })
     .response
     .on_hover_text(current_integrator.borrow().core.description());
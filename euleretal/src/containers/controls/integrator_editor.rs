@@ -0,0 +1,319 @@
+use super::{
+    constants,
+    core::{
+        integrators::{
+            custom::{Custom, QuantityCounts, QuantityNames, Stage, Term},
+            serde_box_dyn_integrator::IntegratorSerDe,
+        },
+        Integrator as _,
+    },
+    entities::Integrator,
+    misc::entity_store,
+    ui_import::{
+        egui::{self, DragValue, Label, TextEdit, TextStyle},
+        Color32, Stroke, Ui,
+    },
+    World,
+};
+
+/// State of the editor window, which is kept in egui's memory while the window is open.
+#[derive(Clone)]
+struct EditorState {
+    /// `None` if a new integrator is being created
+    integrator_idx: Option<entity_store::Index<Integrator>>,
+    custom: Custom,
+}
+
+fn editor_id() -> egui::Id {
+    egui::Id::new("integrator editor")
+}
+
+/// Opens the editor for a new custom integrator.
+pub fn open_new(ui: &Ui) {
+    ui.memory().id_data_temp.insert(
+        editor_id(),
+        EditorState {
+            integrator_idx: None,
+            custom: Custom::default(),
+        },
+    );
+}
+
+/// Opens the editor for `integrator`, if it is a custom integrator.
+pub fn open_existing(
+    ui: &Ui,
+    integrator_idx: entity_store::Index<Integrator>,
+    integrator: &Integrator,
+) {
+    if let IntegratorSerDe::Custom(custom) = integrator.core.to_concrete_type() {
+        ui.memory().id_data_temp.insert(
+            editor_id(),
+            EditorState {
+                integrator_idx: Some(integrator_idx),
+                custom,
+            },
+        );
+    }
+}
+
+pub fn is_custom(integrator: &Integrator) -> bool {
+    matches!(
+        integrator.core.to_concrete_type(),
+        IntegratorSerDe::Custom(_)
+    )
+}
+
+/// Shows the editor window (if open), and applies the edited integrator to `world` when the user
+/// confirms.
+pub fn show(ui: &Ui, world: &mut World) {
+    let state = ui
+        .memory()
+        .id_data_temp
+        .get::<EditorState>(&editor_id())
+        .cloned();
+    if let Some(mut state) = state {
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new("Integrator Editor")
+            .id(editor_id())
+            .open(&mut open)
+            .collapsible(false)
+            .show(ui.ctx(), |ui| {
+                apply = show_editor(ui, &mut state.custom);
+            });
+        if apply {
+            if let Some(integrator_idx) = state.integrator_idx {
+                world.edit(integrator_idx, |integrator: &mut Integrator| {
+                    integrator.core = Box::new(state.custom);
+                });
+            } else {
+                world.add_integrator(Integrator {
+                    core: Box::new(state.custom),
+                    stroke: Stroke::new(1_f32, Color32::WHITE),
+                });
+            }
+            ui.memory().id_data_temp.remove(&editor_id());
+        } else if open {
+            ui.memory().id_data_temp.insert(editor_id(), state);
+        } else {
+            ui.memory().id_data_temp.remove(&editor_id());
+        }
+    }
+}
+
+/// Returns `true` if the user wants to apply the edited integrator.
+fn show_editor(ui: &mut Ui, custom: &mut Custom) -> bool {
+    ui.horizontal(|ui| {
+        ui.label("Label");
+        ui.add(TextEdit::singleline(&mut custom.label));
+    });
+    ui.separator();
+
+    let names = custom.quantity_names();
+    let counts = custom.quantity_counts();
+    let mut deletion = None;
+    let mut move_up = None;
+    for (n, (stage, available)) in custom.stages.iter_mut().zip(counts).enumerate() {
+        ui.horizontal(|ui| {
+            if ui.small_button(constants::BUTTON_GLYPH_DELETE).clicked() {
+                deletion = Some(n);
+            }
+            if ui
+                .add_enabled(n > 0, egui::Button::new("Up").small())
+                .clicked()
+            {
+                move_up = Some(n);
+            }
+            ui.label(format!("{}.", n + 1));
+            show_stage_kind_selector(ui, n, stage);
+        });
+        ui.indent(("stage", n), |ui| {
+            edit_stage(ui, n, stage, &names, available);
+        });
+    }
+    if let Some(n) = deletion {
+        custom.stages.remove(n);
+    }
+    if let Some(n) = move_up {
+        custom.stages.swap(n - 1, n);
+    }
+    if ui.small_button(constants::BUTTON_GLYPH_ADD).clicked() {
+        custom.stages.push(Stage::Acceleration { position: 0 });
+    }
+
+    ui.separator();
+    match custom.validate() {
+        Ok(()) => {
+            ui.add(Label::new(custom.description()).text_style(TextStyle::Monospace));
+            ui.separator();
+            ui.button("Apply").clicked()
+        }
+        Err(err) => {
+            ui.add(Label::new(&err).text_color(Color32::RED));
+            ui.separator();
+            ui.add_enabled(false, egui::Button::new("Apply"))
+                .on_disabled_hover_text(err);
+            false
+        }
+    }
+}
+
+fn edit_stage(
+    ui: &mut Ui,
+    n: usize,
+    stage: &mut Stage,
+    names: &QuantityNames,
+    available: QuantityCounts,
+) {
+    match stage {
+        Stage::Acceleration { position } => {
+            ui.horizontal(|ui| {
+                ui.label("at");
+                select_quantity(
+                    ui,
+                    ("position", n),
+                    position,
+                    &names.positions[..available.positions],
+                );
+            });
+        }
+        Stage::Position {
+            dt_fraction,
+            base,
+            velocity_terms,
+            acceleration_terms,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("start at");
+                select_quantity(
+                    ui,
+                    ("base", n),
+                    base,
+                    &names.positions[..available.positions],
+                );
+                edit_dt_fraction(ui, dt_fraction);
+            });
+            edit_terms(
+                ui,
+                ("velocity terms", n),
+                "+ v·dt",
+                velocity_terms,
+                &names.velocities[..available.velocities],
+            );
+            edit_terms(
+                ui,
+                ("acceleration terms", n),
+                "+ a·dt²",
+                acceleration_terms,
+                &names.accelerations[..available.accelerations],
+            );
+        }
+        Stage::Velocity {
+            dt_fraction,
+            base,
+            acceleration_terms,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("start at");
+                select_quantity(
+                    ui,
+                    ("base", n),
+                    base,
+                    &names.velocities[..available.velocities],
+                );
+                edit_dt_fraction(ui, dt_fraction);
+            });
+            edit_terms(
+                ui,
+                ("acceleration terms", n),
+                "+ a·dt",
+                acceleration_terms,
+                &names.accelerations[..available.accelerations],
+            );
+        }
+    }
+}
+
+fn show_stage_kind_selector(ui: &mut Ui, n: usize, stage: &mut Stage) {
+    let kind_label = |stage: &Stage| match stage {
+        Stage::Acceleration { .. } => "Acceleration",
+        Stage::Position { .. } => "Position",
+        Stage::Velocity { .. } => "Velocity",
+    };
+    let templates = [
+        Stage::Acceleration { position: 0 },
+        Stage::Position {
+            dt_fraction: (1, 1),
+            base: 0,
+            velocity_terms: vec![Term::new(0)],
+            acceleration_terms: Vec::new(),
+        },
+        Stage::Velocity {
+            dt_fraction: (1, 1),
+            base: 0,
+            acceleration_terms: vec![Term::new(0)],
+        },
+    ];
+    let current_label = kind_label(stage);
+    egui::ComboBox::from_id_source(ui.make_persistent_id(("stage kind", n)))
+        .selected_text(current_label)
+        .show_ui(ui, |ui| {
+            for template in templates {
+                let label = kind_label(&template);
+                if ui.selectable_label(label == current_label, label).clicked()
+                    && label != current_label
+                {
+                    *stage = template;
+                }
+            }
+        });
+}
+
+fn select_quantity(
+    ui: &mut Ui,
+    id_source: impl ::std::hash::Hash + ::std::fmt::Debug,
+    quantity: &mut usize,
+    names: &[String],
+) {
+    egui::ComboBox::from_id_source(ui.make_persistent_id(id_source))
+        .width(40.)
+        .selected_text(names.get(*quantity).map_or("?", String::as_str))
+        .show_ui(ui, |ui| {
+            for (each_quantity, name) in names.iter().enumerate() {
+                ui.selectable_value(quantity, each_quantity, name);
+            }
+        });
+}
+
+fn edit_dt_fraction(ui: &mut Ui, (numerator, denominator): &mut (usize, usize)) {
+    ui.label("with dt ×");
+    ui.add(DragValue::new(numerator).clamp_range(0..=16));
+    ui.label("/");
+    ui.add(DragValue::new(denominator).clamp_range(1..=16));
+}
+
+fn edit_terms(
+    ui: &mut Ui,
+    id_source: (&str, usize),
+    add_label: &str,
+    terms: &mut Vec<Term>,
+    names: &[String],
+) {
+    let mut deletion = None;
+    for (t, term) in terms.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            if ui.small_button(constants::BUTTON_GLYPH_DELETE).clicked() {
+                deletion = Some(t);
+            }
+            ui.add(DragValue::new(&mut term.factor).speed(0.01))
+                .on_hover_text("Factor");
+            select_quantity(ui, (id_source, t), &mut term.quantity, names);
+        });
+    }
+    if let Some(t) = deletion {
+        terms.remove(t);
+    }
+    if ui.small_button(add_label).clicked() {
+        terms.push(Term::new(0));
+    }
+}
@@ -1,19 +1,102 @@
-use super::{misc::my_stroke_ui, ui_import::Ui, World};
+use super::{
+    constants,
+    core::{self, integration_step::symbolic::Trace, truncation::Analysis},
+    entities::Integrator,
+    integrator_editor,
+    misc::{
+        entity_store::{self, RemovalStrategy},
+        my_stroke_ui,
+    },
+    removal,
+    ui_import::{egui, Ui},
+    World,
+};
+use ::std::{collections::hash_map::DefaultHasher, hash::Hasher};
 
 #[allow(clippy::borrowed_box)]
 pub fn show(ui: &mut Ui, world: &mut World) {
-    world.integrators().iter().for_each(|integrator| {
-        let (label, description);
-        {
-            let core_integrator = &integrator.borrow().core;
-            label = core_integrator.label();
-            description = core_integrator.description();
+    let mut deletion: Option<(entity_store::Index<Integrator>, RemovalStrategy<Integrator>)> = None;
+    let mut stroke_edit = None;
+    if ui
+        .small_button(constants::BUTTON_GLYPH_ADD)
+        .on_hover_text("Compose a new integrator")
+        .clicked()
+    {
+        integrator_editor::open_new(ui);
+    }
+    let alternatives = world
+        .integrators()
+        .enumerate()
+        .map(|(idx, integrator)| (idx, integrator.borrow().core.label()))
+        .collect::<Vec<_>>();
+    for (samples_per_step, members) in world.integrators_by_samples() {
+        ui.label(Integrator::group_label(samples_per_step));
+        for integrator_idx in members {
+            let integrator = &world[integrator_idx];
+            let (label, description);
+            {
+                let core_integrator = &integrator.borrow().core;
+                label = core_integrator.label();
+                description = format!(
+                    "{}\n\n{}\n\n{}",
+                    core_integrator.description(),
+                    core_integrator.metadata(),
+                    truncation_analysis(ui, &**core_integrator)
+                );
+            }
+            ui.horizontal(|ui| {
+                if let Some(strategy) = removal::show_delete_button(
+                    ui,
+                    integrator_idx,
+                    world.count_references(integrator_idx),
+                    "integration",
+                    &alternatives,
+                    |strategy| world.check_integrator_removal(integrator_idx, strategy),
+                ) {
+                    deletion = Some((integrator_idx, strategy));
+                }
+                if integrator_editor::is_custom(&integrator.borrow())
+                    && ui.small_button("Edit").clicked()
+                {
+                    integrator_editor::open_existing(ui, integrator_idx, &integrator.borrow());
+                }
+                if ui
+                    .small_button("TeX")
+                    .on_hover_text("Copy the description as LaTeX")
+                    .clicked()
+                {
+                    ui.output().copied_text = Trace::of(&*integrator.borrow().core).to_latex();
+                }
+                let mut stroke = integrator.borrow().stroke;
+                my_stroke_ui(ui, &mut stroke, &label, &description);
+                if stroke != integrator.borrow().stroke {
+                    stroke_edit = Some((integrator_idx, stroke));
+                }
+            });
         }
-        my_stroke_ui(
-            ui,
-            &mut integrator.borrow_mut().stroke,
-            &label,
-            &description,
-        );
-    });
+    }
+    if let Some((integrator_idx, stroke)) = stroke_edit {
+        world.edit(integrator_idx, |integrator: &mut Integrator| {
+            integrator.stroke = stroke;
+        });
+    }
+    if let Some((integrator_idx, strategy)) = deletion {
+        if let Err(err) = world.remove_integrator(integrator_idx, strategy) {
+            log::warn!("Cannot delete integrator: {}", err);
+        }
+    }
+    integrator_editor::show(ui, world);
+}
+
+/// The analysis is cached, as it is too expensive to be repeated for each frame.
+fn truncation_analysis(ui: &Ui, integrator: &dyn core::Integrator) -> String {
+    let mut hasher = DefaultHasher::new();
+    integrator.hash(&mut hasher);
+    let id = egui::Id::new(("truncation analysis", hasher.finish()));
+    let cached = ui.memory().id_data_temp.get::<String>(&id).cloned();
+    cached.unwrap_or_else(|| {
+        let analysis = Analysis::of(integrator).to_string();
+        ui.memory().id_data_temp.insert(id, analysis.clone());
+        analysis
+    })
 }
@@ -1,12 +1,14 @@
 use super::{Ui, World};
 
 pub fn show(ui: &mut Ui, world: &mut World) {
-    ui.vertical(|ui| {
-        ui.checkbox(&mut world.settings.layerflags.coordinates, "Coordinates");
-        ui.checkbox(
-            &mut world.settings.layerflags.acceleration_field,
-            "Acceleration Field",
-        );
-        ui.checkbox(&mut world.settings.layerflags.inspector, "Inspector");
+    world.edit_settings(|settings| {
+        ui.vertical(|ui| {
+            ui.checkbox(&mut settings.layerflags.coordinates, "Coordinates");
+            ui.checkbox(
+                &mut settings.layerflags.acceleration_field,
+                "Acceleration Field",
+            );
+            ui.checkbox(&mut settings.layerflags.inspector, "Inspector");
+        });
     });
 }
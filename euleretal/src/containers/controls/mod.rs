@@ -1,13 +1,16 @@
 use super::{
-    constants, core, entities, misc, ui_import,
+    constants, core, entities, import, misc, ui_import,
     ui_import::{egui::CollapsingHeader, Ui},
     World,
 };
 
+mod integrator_editor;
 mod integrators;
 mod layers;
+mod removal;
 mod scenarios;
 mod step_sizes;
+mod work_precision;
 
 pub fn show(ui: &mut Ui, world: &mut World) {
     ui.collapsing("Layer Visibility", |ui| {
@@ -26,4 +29,7 @@ pub fn show(ui: &mut Ui, world: &mut World) {
     CollapsingHeader::new("Step Sizes")
         .default_open(true)
         .show(ui, |ui| step_sizes::show(ui, world));
+    ui.collapsing("Work-Precision", |ui| {
+        work_precision::show(ui, world);
+    });
 }
@@ -0,0 +1,85 @@
+use super::{
+    constants,
+    misc::entity_store::{Index, RemovalStrategy},
+    ui_import::{egui, Ui},
+};
+
+/// Shows a delete button for the entity at `idx`.  If the entity is in use, the button opens a
+/// pop-up which offers to delete the users as well, or to let them use one of the `alternatives`
+/// instead.  Options rejected by `check` are disabled, and show the reason when hovered.
+///
+/// Returns the removal strategy chosen by the user.
+pub fn show_delete_button<T>(
+    ui: &mut Ui,
+    idx: Index<T>,
+    usage: usize,
+    users: &str,
+    alternatives: &[(Index<T>, String)],
+    check: impl Fn(RemovalStrategy<T>) -> Result<(), String>,
+) -> Option<RemovalStrategy<T>> {
+    let mut strategy = None;
+    if usage == 0 {
+        if add_checked_button(
+            ui,
+            constants::BUTTON_GLYPH_DELETE,
+            RemovalStrategy::Block,
+            &check,
+        ) {
+            strategy = Some(RemovalStrategy::Block);
+        }
+    } else {
+        let popup_id = ui.make_persistent_id(("delete_button", users, idx));
+        let button_response = ui
+            .small_button(constants::BUTTON_GLYPH_DELETE)
+            .on_hover_text(format!("In use by {} {}(s)", usage, users));
+        if button_response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        egui::popup::popup_below_widget(ui, popup_id, &button_response, |ui| {
+            ui.set_min_width(200.);
+            ui.label(format!("In use by {} {}(s).", usage, users));
+            if add_checked_button(
+                ui,
+                format!("Delete together with its {}(s)", users),
+                RemovalStrategy::Cascade,
+                &check,
+            ) {
+                strategy = Some(RemovalStrategy::Cascade);
+            }
+            ui.separator();
+            ui.label("…or let them use:");
+            for (alternative_idx, label) in alternatives {
+                if *alternative_idx != idx
+                    && add_checked_button(
+                        ui,
+                        label,
+                        RemovalStrategy::Reassign(*alternative_idx),
+                        &check,
+                    )
+                {
+                    strategy = Some(RemovalStrategy::Reassign(*alternative_idx));
+                }
+            }
+        });
+        if strategy.is_some() {
+            ui.memory().close_popup();
+        }
+    }
+    strategy
+}
+
+fn add_checked_button<T>(
+    ui: &mut Ui,
+    text: impl ToString,
+    strategy: RemovalStrategy<T>,
+    check: impl Fn(RemovalStrategy<T>) -> Result<(), String>,
+) -> bool {
+    match check(strategy) {
+        Ok(()) => ui.small_button(text).clicked(),
+        Err(reason) => {
+            ui.add_enabled(false, egui::Button::new(text).small())
+                .on_disabled_hover_text(reason);
+            false
+        }
+    }
+}
@@ -1,25 +1,566 @@
 use super::{
-    ui_import::{egui, egui::Slider, Ui},
+    constants,
+    core::{
+        bodies::Body,
+        event::{Event, EventFunction},
+        obstacle::{Obstacle, Shape},
+        orbit::Orbit,
+        scenarios::serde_box_dyn_acceleration_field::AccelerationFieldSerDe,
+        AccelerationField, Position, Scenario, Velocity,
+    },
+    import::Vec3,
+    misc::entity_store::{self, RemovalStrategy},
+    removal,
+    ui_import::{
+        egui,
+        egui::{DragValue, Label},
+        Color32, Ui,
+    },
     World,
 };
+use ::std::{cell::RefCell, ops::RangeInclusive};
+
+enum Operation {
+    Noop,
+    Create,
+    Duplicate(entity_store::Index<Scenario>),
+    Delete(entity_store::Index<Scenario>, RemovalStrategy<Scenario>),
+    IntegrateFurther(entity_store::Index<Scenario>, bool),
+    Edit(entity_store::Index<Scenario>, Box<Scenario>),
+}
 
-#[allow(clippy::borrowed_box)]
 pub fn show(ui: &mut Ui, world: &mut World) {
+    let error_id = ui.make_persistent_id("scenario validation error");
+    let mut operation = Operation::Noop;
+    let mut validation = None;
     egui::Grid::new("integrator grid")
         .striped(false)
         .show(ui, |ui| {
             // table header:
+            if ui.small_button(constants::BUTTON_GLYPH_ADD).clicked() {
+                operation = Operation::Create;
+            }
+            ui.label("Acceleration Field");
             ui.label("Duration");
-            ui.label("Scenario");
+            ui.label("Start Position");
+            ui.label("Start Velocity");
+            ui.label("Event");
+            ui.label("Obstacles");
+            ui.label("Bodies");
+            ui.label("");
+            ui.label("");
             ui.end_row();
 
-            for scenario in world.scenarios().iter() {
-                let mut duration_for_edit = scenario.borrow().duration.into();
-                ui.add(Slider::new(&mut duration_for_edit, 0.1..=50.).logarithmic(true));
-                scenario.borrow_mut().duration = duration_for_edit.into();
+            let alternatives = world
+                .scenarios()
+                .enumerate()
+                .map(|(idx, scenario)| (idx, scenario.borrow().label()))
+                .collect::<Vec<_>>();
+            for (scenario_idx, scenario) in world.scenarios().enumerate() {
+                if let Some(strategy) = removal::show_delete_button(
+                    ui,
+                    scenario_idx,
+                    world.count_references(scenario_idx),
+                    "canvas",
+                    &alternatives,
+                    |strategy| world.check_scenario_removal(scenario_idx, strategy),
+                ) {
+                    operation = Operation::Delete(scenario_idx, strategy);
+                }
+
+                if let Some(result) = show_scenario_editor(ui, scenario_idx, scenario) {
+                    validation = Some(result.map(|edited| {
+                        operation = Operation::Edit(scenario_idx, Box::new(edited));
+                    }));
+                }
+
+                if ui
+                    .small_button("Copy")
+                    .on_hover_text("Duplicate this scenario")
+                    .clicked()
+                {
+                    operation = Operation::Duplicate(scenario_idx);
+                }
 
-                ui.label(scenario.borrow().acceleration.label());
+                let integrating_further = world.is_integrating_further(scenario_idx);
+                if ui
+                    .selectable_label(integrating_further, "Further")
+                    .on_hover_text("Keep appending steps to the integrations of this scenario")
+                    .clicked()
+                {
+                    operation = Operation::IntegrateFurther(scenario_idx, !integrating_further);
+                }
                 ui.end_row();
             }
         });
+
+    validation = show_orbit_builder(ui, world).or(validation);
+
+    // the validation error of the last edit is shown until the next valid edit:
+    match validation {
+        Some(Ok(())) => ui.memory().id_data_temp.remove(&error_id),
+        Some(Err(err)) => ui.memory().id_data_temp.insert(error_id, err),
+        None => (),
+    }
+    let error = ui.memory().id_data_temp.get::<String>(&error_id).cloned();
+    if let Some(error) = error {
+        ui.add(Label::new(error).text_color(Color32::RED));
+    }
+
+    match operation {
+        Operation::Create => {
+            world.add_scenario(Scenario {
+                acceleration: AccelerationFieldSerDe::variants().remove(0).into_box(),
+                start_position: Position::new(0., 1., 0.),
+                start_velocity: Velocity::new(1., 0., 0.),
+                duration: 1.0.into(),
+                event: None,
+                obstacles: Vec::new(),
+                mass: 1.,
+                bodies: Vec::new(),
+            });
+        }
+        Operation::Duplicate(scenario_idx) => {
+            let duplicate = world.scenarios()[scenario_idx].borrow().clone();
+            world.add_scenario(duplicate);
+        }
+        Operation::Delete(scenario_idx, strategy) => {
+            if let Err(err) = world.remove_scenario(scenario_idx, strategy) {
+                log::warn!("Cannot delete scenario: {}", err);
+            }
+        }
+        Operation::IntegrateFurther(scenario_idx, enabled) => {
+            world.set_integrating_further(scenario_idx, enabled);
+        }
+        Operation::Edit(scenario_idx, edited) => {
+            world.edit(scenario_idx, |scenario: &mut Scenario| *scenario = *edited);
+        }
+        Operation::Noop => (),
+    }
+}
+
+/// Shows the editable attributes of `scenario` as cells of a table row.  The scenario is only
+/// cloned once an attribute has been edited.
+///
+/// Returns the edited scenario if it has been edited and is valid, or the validation error.
+fn show_scenario_editor(
+    ui: &mut Ui,
+    scenario_idx: entity_store::Index<Scenario>,
+    scenario: &RefCell<Scenario>,
+) -> Option<Result<Scenario, String>> {
+    let current = scenario.borrow();
+    let mut edited = None;
+
+    // acceleration field:
+    ui.horizontal(|ui| {
+        let current_label = current.acceleration.label();
+        egui::ComboBox::from_id_source(ui.make_persistent_id(("acceleration field", scenario_idx)))
+            .selected_text(&current_label)
+            .show_ui(ui, |ui| {
+                for variant in AccelerationFieldSerDe::variants() {
+                    let acceleration = variant.into_box();
+                    let label = acceleration.label();
+                    if ui.selectable_label(label == current_label, label).clicked() {
+                        edited.get_or_insert_with(|| current.clone()).acceleration = acceleration;
+                    }
+                }
+            });
+        if let Some(acceleration) = edit_acceleration_field(ui, current.acceleration.as_ref()) {
+            edited.get_or_insert_with(|| current.clone()).acceleration = acceleration;
+        }
+    });
+    // duration:
+    let mut duration = f32::from(current.duration);
+    if ui
+        .add(
+            DragValue::new(&mut duration)
+                .speed(0.01)
+                .clamp_range(0.1..=50.),
+        )
+        .changed()
+    {
+        edited.get_or_insert_with(|| current.clone()).duration = duration.into();
+    }
+    // start condition:
+    let mut start_position = *current.start_position.as_point();
+    if edit_xy(ui, &mut start_position.coords) {
+        edited.get_or_insert_with(|| current.clone()).start_position = start_position.into();
+    }
+    let mut start_velocity = *current.start_velocity.as_vector();
+    if edit_xy(ui, &mut start_velocity) {
+        edited.get_or_insert_with(|| current.clone()).start_velocity = start_velocity.into();
+    }
+    // event:
+    let mut event = current.event;
+    if edit_event(ui, scenario_idx, &mut event) {
+        edited.get_or_insert_with(|| current.clone()).event = event;
+    }
+    // obstacles:
+    if let Some(obstacles) = edit_obstacles(ui, scenario_idx, &current.obstacles) {
+        edited.get_or_insert_with(|| current.clone()).obstacles = obstacles;
+    }
+    // bodies:
+    if let Some((mass, bodies)) = edit_bodies(ui, scenario_idx, current.mass, &current.bodies) {
+        let edited = edited.get_or_insert_with(|| current.clone());
+        edited.mass = mass;
+        edited.bodies = bodies;
+    }
+
+    drop(current);
+    let edited = edited?;
+    Some(edited.validate().map(|()| edited))
+}
+
+/// Edits the parameters of `acceleration`, if it has any.  Returns the edited field if changed.
+///
+/// The ranges of the parameters lie within what `AccelerationField::validate()` accepts, so that
+/// dragging cannot produce an invalid field.  Typed values are validated with the scenario.
+fn edit_acceleration_field(
+    ui: &mut Ui,
+    acceleration: &dyn AccelerationField,
+) -> Option<Box<dyn AccelerationField>> {
+    let mut field = acceleration.to_concrete_type();
+    let mut edit = |value: &mut f32, range: RangeInclusive<f32>, prefix: &str, hover_text: &str| {
+        ui.add(
+            DragValue::new(value)
+                .speed(0.01)
+                .clamp_range(range)
+                .prefix(prefix),
+        )
+        .on_hover_text(hover_text)
+        .changed()
+    };
+    let changed = match &mut field {
+        AccelerationFieldSerDe::HarmonicOscillator(oscillator) => {
+            let x_changed = edit(
+                &mut oscillator.omega_x,
+                0. ..=10.,
+                "ωx: ",
+                "Angular frequency along x",
+            );
+            let y_changed = edit(
+                &mut oscillator.omega_y,
+                0. ..=10.,
+                "ωy: ",
+                "Angular frequency along y",
+            );
+            x_changed || y_changed
+        }
+        AccelerationFieldSerDe::QuarticOscillator(oscillator) => {
+            let stiffness_changed = edit(
+                &mut oscillator.stiffness,
+                0. ..=10.,
+                "k: ",
+                "Harmonic stiffness",
+            );
+            let anharmonicity_changed = edit(
+                &mut oscillator.anharmonicity,
+                -10. ..=10.,
+                "λ: ",
+                "Strength of the quartic term of the potential (negative: the well is surrounded \
+                 by a barrier, beyond which the particle escapes)",
+            );
+            stiffness_changed || anharmonicity_changed
+        }
+        AccelerationFieldSerDe::DoubleWell(well) => {
+            let separation_changed = edit(
+                &mut well.separation,
+                0.01..=10.,
+                "a: ",
+                "Distance of the two minima from the center",
+            );
+            let barrier_changed = edit(
+                &mut well.barrier,
+                0.01..=10.,
+                "V₀: ",
+                "Height of the barrier between the minima",
+            );
+            separation_changed || barrier_changed
+        }
+        _ => false,
+    };
+    changed.then(|| field.into_box())
+}
+
+/// Edits the event function and whether the event terminates the scenario.  Returns `true` if
+/// changed.
+fn edit_event(
+    ui: &mut Ui,
+    scenario_idx: entity_store::Index<Scenario>,
+    event: &mut Option<Event>,
+) -> bool {
+    ui.horizontal(|ui| {
+        let mut changed = false;
+        let current = event.map(|event| event.function);
+        egui::ComboBox::from_id_source(ui.make_persistent_id(("event", scenario_idx)))
+            .selected_text(current.map_or("None", EventFunction::label))
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(current.is_none(), "None").clicked() {
+                    *event = None;
+                    changed = true;
+                }
+                for function in EventFunction::variants() {
+                    if ui
+                        .selectable_label(current == Some(function), function.label())
+                        .clicked()
+                    {
+                        *event = Some(Event {
+                            function,
+                            terminal: event.is_none_or(|event| event.terminal),
+                        });
+                        changed = true;
+                    }
+                }
+            });
+        if let Some(event) = event {
+            changed |= ui
+                .checkbox(&mut event.terminal, "stop")
+                .on_hover_text("End the scenario with the first occurrence of the event")
+                .changed();
+        }
+        changed
+    })
+    .inner
+}
+
+/// Edits, adds and removes obstacles.  Returns the edited obstacles if changed.
+fn edit_obstacles(
+    ui: &mut Ui,
+    scenario_idx: entity_store::Index<Scenario>,
+    obstacles: &[Obstacle],
+) -> Option<Vec<Obstacle>> {
+    let mut edited = None;
+    egui::CollapsingHeader::new(format!("{} Obstacles", obstacles.len()))
+        .id_source(("obstacles", scenario_idx))
+        .show(ui, |ui| {
+            let mut removed = None;
+            for (obstacle_idx, &obstacle) in obstacles.iter().enumerate() {
+                let mut obstacle = obstacle;
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    if ui.small_button(constants::BUTTON_GLYPH_DELETE).clicked() {
+                        removed = Some(obstacle_idx);
+                    }
+                    ui.label(obstacle.shape.label());
+                    let mut position = *obstacle.position.as_point();
+                    if edit_xy(ui, &mut position.coords) {
+                        obstacle.position = position.into();
+                        changed = true;
+                    }
+                    changed |= match &mut obstacle.shape {
+                        Shape::Wall { normal } => {
+                            ui.label("normal");
+                            edit_xy(ui, normal)
+                        }
+                        Shape::Sphere { radius } => ui
+                            .add(
+                                DragValue::new(radius)
+                                    .speed(0.01)
+                                    .clamp_range(0.01..=f32::MAX)
+                                    .prefix("r: "),
+                            )
+                            .changed(),
+                        Shape::Cuboid { half_extents } => {
+                            ui.label("half size");
+                            edit_xy(ui, half_extents)
+                        }
+                    };
+                    changed |= ui
+                        .add(
+                            DragValue::new(&mut obstacle.restitution)
+                                .speed(0.01)
+                                .clamp_range(0. ..=1.)
+                                .prefix("restitution: "),
+                        )
+                        .on_hover_text("1 for elastic bounces, 0 for perfectly inelastic ones")
+                        .changed();
+                });
+                if changed {
+                    edited.get_or_insert_with(|| obstacles.to_vec())[obstacle_idx] = obstacle;
+                }
+            }
+            if let Some(obstacle_idx) = removed {
+                edited
+                    .get_or_insert_with(|| obstacles.to_vec())
+                    .remove(obstacle_idx);
+            }
+            ui.horizontal(|ui| {
+                for shape in Shape::variants() {
+                    if ui
+                        .small_button(format!("{} {}", constants::BUTTON_GLYPH_ADD, shape.label()))
+                        .clicked()
+                    {
+                        edited
+                            .get_or_insert_with(|| obstacles.to_vec())
+                            .push(Obstacle::new(shape, Position::origin()));
+                    }
+                }
+            });
+        });
+    edited
+}
+
+/// Edits the mass of the particle, and edits, adds and removes further bodies.  Returns the
+/// edited mass and bodies if changed.
+fn edit_bodies(
+    ui: &mut Ui,
+    scenario_idx: entity_store::Index<Scenario>,
+    particle_mass: f32,
+    bodies: &[Body],
+) -> Option<(f32, Vec<Body>)> {
+    let mut edited = None;
+    egui::CollapsingHeader::new(format!("{} Bodies", 1 + bodies.len()))
+        .id_source(("bodies", scenario_idx))
+        .show(ui, |ui| {
+            let mut mass = particle_mass;
+            if ui
+                .add(
+                    DragValue::new(&mut mass)
+                        .speed(0.01)
+                        .clamp_range(0. ..=f32::MAX)
+                        .prefix("particle mass: "),
+                )
+                .on_hover_text("Only matters for the attraction of the other bodies")
+                .changed()
+            {
+                edited
+                    .get_or_insert_with(|| (particle_mass, bodies.to_vec()))
+                    .0 = mass;
+            }
+            let mut removed = None;
+            for (body_idx, &body) in bodies.iter().enumerate() {
+                let mut body = body;
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    if ui.small_button(constants::BUTTON_GLYPH_DELETE).clicked() {
+                        removed = Some(body_idx);
+                    }
+                    changed |= ui
+                        .add(
+                            DragValue::new(&mut body.mass)
+                                .speed(0.01)
+                                .clamp_range(0. ..=f32::MAX)
+                                .prefix("m: "),
+                        )
+                        .changed();
+                    let mut position = *body.position.as_point();
+                    if edit_xy(ui, &mut position.coords) {
+                        body.position = position.into();
+                        changed = true;
+                    }
+                    ui.label("v");
+                    let mut velocity = *body.velocity.as_vector();
+                    if edit_xy(ui, &mut velocity) {
+                        body.velocity = velocity.into();
+                        changed = true;
+                    }
+                });
+                if changed {
+                    edited
+                        .get_or_insert_with(|| (particle_mass, bodies.to_vec()))
+                        .1[body_idx] = body;
+                }
+            }
+            if let Some(body_idx) = removed {
+                edited
+                    .get_or_insert_with(|| (particle_mass, bodies.to_vec()))
+                    .1
+                    .remove(body_idx);
+            }
+            if ui
+                .small_button(format!("{} Body", constants::BUTTON_GLYPH_ADD))
+                .clicked()
+            {
+                edited
+                    .get_or_insert_with(|| (particle_mass, bodies.to_vec()))
+                    .1
+                    .push(Body::new(1., Position::origin(), Velocity::zeros()));
+            }
+        });
+    edited
+}
+
+/// Adds a Kepler scenario with the orbital elements entered by the user, once they have been
+/// confirmed.
+///
+/// Returns the validation result if confirmed.
+fn show_orbit_builder(ui: &mut Ui, world: &mut World) -> Option<Result<(), String>> {
+    let id = ui.make_persistent_id("kepler orbit builder");
+    let mut orbit = *ui.memory().id_data_temp.get_or_default::<Orbit>(id);
+    let mut create = None;
+    ui.horizontal(|ui| {
+        ui.label("Kepler:");
+        ui.add(
+            DragValue::new(&mut orbit.semi_major_axis)
+                .speed(0.01)
+                .clamp_range(0.01..=10.)
+                .prefix("a: "),
+        )
+        .on_hover_text("Semi-major axis");
+        ui.add(
+            DragValue::new(&mut orbit.eccentricity)
+                .speed(0.001)
+                .clamp_range(0. ..=0.99)
+                .prefix("e: "),
+        )
+        .on_hover_text("Eccentricity");
+        ui.add(
+            DragValue::new(&mut orbit.central_mass)
+                .speed(0.01)
+                .clamp_range(0.01..=100.)
+                .prefix("M: "),
+        )
+        .on_hover_text("Central mass");
+        ui.add(
+            DragValue::new(&mut orbit.periods)
+                .speed(0.1)
+                .clamp_range(0.1..=100.)
+                .suffix(" periods"),
+        );
+        ui.add(
+            DragValue::new(&mut orbit.softening)
+                .speed(0.001)
+                .clamp_range(0. ..=1.)
+                .prefix("ε: "),
+        )
+        .on_hover_text("Plummer softening length of the central mass");
+        if ui
+            .button("Create")
+            .on_hover_text("Add a scenario which starts at the perihelion of this orbit")
+            .clicked()
+        {
+            create = Some(orbit);
+        }
+        if ui
+            .button("Eccentric")
+            .on_hover_text("Add a stress test: 3 periods of an orbit with e = 0.9")
+            .clicked()
+        {
+            create = Some(Orbit {
+                eccentricity: 0.9,
+                periods: 3.,
+                ..Orbit::default()
+            });
+        }
+    });
+    ui.memory().id_data_temp.insert(id, orbit);
+    create.map(|orbit| {
+        orbit.validate()?;
+        world.add_scenario(orbit.scenario());
+        Ok(())
+    })
+}
+
+/// Edits the x and y coordinates of `vector`.  Returns `true` if changed.
+fn edit_xy(ui: &mut Ui, vector: &mut Vec3) -> bool {
+    ui.horizontal(|ui| {
+        let x_changed = ui
+            .add(DragValue::new(&mut vector.x).speed(0.01).prefix("x: "))
+            .changed();
+        let y_changed = ui
+            .add(DragValue::new(&mut vector.y).speed(0.01).prefix("y: "))
+            .changed();
+        x_changed || y_changed
+    })
+    .inner
 }
@@ -1,30 +1,86 @@
 use super::{
     constants,
     core::Duration,
-    entities::StepSize,
-    misc::{entity_store, UserLabel},
+    entities::{GroupMember, StepSize, StepSizeGroup},
+    misc::{
+        entity_store::{self, RemovalStrategy},
+        UserLabel,
+    },
+    removal,
     ui_import::{
         egui,
         egui::{
             color_picker::{color_edit_button_hsva, Alpha},
-            Slider, TextEdit,
+            DragValue, Slider, TextEdit,
         },
         Color32, Hsva, Ui,
     },
     World,
 };
+use crate::world::{MAX_STEP_DURATION, MIN_STEP_DURATION};
 
 enum Operation {
     Noop,
     Create,
-    Delete(entity_store::Index<StepSize>),
+    Delete(entity_store::Index<StepSize>, RemovalStrategy<StepSize>),
     SetDuration(entity_store::Index<StepSize>, Duration),
     SetColor(entity_store::Index<StepSize>, Color32),
     SetLabel(entity_store::Index<StepSize>, String),
+    SetGroupDt(entity_store::Index<StepSizeGroup>, Duration),
+    DeleteGroup(entity_store::Index<StepSizeGroup>),
+    GenerateSeries(SeriesGenerator),
+}
+
+/// Parameters of a geometric series of linked step sizes: `first_dt * ratio^i` for `i` in
+/// `0..count`.
+#[derive(Clone, Copy)]
+struct SeriesGenerator {
+    count: usize,
+    first_dt: f32,
+    ratio: f32,
+}
+
+impl SeriesGenerator {
+    /// The ratios of the members to `first_dt`.  The series ends early if the members would get
+    /// shorter than `MIN_STEP_DURATION`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn ratios(self) -> Vec<f32> {
+        (0..self.count)
+            .map(|i| self.ratio.powi(i as i32))
+            .take_while(|ratio| self.first_dt() * ratio >= MIN_STEP_DURATION * (1. - 1e-4))
+            .collect()
+    }
+
+    /// within `MIN_STEP_DURATION..=MAX_STEP_DURATION`
+    fn first_dt(self) -> f32 {
+        self.first_dt.clamp(MIN_STEP_DURATION, MAX_STEP_DURATION)
+    }
+}
+
+impl Default for SeriesGenerator {
+    fn default() -> Self {
+        Self {
+            count: 4,
+            first_dt: 0.5,
+            ratio: 0.5,
+        }
+    }
 }
 
 pub fn show(ui: &mut Ui, world: &mut World) {
-    let operation = show_step_size_table(ui, world);
+    let mut operation = show_step_size_table(ui, world);
+    if world.step_size_groups().iter().next().is_some() {
+        ui.separator();
+        if let group_operation @ (Operation::SetGroupDt(..) | Operation::DeleteGroup(_)) =
+            show_group_table(ui, world)
+        {
+            operation = group_operation;
+        }
+    }
+    ui.separator();
+    if let Some(generator) = show_series_generator(ui) {
+        operation = Operation::GenerateSeries(generator);
+    }
     match operation {
         Operation::Create => {
             world.add_step_size(StepSize {
@@ -33,17 +89,36 @@ pub fn show(ui: &mut Ui, world: &mut World) {
                 color: Color32::default(),
             });
         }
-        Operation::Delete(step_size) => {
-            world.remove_step_size(step_size);
+        Operation::Delete(step_size_idx, strategy) => {
+            if let Err(err) = world.remove_step_size(step_size_idx, strategy) {
+                log::warn!("Cannot delete step size: {}", err);
+            }
         }
         Operation::SetDuration(step_size_idx, new_duration) => {
-            world[step_size_idx].borrow_mut().duration = new_duration.max(0.01.into());
+            world.set_step_size_duration(step_size_idx, new_duration);
         }
         Operation::SetColor(step_size_idx, new_color) => {
-            world[step_size_idx].borrow_mut().color = new_color;
+            world.edit(step_size_idx, |step_size: &mut StepSize| {
+                step_size.color = new_color;
+            });
         }
         Operation::SetLabel(step_size_idx, new_label) => {
-            world[step_size_idx].borrow_mut().user_label.0 = new_label;
+            world.edit(step_size_idx, |step_size: &mut StepSize| {
+                step_size.user_label.0 = new_label;
+            });
+        }
+        Operation::SetGroupDt(group_idx, master_dt) => {
+            world.set_step_size_group_dt(group_idx, master_dt);
+        }
+        Operation::DeleteGroup(group_idx) => {
+            if let Err(err) = world.remove_step_size_group(group_idx) {
+                log::warn!("Cannot unlink step sizes: {}", err);
+            }
+        }
+        Operation::GenerateSeries(generator) => {
+            if let Err(err) = generate_series(world, generator) {
+                log::warn!("Cannot link step sizes: {}", err);
+            }
         }
         Operation::Noop => (),
     }
@@ -65,24 +140,38 @@ fn show_step_size_table(ui: &mut Ui, world: &World) -> Operation {
             ui.end_row();
 
             // table body:
+            let alternatives = world
+                .step_sizes()
+                .enumerate()
+                .map(|(idx, step_size)| (idx, format!("{}", step_size.borrow())))
+                .collect::<Vec<_>>();
             world
                 .step_sizes()
                 .enumerate()
                 .for_each(|(each_step_size_idx, each_step_size)| {
                     // button '-':
-                    if is_deletion_allowed(each_step_size_idx, world) {
-                        if ui.small_button(constants::BUTTON_GLYPH_DELETE).clicked() {
-                            operation = Operation::Delete(each_step_size_idx);
-                        }
-                    } else {
-                        ui.label("");
+                    if let Some(strategy) = removal::show_delete_button(
+                        ui,
+                        each_step_size_idx,
+                        world.count_references(each_step_size_idx),
+                        "integration",
+                        &alternatives,
+                        |strategy| world.check_step_size_removal(each_step_size_idx, strategy),
+                    ) {
+                        operation = Operation::Delete(each_step_size_idx, strategy);
                     }
                     // edit dt:
                     let mut dt = each_step_size.borrow().duration.into();
-                    if ui
-                        .add(Slider::new(&mut dt, 0.01..=2.).logarithmic(true))
-                        .changed()
+                    let mut response = ui.add(Slider::new(&mut dt, 0.01..=2.).logarithmic(true));
+                    if let Some(group) = world
+                        .step_size_groups()
+                        .iter()
+                        .find(|group| group.borrow().member(each_step_size_idx).is_some())
                     {
+                        response = response
+                            .on_hover_text(format!("Rescales its group {}", group.borrow()));
+                    }
+                    if response.changed() {
                         operation = Operation::SetDuration(each_step_size_idx, dt.into());
                     };
                     // edit color:
@@ -91,7 +180,7 @@ fn show_step_size_table(ui: &mut Ui, world: &World) -> Operation {
                         operation = Operation::SetColor(each_step_size_idx, color.into());
                     }
                     // edit label:
-                    let mut label = each_step_size.borrow().user_label.clone();
+                    let mut label = each_step_size.borrow().user_label.0.clone();
                     if ui
                         .add(TextEdit::singleline(&mut label).desired_width(20.))
                         .changed()
@@ -105,11 +194,172 @@ fn show_step_size_table(ui: &mut Ui, world: &World) -> Operation {
     operation
 }
 
-fn is_deletion_allowed(step_size_idx: entity_store::Index<StepSize>, world: &World) -> bool {
-    !world.canvases().any(|canvas| {
-        canvas
-            .borrow()
-            .integrations()
-            .any(|integration| step_size_idx == integration.borrow().step_size_idx())
+fn show_group_table(ui: &mut Ui, world: &World) -> Operation {
+    let mut operation = Operation::Noop;
+
+    egui::Grid::new("step size group grid")
+        .striped(false)
+        .show(ui, |ui| {
+            // table header:
+            ui.label("");
+            ui.label("Master dt");
+            ui.label("Linked Step Sizes");
+            ui.end_row();
+
+            // table body:
+            world
+                .step_size_groups()
+                .enumerate()
+                .for_each(|(group_idx, group)| {
+                    // button '-':
+                    if ui
+                        .small_button(constants::BUTTON_GLYPH_DELETE)
+                        .on_hover_text("Unlink the step sizes (they are kept)")
+                        .clicked()
+                    {
+                        operation = Operation::DeleteGroup(group_idx);
+                    }
+                    // edit master dt:
+                    let mut master_dt = group.borrow().master_dt.into();
+                    if ui
+                        .add(Slider::new(&mut master_dt, 0.01..=2.).logarithmic(true))
+                        .changed()
+                    {
+                        operation = Operation::SetGroupDt(group_idx, master_dt.into());
+                    }
+                    ui.label(format!("{}", group.borrow()));
+                    ui.end_row();
+                });
+        });
+    operation
+}
+
+/// Returns the parameters of the series to be generated, once the user has confirmed them.
+fn show_series_generator(ui: &mut Ui) -> Option<SeriesGenerator> {
+    let id = ui.make_persistent_id("step size series generator");
+    let mut generator = *ui
+        .memory()
+        .id_data_temp
+        .get_or_default::<SeriesGenerator>(id);
+    let mut generate = false;
+    ui.horizontal(|ui| {
+        ui.label("Series:");
+        ui.add(
+            DragValue::new(&mut generator.count)
+                .clamp_range(1..=10)
+                .suffix(" ×"),
+        );
+        ui.label("dt");
+        ui.add(
+            DragValue::new(&mut generator.first_dt)
+                .speed(0.01)
+                .clamp_range(MIN_STEP_DURATION..=MAX_STEP_DURATION),
+        );
+        ui.label("ratio");
+        ui.add(
+            DragValue::new(&mut generator.ratio)
+                .speed(0.01)
+                .clamp_range(0.05..=0.95),
+        );
+        generate = ui
+            .button("Generate")
+            .on_hover_text(
+                "Add linked step sizes dt, dt·ratio, dt·ratio², … (as far as they are at least \
+                 0.01)",
+            )
+            .clicked();
+    });
+    ui.memory().id_data_temp.insert(id, generator);
+    if generate {
+        Some(generator)
+    } else {
+        None
+    }
+}
+
+/// Adds new step sizes for the `generator.ratios()`, and links them in a new group.
+fn generate_series(
+    world: &mut World,
+    generator: SeriesGenerator,
+) -> Result<entity_store::Index<StepSizeGroup>, String> {
+    let first_color_idx = world.step_sizes().len();
+    let members = generator
+        .ratios()
+        .into_iter()
+        .enumerate()
+        .map(|(i, ratio)| {
+            let step_size = world.add_step_size(StepSize {
+                user_label: UserLabel(StepSizeGroup::format_ratio(ratio)),
+                duration: (generator.first_dt() * ratio).into(),
+                color: automatic_color(first_color_idx + i),
+            });
+            GroupMember { step_size, ratio }
+        })
+        .collect();
+    world.add_step_size_group(StepSizeGroup {
+        user_label: UserLabel("".into()),
+        master_dt: generator.first_dt().into(),
+        members,
     })
 }
+
+/// Distinct colors for consecutive `n`, by stepping the hue by the golden ratio.
+#[allow(clippy::cast_precision_loss)]
+fn automatic_color(n: usize) -> Color32 {
+    let hue = (n as f32 * 0.618_034).fract();
+    Hsva::new(hue, 0.8, 1., 1.).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_series, SeriesGenerator, MAX_STEP_DURATION, MIN_STEP_DURATION};
+    use crate::World;
+
+    fn durations(world: &World) -> Vec<f32> {
+        world
+            .step_sizes()
+            .iter()
+            .map(|step_size| step_size.borrow().duration.into())
+            .collect()
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // the ratios are powers of two
+    fn series() {
+        let mut world = World::default();
+        let generator = SeriesGenerator {
+            count: 3,
+            first_dt: 0.4,
+            ratio: 0.5,
+        };
+        let group_idx = generate_series(&mut world, generator).unwrap();
+        assert_eq!(durations(&world), vec![0.4, 0.2, 0.1]);
+        assert_eq!(world[group_idx].borrow().members.len(), 3);
+        assert_eq!(f32::from(world[group_idx].borrow().master_dt), 0.4);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // the ratios are powers of two
+    fn series_stays_within_limits() {
+        let mut world = World::default();
+        let generator = SeriesGenerator {
+            count: 10,
+            first_dt: 0.5,
+            ratio: 0.5,
+        };
+        assert_eq!(generator.ratios().len(), 6);
+        let group_idx = generate_series(&mut world, generator).unwrap();
+        assert_eq!(f32::from(world[group_idx].borrow().master_dt), 0.5);
+        assert!(durations(&world)
+            .iter()
+            .all(|&dt| (MIN_STEP_DURATION..=MAX_STEP_DURATION).contains(&dt)));
+
+        let too_long = SeriesGenerator {
+            count: 2,
+            first_dt: 5.,
+            ratio: 0.5,
+        };
+        generate_series(&mut world, too_long).unwrap();
+        assert_eq!(&durations(&world)[6..], &[2., 1.]);
+    }
+}
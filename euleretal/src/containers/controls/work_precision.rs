@@ -0,0 +1,159 @@
+use super::{
+    core::Scenario,
+    entities::Integrator,
+    misc::{divergence_badge, entity_store},
+    ui_import::{
+        egui::{
+            self,
+            plot::{Legend, Line, MarkerShape, Plot, Points, Value, Values},
+        },
+        Ui,
+    },
+    World,
+};
+
+/// The results of all integrations of one integrator in one scenario.
+struct Series {
+    scenario_idx: entity_store::Index<Scenario>,
+    integrator_idx: entity_store::Index<Integrator>,
+    /// `(evaluations, final error)`, sorted by evaluations
+    points: Vec<(usize, f32)>,
+}
+
+pub fn show(ui: &mut Ui, world: &mut World) {
+    world.edit_settings(|settings| {
+        ui.checkbox(&mut settings.equal_cost, "Equal cost")
+            .on_hover_text(
+                "Scale the step size of each integration by the samples per step of its integrator",
+            );
+    });
+    show_cost_table(ui, world);
+
+    let series = collect_series(world);
+    for (scenario_idx, scenario) in world.scenarios().enumerate() {
+        if !series.iter().any(|each| each.scenario_idx == scenario_idx) {
+            continue;
+        }
+        ui.label(format!(
+            "{}: log₁₀ error vs. log₁₀ evaluations",
+            scenario.borrow().label()
+        ));
+        let mut plot = Plot::new(("work precision", scenario_idx))
+            .height(160.)
+            .legend(Legend::default());
+        for each in series
+            .iter()
+            .filter(|each| each.scenario_idx == scenario_idx)
+        {
+            let integrator = world[each.integrator_idx].borrow();
+            #[allow(clippy::cast_precision_loss)]
+            let values = || {
+                Values::from_values_iter(each.points.iter().filter(|(_, error)| *error > 0.).map(
+                    |&(evaluations, error)| Value::new((evaluations as f64).log10(), error.log10()),
+                ))
+            };
+            let label = integrator.core.label();
+            plot = plot
+                .line(
+                    Line::new(values())
+                        .color(integrator.stroke.color)
+                        .name(&label),
+                )
+                .points(
+                    Points::new(values())
+                        .shape(MarkerShape::Circle)
+                        .radius(2_f32)
+                        .color(integrator.stroke.color)
+                        .name(&label),
+                );
+        }
+        ui.add(plot);
+    }
+}
+
+fn collect_series(world: &World) -> Vec<Series> {
+    let mut result: Vec<Series> = Vec::new();
+    for canvas in world.canvases() {
+        let canvas = canvas.borrow();
+        let scenario_idx = canvas.scenario_idx();
+        for integration in canvas.integrations() {
+            let integration = integration.borrow();
+            let integrator_idx = integration.integrator_idx();
+            if integration.divergence().is_some() {
+                // the error of the shorter integration is not comparable
+                continue;
+            }
+            if let (Some(cost), Some(error)) = (integration.cost(), integration.final_error()) {
+                let point = (cost.evaluations, error);
+                match result.iter_mut().find(|each| {
+                    each.scenario_idx == scenario_idx && each.integrator_idx == integrator_idx
+                }) {
+                    Some(series) => series.points.push(point),
+                    None => result.push(Series {
+                        scenario_idx,
+                        integrator_idx,
+                        points: vec![point],
+                    }),
+                }
+            }
+        }
+    }
+    for series in &mut result {
+        series.points.sort_by_key(|&(evaluations, _)| evaluations);
+    }
+    result
+}
+
+fn show_cost_table(ui: &mut Ui, world: &World) {
+    egui::Grid::new("cost grid").striped(true).show(ui, |ui| {
+        ui.label("Integrator");
+        ui.label("dt");
+        ui.label("Samples");
+        ui.label("Time");
+        ui.label("Error");
+        ui.label("Precession")
+            .on_hover_text("Error of the perihelion advance per orbit, in degrees");
+        ui.label("Period Error")
+            .on_hover_text("Error of the orbital period, relative to the reference");
+        ui.end_row();
+
+        let precision = world.settings().format_precision;
+        for canvas in world.canvases() {
+            for integration in canvas.borrow().integrations() {
+                let integration = integration.borrow();
+                if let Some(cost) = integration.cost() {
+                    ui.label(world[integration.integrator_idx()].borrow().core.label());
+                    ui.label(format!(
+                        "{:.*}",
+                        precision,
+                        integration.fetch_step_duration(world)
+                    ));
+                    ui.label(cost.evaluations.to_string());
+                    ui.label(cost.duration.map_or_else(
+                        || "–".to_string(),
+                        |duration| format!("{}µs", duration.as_micros()),
+                    ));
+                    ui.horizontal(|ui| {
+                        ui.label(integration.final_error().map_or_else(
+                            || "–".to_string(),
+                            |error| format!("{:.*e}", precision, error),
+                        ));
+                        divergence_badge(ui, integration.labelled_divergence(world), precision);
+                    });
+                    if let Some((_, errors)) = integration.orbit_metrics() {
+                        ui.label(format!(
+                            "{:+.*e}°",
+                            precision,
+                            errors.precession.to_degrees()
+                        ));
+                        ui.label(format!("{:+.*e}", precision, errors.period));
+                    } else {
+                        ui.label("–");
+                        ui.label("–");
+                    }
+                    ui.end_row();
+                }
+            }
+        }
+    });
+}
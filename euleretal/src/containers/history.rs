@@ -0,0 +1,23 @@
+use super::{
+    ui_import::{egui::Button, Ui},
+    History, World,
+};
+
+pub fn show(ui: &mut Ui, history: &mut History, world: &mut World) {
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(history.can_undo(), Button::new("Undo"))
+            .on_hover_text("Ctrl+Z")
+            .clicked()
+        {
+            history.undo(world);
+        }
+        if ui
+            .add_enabled(history.can_redo(), Button::new("Redo"))
+            .on_hover_text("Ctrl+Shift+Z")
+            .clicked()
+        {
+            history.redo(world);
+        }
+    });
+}
@@ -1,6 +1,7 @@
 pub mod canvas;
 mod constants;
 pub mod controls;
+pub mod history;
 pub mod settings;
 
-use super::{core, entities, import, misc, ui_import, World};
+use super::{command, core, entities, import, misc, ui_import, History, World};
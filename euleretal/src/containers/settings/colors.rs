@@ -10,5 +10,12 @@ pub fn show(ui: &mut Ui, strokes: &mut Strokes) {
         my_stroke_ui(ui, &mut strokes.coordinates, "Coordinates", "");
         my_stroke_ui(ui, &mut strokes.focussed_acceleration, "Acceleration", "");
         my_stroke_ui(ui, &mut strokes.focussed_velocity, "Velocity", "");
+        my_stroke_ui(ui, &mut strokes.obstacle, "Obstacles", "");
+        my_stroke_ui(
+            ui,
+            &mut strokes.divergence,
+            "Divergences",
+            "Where integrations stopped because they diverged",
+        );
     });
 }
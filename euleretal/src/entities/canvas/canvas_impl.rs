@@ -1,12 +1,16 @@
 use super::{
-    core::{Duration, Scenario},
+    core::{Duration, ReferenceCache, Scenario},
     import::{Point3, Vec3},
     misc::{entity_store, BoundingBox},
     trajectory_buffer::TrajectoryBuffer,
     ui_import::{egui, Pos2, Ui, Vec2},
-    Integration, Painter, World,
+    Integration, Integrator, Painter, StepSize, World,
+};
+use ::std::{
+    cell::RefCell,
+    hash::{Hash, Hasher},
+    sync::Arc,
 };
-use ::std::cell::RefCell;
 
 #[derive(::serde::Deserialize, ::serde::Serialize)]
 pub struct Canvas {
@@ -20,6 +24,9 @@ pub struct Canvas {
     pub ui_integrations_window_is_open: bool,
     #[serde(skip)]
     pub(super) trajectory_buffer: Option<TrajectoryBuffer>, // todo: should not be public (or explained)
+    /// The viewport is adjusted as soon as the trajectory of a new scenario is available.
+    #[serde(skip)]
+    bbox_is_pending: bool,
 }
 
 impl ::std::fmt::Debug for Canvas {
@@ -62,6 +69,7 @@ impl Canvas {
             scale: Vec3::default(),
             area_center: Pos2::default(),
             trajectory_buffer: None,
+            bbox_is_pending: false,
             ui_integrations_window_is_open: false,
         }
     }
@@ -84,23 +92,61 @@ impl Canvas {
         self.integrations.push(RefCell::new(integration));
     }
 
-    #[allow(clippy::needless_pass_by_value)]
-    pub fn remove_integration(&mut self, integration_idx: usize) {
-        self.integrations.remove(integration_idx);
+    pub fn insert_integration(&mut self, integration_idx: usize, integration: Integration) {
+        self.integrations
+            .insert(integration_idx, RefCell::new(integration));
+    }
+
+    pub fn remove_integration(&mut self, integration_idx: usize) -> Integration {
+        self.integrations.remove(integration_idx).into_inner()
     }
 
     pub fn integration_at(&self, integration_idx: usize) -> &RefCell<Integration> {
         &self.integrations[integration_idx]
     }
 
-    pub fn update_trajectory(&mut self, scenario: &Scenario, min_dt: Duration) {
+    /// returns `true` if a new trajectory is available
+    pub fn update_trajectory(
+        &mut self,
+        scenario: &Scenario,
+        min_dt: Duration,
+        references: &Arc<ReferenceCache>,
+    ) -> bool {
         if let Some(ref mut buffer) = self.trajectory_buffer {
-            buffer.update_trajectory(scenario, min_dt);
+            buffer.update_trajectory(scenario, min_dt, references)
         } else {
-            self.trajectory_buffer = Some(TrajectoryBuffer::new(scenario, min_dt));
+            let buffer = TrajectoryBuffer::new(scenario, min_dt, references);
+            let is_available = !buffer.is_stale();
+            self.trajectory_buffer = Some(buffer);
+            is_available
         }
     }
 
+    /// `true` while the trajectory or any integration is being calculated
+    pub fn is_busy(&self) -> bool {
+        self.trajectory_buffer
+            .as_ref()
+            .is_some_and(TrajectoryBuffer::is_stale)
+            || self
+                .integrations
+                .iter()
+                .any(|integration| integration.borrow().is_stale())
+    }
+
+    /// The progress of the least advanced calculation, between 0 and 1.
+    pub fn progress(&self) -> Option<f32> {
+        self.trajectory_buffer
+            .as_ref()
+            .and_then(TrajectoryBuffer::progress)
+            .into_iter()
+            .chain(
+                self.integrations
+                    .iter()
+                    .filter_map(|integration| integration.borrow().progress()),
+            )
+            .reduce(f32::min)
+    }
+
     pub fn scenario_is_new_once(&mut self) -> bool {
         let result = self.scenario_is_new;
         self.scenario_is_new = false;
@@ -109,10 +155,10 @@ impl Canvas {
 
     pub fn bbox(&self) -> Option<BoundingBox> {
         self.trajectory_buffer.as_ref().and_then(|buf| {
-            let mut positions = buf.iter();
+            let mut positions = buf.positions();
             positions.next().map(|first_position| {
-                let mut bbox = BoundingBox::new_at(*first_position);
-                positions.for_each(|position| bbox.expand_to(*position));
+                let mut bbox = BoundingBox::new_at(first_position);
+                positions.for_each(|position| bbox.expand_to(position));
                 bbox
             })
         })
@@ -142,6 +188,18 @@ impl Canvas {
         self.area_center = paint_area.center();
     }
 
+    /// Hash over everything `user_to_screen()` depends on
+    pub fn hash_viewport(&self, state: &mut impl Hasher) {
+        for coordinate in self
+            .focus
+            .iter()
+            .chain(self.scale.iter())
+            .chain([self.area_center.x, self.area_center.y].iter())
+        {
+            coordinate.to_bits().hash(state);
+        }
+    }
+
     pub fn user_to_screen(&self, pos: impl Into<Point3>) -> Pos2 {
         (pos.into() - self.focus)
             .component_mul(&self.scale)
@@ -171,15 +229,18 @@ impl Canvas {
 
         let scenario_is_new = self.scenario_is_new_once(); //todo: method not needed
         let scenario = world.scenarios()[self.scenario_idx()].borrow();
-        self.update_trajectory(&scenario, min_dt);
+        if scenario_is_new {
+            self.bbox_is_pending = true;
+        }
+        let trajectory_is_new = self.update_trajectory(&scenario, min_dt, world.references());
         self.integrations.iter().for_each(|integration| {
             let mut integration = integration.borrow_mut();
             if scenario_is_new {
                 integration.reset();
             }
             let integrator = &*world[integration.integrator_idx()].borrow().core;
-            let step_duration = world[integration.step_size_idx()].borrow().duration;
-            updated |= integration.update(&scenario, integrator, step_duration);
+            let step_duration = integration.fetch_step_duration(world);
+            updated |= integration.update(&scenario, integrator, step_duration, world.references());
         });
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -190,8 +251,9 @@ impl Canvas {
             );
         }
 
-        if scenario_is_new {
+        if self.bbox_is_pending && trajectory_is_new {
             log::debug!("updating bounding box");
+            self.bbox_is_pending = false;
             self.update_bounding_box();
         }
     }
@@ -218,6 +280,24 @@ impl Canvas {
     }
 }
 
+impl entity_store::Referrer<Integrator> for Canvas {
+    fn count_references(&self, idx: entity_store::Index<Integrator>) -> usize {
+        self.integrations
+            .iter()
+            .filter(|integration| integration.borrow().integrator_idx() == idx)
+            .count()
+    }
+}
+
+impl entity_store::Referrer<StepSize> for Canvas {
+    fn count_references(&self, idx: entity_store::Index<StepSize>) -> usize {
+        self.integrations
+            .iter()
+            .filter(|integration| integration.borrow().step_size_idx() == idx)
+            .count()
+    }
+}
+
 trait ToPos2 {
     fn to_pos2(&self) -> Pos2;
 }
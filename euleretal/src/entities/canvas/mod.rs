@@ -3,5 +3,7 @@ mod painter;
 mod trajectory_buffer;
 
 pub use self::painter::Painter;
-use super::{core, import, misc, ui_import, Integration, World};
+use super::{
+    core, import, misc, ui_import, Integration, Integrator, StepSize, World, STALE_OPACITY,
+};
 pub use canvas_impl::{Canvas, ObjExtras};
@@ -1,11 +1,18 @@
 use super::{
     core::{Position, Samples, Scenario},
     import::{Point3, Vec3},
-    misc::{entity_store, PointFormat},
+    misc::{entity_store, PointFormat, Polyline},
     ui_import::{egui, Color32, Pos2, Vec2},
-    Canvas, Integration,
+    Canvas, Integration, STALE_OPACITY,
 };
-use ::std::cell::{Ref, RefCell, RefMut};
+use ::std::{
+    cell::{Ref, RefCell, RefMut},
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// Maximum distance (in screen dimensions) of the pointer from a handle to grab it.
+const HANDLE_GRAB_RADIUS: f32 = 8.;
 
 pub struct Painter<'c> {
     canvas: RefMut<'c, Canvas>,
@@ -128,6 +135,18 @@ impl<'c> Painter<'c> {
         );
     }
 
+    /// A circle in the x/y plane
+    pub fn draw_circle(&self, center: impl Into<Point3>, radius: f32, stroke: egui::Stroke) {
+        let center = center.into();
+        let screen_center = self.canvas.user_to_screen(center);
+        let screen_radius = screen_center.distance(
+            self.canvas
+                .user_to_screen(center + Vec3::new(radius, 0., 0.)),
+        );
+        self.painter
+            .circle_stroke(screen_center, screen_radius, stroke);
+    }
+
     pub fn draw_sample_point(&self, p: impl Into<Point3>, format: &PointFormat) {
         format.draw_position_on(self.canvas.user_to_screen(p), &self.painter);
     }
@@ -205,39 +224,139 @@ impl<'c> Painter<'c> {
         }
     }
 
-    pub fn draw_trajectory(&self, stroke: egui::Stroke) {
+    /// Lets the user drag the handle named `handle`, which is located at `position`.  Returns the
+    /// pointer position translated to application coordinates while the handle is dragged.
+    ///
+    /// Only one handle can be dragged at a time.  If handles overlap, the one registered first
+    /// (in rendering order) is grabbed.
+    pub fn drag_handle(&self, handle: &'static str, position: impl Into<Point3>) -> Option<Point3> {
+        if self.response.drag_started() && self.dragged_handle().is_none() {
+            let handle_pos = self.canvas.user_to_screen(position);
+            if let Some(press_origin) = self.input().pointer.press_origin() {
+                if press_origin.distance(handle_pos) <= HANDLE_GRAB_RADIUS {
+                    self.response
+                        .ctx
+                        .memory()
+                        .id_data_temp
+                        .insert(self.dragged_handle_id(), handle);
+                }
+            }
+        }
+        if self.dragged_handle() == Some(handle) {
+            // keep on recomputing, even if the pointer does not move:
+            self.response.ctx.request_repaint();
+            self.response
+                .interact_pointer_pos()
+                .map(|pointer_pos| self.canvas.screen_to_user(pointer_pos))
+        } else {
+            None
+        }
+    }
+
+    pub fn is_dragging_handle(&self) -> bool {
+        self.dragged_handle().is_some()
+    }
+
+    fn dragged_handle(&self) -> Option<&'static str> {
+        let id = self.dragged_handle_id();
+        let mut memory = self.response.ctx.memory();
+        if self.response.dragged() {
+            memory.id_data_temp.get::<&'static str>(&id).copied()
+        } else {
+            memory.id_data_temp.remove(&id);
+            None
+        }
+    }
+
+    fn dragged_handle_id(&self) -> egui::Id {
+        self.response.id.with("dragged handle")
+    }
+
+    /// Draws the trajectories of all bodies.  A stale trajectory is drawn faded.
+    pub fn draw_trajectory(&self, mut stroke: egui::Stroke) {
         if let Some(ref buffer) = &self.canvas.trajectory_buffer {
-            self.draw_connected_samples(buffer.iter().copied(), stroke);
+            if buffer.is_stale() {
+                stroke.color = stroke.color.linear_multiply(STALE_OPACITY);
+            }
+            let mut polylines = buffer.polylines().borrow_mut();
+            polylines.resize_with(buffer.num_bodies(), Polyline::default);
+            for (body, polyline) in polylines.iter_mut().enumerate() {
+                self.draw_polyline(
+                    polyline,
+                    buffer.positions_key(body),
+                    || buffer.body_positions(body),
+                    stroke,
+                );
+            }
+        }
+    }
+
+    /// Shows the progress of pending calculations in the top left corner, and keeps repainting
+    /// until they are finished.
+    pub fn draw_progress(&self) {
+        if let Some(progress) = self.canvas.progress() {
+            let style = self.response.ctx.style();
+            self.painter.text(
+                self.response.rect.left_top() + Vec2::splat(style.spacing.item_spacing.x),
+                egui::Align2::LEFT_TOP,
+                format!("Calculating… {:.0}%", progress * 100.),
+                egui::TextStyle::Small,
+                style.visuals.weak_text_color(),
+            );
+        }
+        if self.canvas.is_busy() {
+            self.response.ctx.request_repaint();
         }
     }
 
-    pub fn draw_sample_trajectory(&self, samples: &Samples, stroke: egui::Stroke) {
+    /// `polyline` caches the simplified trajectory for the current viewport, as long as
+    /// `sample_validity` (see `Integration::sample_validity()`) does not change.
+    pub fn draw_sample_trajectory(
+        &self,
+        samples: &Samples,
+        sample_validity: u64,
+        polyline: &mut Polyline,
+        stroke: egui::Stroke,
+    ) {
         if !samples.is_empty() {
-            let start_position = samples.at(0).positions_iter().next();
-            self.draw_connected_samples(
-                start_position.into_iter().chain(samples.step_positions()),
+            self.draw_polyline(
+                polyline,
+                sample_validity,
+                || {
+                    let start_position = samples.at(0).positions_iter().next();
+                    start_position.into_iter().chain(samples.step_positions())
+                },
                 stroke,
             );
         }
     }
 
-    fn draw_connected_samples(
+    /// Draws the connected `positions`, which are only fetched (and simplified) if `positions_key`
+    /// or the viewport has changed since `polyline` has been drawn the last time.
+    fn draw_polyline<I>(
         &self,
-        positions: impl Iterator<Item = Position>,
+        polyline: &mut Polyline,
+        positions_key: u64,
+        positions: impl FnOnce() -> I,
         stroke: egui::Stroke,
-    ) {
+    ) where
+        I: Iterator<Item = Position>,
+    {
         let canvas = &self.canvas;
-        positions
-            .map(|p| canvas.user_to_screen(p))
-            .reduce(|u0, u1| {
-                // avoid drawing extremely short line segments:
-                if (u0.x - u1.x).abs() > 2. || (u0.y - u1.y).abs() > 2. {
-                    self.painter.line_segment([u0, u1], stroke);
-                    u1
-                } else {
-                    u0
-                }
-            });
+        let rect = self.response.rect;
+        let mut hasher = DefaultHasher::new();
+        positions_key.hash(&mut hasher);
+        canvas.hash_viewport(&mut hasher);
+        for coordinate in [rect.min.x, rect.min.y, rect.max.x, rect.max.y] {
+            coordinate.to_bits().hash(&mut hasher);
+        }
+        polyline.draw(
+            hasher.finish(),
+            || positions().map(|p| canvas.user_to_screen(p)),
+            rect,
+            stroke,
+            &self.painter,
+        );
     }
 }
 
@@ -1,28 +1,77 @@
-use super::core::{Duration, Position, Scenario};
-use ::std::{collections::hash_map::DefaultHasher, hash::Hasher};
+use super::core::{
+    job::{Job, Status},
+    Duration, Position, ReferenceCache, Scenario, Trajectory,
+};
+use crate::misc::Polyline;
+use ::std::{
+    cell::RefCell,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
 
+/// The trajectory itself is shared with other canvases via the `ReferenceCache`.  Since it may
+/// be longer than the scenario, only the part up to `duration` is drawn.  That is the end of the
+/// scenario, or the first occurrence of its terminal event.
 #[derive(Default)]
 pub struct TrajectoryBuffer {
-    trajectory: Vec<Position>,
+    trajectory: Arc<Trajectory>,
+    /// Incremented whenever a trajectory is received
+    generation: u64,
     scenario_hash: u64,
     trajectory_min_dt: Duration,
+    duration: Duration,
+    /// The calculation for the most recent inputs
+    pending: Option<Pending>,
+    /// one per body
+    polylines: RefCell<Vec<Polyline>>,
 }
 
-impl ::std::ops::Deref for TrajectoryBuffer {
-    type Target = Vec<Position>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.trajectory
-    }
+struct Pending {
+    job: Job<(Arc<Trajectory>, Duration)>,
+    scenario_hash: u64,
+    min_dt: Duration,
 }
 
 impl TrajectoryBuffer {
-    pub fn new(scenario: &Scenario, min_dt: Duration) -> Self {
-        Self {
-            trajectory: scenario.calculate_trajectory(min_dt),
-            trajectory_min_dt: min_dt,
-            scenario_hash: Self::hash_scenario(scenario),
-        }
+    pub fn new(scenario: &Scenario, min_dt: Duration, references: &Arc<ReferenceCache>) -> Self {
+        let mut buffer = Self::default();
+        buffer.update_trajectory(scenario, min_dt, references);
+        buffer
+    }
+
+    /// The positions of all bodies
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        self.trajectory
+            .dense_outputs()
+            .flat_map(|dense_output| dense_output.node_positions(self.duration))
+    }
+
+    /// The particle and the further bodies of the scenario
+    pub fn num_bodies(&self) -> usize {
+        1 + self.trajectory.bodies.len()
+    }
+
+    /// The positions of the particle (`body` 0) or a further body
+    pub fn body_positions(&self, body: usize) -> impl Iterator<Item = Position> + '_ {
+        self.trajectory
+            .body(body)
+            .into_iter()
+            .flat_map(|dense_output| dense_output.node_positions(self.duration))
+    }
+
+    /// Changes whenever `body_positions()` of the `body` changes.
+    pub fn positions_key(&self, body: usize) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.generation.hash(&mut hasher);
+        self.duration.hash(&mut hasher);
+        body.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The simplified trajectories of the bodies, as drawn for the current viewport
+    pub fn polylines(&self) -> &RefCell<Vec<Polyline>> {
+        &self.polylines
     }
 
     pub fn hash_scenario(scenario: &Scenario) -> u64 {
@@ -31,12 +80,89 @@ impl TrajectoryBuffer {
         hasher.finish()
     }
 
-    pub fn update_trajectory(&mut self, scenario: &Scenario, min_dt: Duration) {
+    /// Starts a re-calculation in the background if necessary.  The previous trajectory remains
+    /// available until the new one has been calculated.
+    ///
+    /// returns `true` if a new trajectory has been received
+    pub fn update_trajectory(
+        &mut self,
+        scenario: &Scenario,
+        min_dt: Duration,
+        references: &Arc<ReferenceCache>,
+    ) -> bool {
         let scenario_hash = Self::hash_scenario(scenario);
-        if self.scenario_hash != scenario_hash || self.trajectory_min_dt > min_dt {
-            self.trajectory = scenario.calculate_trajectory(min_dt);
-            self.trajectory_min_dt = min_dt;
-            self.scenario_hash = scenario_hash;
+        let (requested_hash, requested_min_dt) = self
+            .pending
+            .as_ref()
+            .map_or((self.scenario_hash, self.trajectory_min_dt), |pending| {
+                (pending.scenario_hash, pending.min_dt)
+            });
+        if requested_hash != scenario_hash || requested_min_dt > min_dt {
+            let scenario = scenario.clone();
+            let references = Arc::clone(references);
+            let mut hasher = DefaultHasher::new();
+            scenario.hash_without_duration(&mut hasher);
+            let cache_key = hasher.finish();
+            let job = Job::spawn(
+                &format!("trajectory {}", scenario.label()),
+                move |progress| {
+                    let trajectory =
+                        references.trajectory(&scenario, cache_key, min_dt, progress)?;
+                    let duration = match scenario.event {
+                        Some(event) if event.terminal => event
+                            .function
+                            .first_occurrence(&trajectory.dense_output, scenario.duration)
+                            .map_or(scenario.duration, |occurrence| occurrence.t),
+                        _ => scenario.duration,
+                    };
+                    Some((trajectory, duration))
+                },
+            );
+            // replacing the pending job cancels it
+            self.pending = Some(Pending {
+                job,
+                scenario_hash,
+                min_dt,
+            });
+        }
+        self.receive()
+    }
+
+    /// `true` while the trajectory does not belong to the most recent inputs
+    pub fn is_stale(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn progress(&self) -> Option<f32> {
+        self.pending.as_ref().map(|pending| pending.job.progress())
+    }
+
+    fn receive(&mut self) -> bool {
+        let status = match &self.pending {
+            Some(pending) => pending.job.poll(),
+            None => return false,
+        };
+        match status {
+            Status::Running => false,
+            Status::Done((trajectory, duration)) => {
+                if let Some(pending) = self.pending.take() {
+                    self.trajectory = trajectory;
+                    self.generation += 1;
+                    self.scenario_hash = pending.scenario_hash;
+                    self.trajectory_min_dt = pending.min_dt;
+                    self.duration = duration;
+                }
+                true
+            }
+            Status::Failed => {
+                log::error!("Trajectory calculation failed");
+                // keep the previous trajectory, but do not retry with the same inputs
+                if let Some(pending) = self.pending.take() {
+                    self.scenario_hash = pending.scenario_hash;
+                    self.trajectory_min_dt = pending.min_dt;
+                }
+                false
+            }
         }
     }
 }
@@ -1,9 +1,19 @@
 use super::{
-    core::{self, Duration, Position, Scenario, Step},
-    misc::BoundingBox,
-    Integrator, StepSize, World,
+    core::{
+        self,
+        dense_output::State,
+        divergence::Diverged,
+        event::Occurrence,
+        obstacle::Collision,
+        orbit::{OrbitErrors, OrbitMetrics},
+        Duration, Fraction, Position, Recording, ReferenceCache, Scenario, Step,
+    },
+    misc::{BoundingBox, Polyline},
+    ui_import::Color32,
+    Integrator, StepSize, World, STALE_OPACITY,
 };
 use crate::misc::entity_store;
+use ::std::{cell::RefCell, sync::Arc};
 
 #[derive(::serde::Deserialize, ::serde::Serialize)]
 pub struct Integration {
@@ -14,6 +24,12 @@ pub struct Integration {
     #[serde(rename = "step_size")]
     step_size_idx: entity_store::Index<StepSize>,
     current_sample_index: Option<usize>,
+    /// The body whose step at `current_sample_index` has been picked, 0 being the particle
+    #[serde(default)]
+    focussed_body: usize,
+    /// one per body
+    #[serde(skip)]
+    polylines: RefCell<Vec<Polyline>>,
 }
 
 impl ::std::fmt::Debug for Integration {
@@ -23,6 +39,7 @@ impl ::std::fmt::Debug for Integration {
             .field("integrator", &self.integrator_idx)
             .field("step_size", &self.step_size_idx)
             .field("current_sample_index", &self.current_sample_index)
+            .field("focussed_body", &self.focussed_body)
             .finish()
     }
 }
@@ -39,15 +56,22 @@ impl Integration {
         step_size: entity_store::Index<StepSize>,
     ) -> Self {
         Self {
-            core: self::core::Integration::new(),
+            core: Self::new_core(),
             integrator_idx: integrator,
             step_size_idx: step_size,
             current_sample_index: None,
+            focussed_body: 0,
+            polylines: RefCell::default(),
         }
     }
 
     pub fn reset(&mut self) {
-        self.core = self::core::Integration::new();
+        self.core = Self::new_core();
+    }
+
+    /// Only the focussed samples get inspected, see `focussed_samples()`.
+    fn new_core() -> self::core::Integration {
+        self::core::Integration::with_recording(Recording::EndStates)
     }
 
     pub fn integrator_idx(&self) -> entity_store::Index<Integrator> {
@@ -68,35 +92,139 @@ impl Integration {
         self.reset();
     }
 
+    /// The step size, scaled by the samples per step of the integrator in equal cost mode.
     pub fn fetch_step_duration(&self, world: &World) -> Duration {
-        world[self.step_size_idx].borrow().duration
+        let duration = world[self.step_size_idx].borrow().duration;
+        if world.settings().equal_cost {
+            let samples_per_step = world[self.integrator_idx]
+                .borrow()
+                .core
+                .metadata()
+                .samples_per_step;
+            #[allow(clippy::cast_precision_loss)]
+            let scale = samples_per_step as f32;
+            scale * duration
+        } else {
+            duration
+        }
+    }
+
+    pub fn cost(&self) -> Option<core::Cost> {
+        self.core.cost()
+    }
+
+    pub fn final_error(&self) -> Option<f32> {
+        self.core.final_error()
+    }
+
+    pub fn orbit_metrics(&self) -> Option<(OrbitMetrics, OrbitErrors)> {
+        self.core.orbit_metrics()
+    }
+
+    pub fn divergence(&self) -> Option<Diverged> {
+        self.core.divergence()
+    }
+
+    /// The divergence together with the label of the integrator, e.g. for `divergence_badge()`
+    pub fn labelled_divergence(&self, world: &World) -> Option<(String, Diverged)> {
+        let label = world[self.integrator_idx].borrow().core.label();
+        self.divergence().map(|diverged| (label, diverged))
+    }
+
+    pub fn detected_event(&self) -> Option<Occurrence> {
+        self.core.detected_event()
+    }
+
+    pub fn reference_event(&self) -> Option<Occurrence> {
+        self.core.reference_event()
+    }
+
+    pub fn collisions(&self) -> impl Iterator<Item = Collision> + '_ {
+        self.core.collisions()
+    }
+
+    pub fn reference_collisions(&self) -> impl Iterator<Item = Collision> + '_ {
+        self.core.reference_collisions()
+    }
+
+    /// Where the focussed step of the particle bounced off an obstacle, and where the reference
+    /// solution bounced off obstacles during the focussed step
+    pub fn focussed_collisions(&self) -> Option<(Collision, Vec<Collision>)> {
+        #![allow(clippy::cast_precision_loss)]
+        let idx = self.current_sample_index?;
+        let collision = self.core.collision_in_step(idx)?;
+        let dt = self.core.samples()?.get(idx)?.dt();
+        let (start, end) = (dt * idx as f32, dt * (idx + 1) as f32);
+        Some((
+            collision,
+            self.reference_collisions()
+                .filter(|reference| start <= reference.t && reference.t <= end)
+                .collect(),
+        ))
     }
 
     pub fn stretch_bbox(&self, bbox: &mut BoundingBox) {
         let integration = &self.core;
-        for samples in integration
-            .reference_samples()
-            .iter()
-            .chain(integration.samples().iter())
-        {
-            samples
-                .step_positions()
-                .for_each(|position| bbox.expand_to(position));
+        for body in 0..integration.num_bodies() {
+            for samples in integration
+                .body_reference_samples(body)
+                .iter()
+                .chain(integration.body_samples(body).iter())
+            {
+                samples
+                    .step_positions()
+                    .for_each(|position| bbox.expand_to(position));
+            }
         }
     }
 
+    /// Focusses the step (of any body) with the point closest to `pos`.
     pub fn focus_closest_sample(&mut self, pos: &Position) {
-        self.current_sample_index = self.core.closest_sample_index(pos);
+        let closest = self.core.closest_sample(pos);
+        self.focussed_body = closest.map_or(0, |(body, _)| body);
+        self.current_sample_index = closest.map(|(_, idx)| idx);
+    }
+
+    /// The body whose step has been focussed, 0 being the particle
+    pub fn focussed_body(&self) -> usize {
+        self.focussed_body
+    }
+
+    /// Re-calculates the steps at the focussed index with all their contributions, unless that
+    /// has been done already.
+    pub fn record_focussed_steps(&mut self, scenario: &Scenario, world: &World) {
+        if let Some(idx) = self.current_sample_index {
+            let integrator = world[self.integrator_idx].borrow();
+            self.core
+                .record_steps(idx, &*integrator.core, &scenario.system());
+        }
+    }
+
+    /// returns (body, reference sample, computed sample) of each body at the focussed step, see
+    /// `record_focussed_steps()`.  Bodies without samples at the focussed index (e.g. a focus
+    /// restored from a save file, while the integration is still being calculated) are left out.
+    pub fn focussed_samples(&self) -> Vec<(usize, Step<'_>, Step<'_>)> {
+        let Some(idx) = self.current_sample_index else {
+            return Vec::new();
+        };
+        (0..self.core.num_bodies())
+            .filter_map(|body| {
+                Some((
+                    body,
+                    self.core.body_reference_samples(body)?.get(idx)?,
+                    self.core.recorded_step(body, idx)?,
+                ))
+            })
+            .collect()
     }
 
-    /// returns (ReferenceSample,ComputedSample)
-    pub fn focussed_sample(&self) -> Option<(&Step, &Step)> {
-        self.current_sample_index.map(|idx| {
-            (
-                self.core.reference_samples().unwrap().at(idx), // todo : idx could be invalid (by loading from incompatible save file)
-                self.core.samples().unwrap().at(idx),
-            )
-        })
+    /// The reference solution of the `body` at `dt_fraction` of the focussed step
+    pub fn focussed_reference_at(&self, body: usize, dt_fraction: Fraction) -> Option<State> {
+        #![allow(clippy::cast_precision_loss)]
+        let idx = self.current_sample_index?;
+        let dt = self.core.samples()?.get(idx)?.dt();
+        self.core
+            .body_reference_at(body, dt * (idx as f32 + f32::from(dt_fraction)))
     }
 
     pub fn update(
@@ -104,8 +232,12 @@ impl Integration {
         scenario: &Scenario,
         integrator: &dyn core::Integrator,
         step_duration: Duration,
+        references: &Arc<ReferenceCache>,
     ) -> bool {
-        if self.core.update(scenario, integrator, step_duration) {
+        if self
+            .core
+            .update(scenario, integrator, step_duration, references)
+        {
             self.adjust_focussed_sample();
             true
         } else {
@@ -113,7 +245,19 @@ impl Integration {
         }
     }
 
+    /// `true` while the samples do not belong to the current integrator and step size
+    pub fn is_stale(&self) -> bool {
+        self.core.is_stale()
+    }
+
+    pub fn progress(&self) -> Option<f32> {
+        self.core.progress()
+    }
+
     fn adjust_focussed_sample(&mut self) {
+        if self.focussed_body >= self.core.num_bodies() {
+            self.focussed_body = 0;
+        }
         if let Some(prev_sample_idx) = self.current_sample_index {
             if let Some(samples) = self.core.samples() {
                 let num_samples = samples.len();
@@ -130,22 +274,41 @@ impl Integration {
         }
     }
 
+    /// Stale samples are drawn faded.
     pub fn draw_on(&self, canvas: &super::CanvasPainter, world: &World) {
-        let sample_color = world[self.step_size_idx].borrow().color;
-        if let Some(reference_samples) = self.core.reference_samples() {
-            canvas.draw_sample_dots(
-                reference_samples,
-                sample_color,
-                &world.settings.point_formats.reference_position,
-            );
-        }
-        if let Some(samples) = self.core.samples() {
-            canvas.draw_sample_trajectory(samples, world[self.integrator_idx].borrow().stroke);
-            canvas.draw_sample_dots(
-                samples,
-                sample_color,
-                &world.settings.point_formats.derived_position,
-            );
+        let fade = |color: Color32| {
+            if self.is_stale() {
+                color.linear_multiply(STALE_OPACITY)
+            } else {
+                color
+            }
+        };
+        let sample_color = fade(world[self.step_size_idx].borrow().color);
+        let mut stroke = world[self.integrator_idx].borrow().stroke;
+        stroke.color = fade(stroke.color);
+        let mut polylines = self.polylines.borrow_mut();
+        polylines.resize_with(self.core.num_bodies(), Polyline::default);
+        for (body, polyline) in polylines.iter_mut().enumerate() {
+            if let Some(reference_samples) = self.core.body_reference_samples(body) {
+                canvas.draw_sample_dots(
+                    reference_samples,
+                    sample_color,
+                    &world.settings().point_formats.reference_position,
+                );
+            }
+            if let Some(samples) = self.core.body_samples(body) {
+                canvas.draw_sample_trajectory(
+                    samples,
+                    self.core.sample_validity(),
+                    polyline,
+                    stroke,
+                );
+                canvas.draw_sample_dots(
+                    samples,
+                    sample_color,
+                    &world.settings().point_formats.derived_position,
+                );
+            }
         }
     }
 
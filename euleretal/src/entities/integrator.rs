@@ -6,3 +6,23 @@ pub struct Integrator {
     pub core: Box<dyn crate::core::Integrator>,
     pub stroke: eframe::egui::Stroke,
 }
+
+impl Clone for Integrator {
+    fn clone(&self) -> Self {
+        Self {
+            core: self.core.to_concrete_type().into_box(),
+            stroke: self.stroke,
+        }
+    }
+}
+
+impl Integrator {
+    /// Heading of a group of integrators which need the same number of samples per step.
+    #[must_use]
+    pub fn group_label(samples_per_step: usize) -> String {
+        match samples_per_step {
+            1 => "1 sample per step".to_string(),
+            n => format!("{} samples per step", n),
+        }
+    }
+}
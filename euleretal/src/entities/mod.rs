@@ -2,10 +2,15 @@ mod canvas;
 mod integration;
 mod integrator;
 mod step_size;
+mod step_size_group;
 
 pub use canvas::{Canvas, ObjExtras, Painter as CanvasPainter};
 pub use integration::Integration;
 pub use integrator::Integrator;
 pub use step_size::StepSize;
+pub use step_size_group::{GroupMember, StepSizeGroup};
 
 use super::{core, import, misc, ui_import, World};
+
+/// Results which are being re-calculated are drawn with this opacity.
+const STALE_OPACITY: f32 = 0.3;
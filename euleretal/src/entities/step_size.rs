@@ -1,6 +1,6 @@
 use super::{core::Duration, misc::UserLabel, ui_import::egui};
 
-#[derive(Debug, ::serde::Deserialize, ::serde::Serialize)]
+#[derive(Clone, Debug, ::serde::Deserialize, ::serde::Serialize)]
 pub struct StepSize {
     pub user_label: UserLabel,
     pub duration: Duration,
@@ -0,0 +1,77 @@
+use super::{
+    core::Duration,
+    misc::{entity_store, UserLabel},
+    StepSize,
+};
+
+/// Step sizes which are linked by fixed ratios to a common master step size, so that they can be
+/// scaled together (useful for testing convergence).
+#[derive(Clone, Debug, ::serde::Deserialize, ::serde::Serialize)]
+pub struct StepSizeGroup {
+    pub user_label: UserLabel,
+    pub master_dt: Duration,
+    pub members: Vec<GroupMember>,
+}
+
+#[derive(Clone, Debug, ::serde::Deserialize, ::serde::Serialize)]
+pub struct GroupMember {
+    pub step_size: entity_store::Index<StepSize>,
+    /// `duration` of the member step size, divided by `master_dt`
+    pub ratio: f32,
+}
+
+impl StepSizeGroup {
+    pub fn member(&self, step_size_idx: entity_store::Index<StepSize>) -> Option<&GroupMember> {
+        self.members
+            .iter()
+            .find(|member| member.step_size == step_size_idx)
+    }
+
+    /// The smallest master step size which does not scale any member below `min_dt`.
+    pub fn min_master_dt(&self, min_dt: Duration) -> Duration {
+        self.members
+            .iter()
+            .map(|member| min_dt / member.ratio)
+            .max()
+            .unwrap_or(min_dt)
+    }
+
+    /// The largest master step size which does not scale any member above `max_dt`.
+    pub fn max_master_dt(&self, max_dt: Duration) -> Duration {
+        self.members
+            .iter()
+            .map(|member| max_dt / member.ratio)
+            .min()
+            .unwrap_or(max_dt)
+    }
+
+    /// Returns `"1/n"` for ratios which are (close to) the reciprocal of a natural number.
+    pub fn format_ratio(ratio: f32) -> String {
+        let reciprocal = ratio.recip();
+        if ratio < 1. && (reciprocal - reciprocal.round()).abs() < 1e-3 {
+            format!("1/{}", reciprocal.round())
+        } else {
+            format!("{}", ratio)
+        }
+    }
+}
+
+impl std::fmt::Display for StepSizeGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ratios = self
+            .members
+            .iter()
+            .map(|member| Self::format_ratio(member.ratio))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.user_label.is_empty() {
+            write!(f, "{} × ({})", self.master_dt, ratios)
+        } else {
+            write!(
+                f,
+                "{} × ({}) \"{}\"",
+                self.master_dt, ratios, self.user_label
+            )
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use super::{command::Command, World};
+
+/// Undo/redo history of all edits of the `World`, including its canvases and settings.
+///
+/// The world journals the commands which revert its edits (see `World::apply()`).  Each entry of
+/// the history holds the journaled commands of one edit, so an entry is undone by applying its
+/// commands in reverse order.  This journals the commands which redo the edit in turn.
+///
+/// Canvases which are closed or whose integrations are removed keep their computations in the
+/// history, so that they are shown right away when the edit is undone.
+#[derive(Default)]
+pub struct History {
+    undo_stack: Vec<Vec<Command>>,
+    redo_stack: Vec<Vec<Command>>,
+}
+
+/// The maximum number of edits that can be undone.
+const MAX_UNDO_DEPTH: usize = 100;
+
+impl History {
+    /// Records the edits which have been applied to `world` since the last call as one entry.
+    /// Continuous edits (like dragging a slider) should only be recorded when they have been
+    /// completed, so that they can be undone in a single step.
+    pub fn record(&mut self, world: &mut World) {
+        let commands = world.take_journal();
+        if !commands.is_empty() {
+            if self.undo_stack.len() == MAX_UNDO_DEPTH {
+                self.undo_stack.remove(0);
+            }
+            self.undo_stack.push(commands);
+            self.redo_stack.clear();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Edits which have not been recorded yet are recorded first, so that they are undone.
+    pub fn undo(&mut self, world: &mut World) {
+        self.record(world);
+        if let Some(commands) = self.undo_stack.pop() {
+            self.redo_stack.push(Self::revert(commands, world));
+        }
+    }
+
+    pub fn redo(&mut self, world: &mut World) {
+        self.record(world);
+        if let Some(commands) = self.redo_stack.pop() {
+            self.undo_stack.push(Self::revert(commands, world));
+        }
+    }
+
+    /// Applies the `commands` in reverse order, and returns the commands which revert them.
+    fn revert(commands: Vec<Command>, world: &mut World) -> Vec<Command> {
+        for command in commands.into_iter().rev() {
+            world.apply(command);
+        }
+        world.take_journal()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{History, World, MAX_UNDO_DEPTH};
+    use crate::{
+        core::{integrators, scenarios, Position, Scenario, Velocity},
+        entities::{Canvas, Integration, Integrator, StepSize},
+        misc::{
+            entity_store::{self, RemovalStrategy},
+            UserLabel,
+        },
+        ui_import::{Color32, Stroke},
+    };
+
+    fn add_step_size(world: &mut World, duration: f32) -> entity_store::Index<StepSize> {
+        world.add_step_size(StepSize {
+            user_label: UserLabel(String::new()),
+            duration: duration.into(),
+            color: Color32::YELLOW,
+        })
+    }
+
+    /// A canvas with an integration for each of two step sizes, which are returned.
+    fn canvas_with_two_integrations(
+        world: &mut World,
+    ) -> (entity_store::Index<StepSize>, entity_store::Index<StepSize>) {
+        let (a, b) = (add_step_size(world, 0.1), add_step_size(world, 0.2));
+        let integrator = world.add_integrator(Integrator {
+            core: Box::new(integrators::euler::Broken),
+            stroke: Stroke::new(1_f32, Color32::WHITE),
+        });
+        let scenario = world.add_scenario(Scenario {
+            acceleration: Box::new(scenarios::ConstantAcceleration),
+            start_position: Position::origin(),
+            start_velocity: Velocity::new(1., 0., 0.),
+            duration: 1_f32.into(),
+            event: None,
+            obstacles: Vec::new(),
+            mass: 1.,
+            bodies: Vec::new(),
+        });
+        let mut canvas = Canvas::new(scenario);
+        canvas.add_integration(Integration::new(integrator, a));
+        canvas.add_integration(Integration::new(integrator, b));
+        world.add_canvas(canvas);
+        (a, b)
+    }
+
+    fn integration_step_sizes(world: &World) -> Vec<entity_store::Index<StepSize>> {
+        world
+            .canvases()
+            .flat_map(|canvas| {
+                canvas
+                    .borrow()
+                    .integrations()
+                    .map(|integration| integration.borrow().step_size_idx())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut world = World::default();
+        let mut history = History::default();
+        history.record(&mut world);
+        assert!(!history.can_undo());
+
+        add_step_size(&mut world, 0.1);
+        history.record(&mut world);
+        // unchanged:
+        history.record(&mut world);
+        add_step_size(&mut world, 0.2);
+        history.record(&mut world);
+
+        history.undo(&mut world);
+        assert_eq!(world.step_sizes().len(), 1);
+        history.undo(&mut world);
+        assert_eq!(world.step_sizes().len(), 0);
+        assert!(!history.can_undo());
+        // restoring is no edit:
+        history.record(&mut world);
+        assert!(history.can_redo());
+
+        history.redo(&mut world);
+        assert_eq!(world.step_sizes().len(), 1);
+        assert!(history.can_redo());
+
+        // a new edit discards what could be redone:
+        add_step_size(&mut world, 0.3);
+        history.record(&mut world);
+        assert!(!history.can_redo());
+        history.undo(&mut world);
+        assert_eq!(world.step_sizes().len(), 1);
+    }
+
+    #[test]
+    fn limited_depth() {
+        let mut world = World::default();
+        let mut history = History::default();
+        history.record(&mut world);
+        #[allow(clippy::cast_precision_loss)]
+        for n in 0..MAX_UNDO_DEPTH + 5 {
+            add_step_size(&mut world, 0.1 + n as f32);
+            history.record(&mut world);
+        }
+        let mut undone = 0;
+        while history.can_undo() {
+            history.undo(&mut world);
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_UNDO_DEPTH);
+        assert_eq!(world.step_sizes().len(), 5);
+    }
+
+    #[test]
+    fn undo_removal_restores_referrers() {
+        let mut world = World::default();
+        let (a, b) = canvas_with_two_integrations(&mut world);
+        world.take_journal();
+        let mut history = History::default();
+
+        world.remove_step_size(a, RemovalStrategy::Cascade).unwrap();
+        history.record(&mut world);
+        assert_eq!(integration_step_sizes(&world), vec![b]);
+        history.undo(&mut world);
+        assert_eq!(world.step_sizes().len(), 2);
+        assert_eq!(integration_step_sizes(&world), vec![a, b]);
+        history.redo(&mut world);
+        assert_eq!(integration_step_sizes(&world), vec![b]);
+        history.undo(&mut world);
+
+        world
+            .remove_step_size(a, RemovalStrategy::Reassign(b))
+            .unwrap();
+        history.record(&mut world);
+        assert_eq!(integration_step_sizes(&world), vec![b, b]);
+        history.undo(&mut world);
+        assert_eq!(integration_step_sizes(&world), vec![a, b]);
+
+        world.remove_canvas(0);
+        history.record(&mut world);
+        assert_eq!(world.canvases().len(), 0);
+        history.undo(&mut world);
+        assert_eq!(integration_step_sizes(&world), vec![a, b]);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // restored values
+    fn continuous_edit_is_one_entry() {
+        let mut world = World::default();
+        let step_size_idx = add_step_size(&mut world, 0.1);
+        let mut history = History::default();
+        history.record(&mut world);
+
+        // dragging a slider:
+        for duration in [0.2, 0.3, 0.4] {
+            world.set_step_size_duration(step_size_idx, duration.into());
+        }
+        history.record(&mut world);
+        history.undo(&mut world);
+        assert_eq!(f32::from(world[step_size_idx].borrow().duration), 0.1);
+        history.undo(&mut world);
+        assert_eq!(world.step_sizes().len(), 0);
+        history.redo(&mut world);
+        history.redo(&mut world);
+        assert_eq!(f32::from(world[step_size_idx].borrow().duration), 0.4);
+    }
+}
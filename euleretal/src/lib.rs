@@ -7,8 +7,10 @@
 //#![deny(missing_docs)]
 
 mod app;
+mod command;
 mod containers;
 mod entities;
+mod history;
 mod misc;
 mod world;
 
@@ -32,6 +34,7 @@ mod ui_import {
 
 use ::euleretal_core as core;
 pub use app::Euleretal;
+use history::History;
 use world::World;
 
 // ----------------------------------------------------------------------------
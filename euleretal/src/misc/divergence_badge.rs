@@ -0,0 +1,34 @@
+use super::{
+    core::divergence::Diverged,
+    ui_import::{egui::Label, Color32, Ui},
+};
+
+const GLYPH_WARNING: &str = "\u{26a0}"; // \u{26a0} = '⚠'
+
+/// A red badge for integrations which stopped early because they diverged, given by the labels
+/// of their integrators.  Hovering it tells where and why.  Shows nothing if there are none.
+pub fn divergence_badge(
+    ui: &mut Ui,
+    diverged: impl IntoIterator<Item = (String, Diverged)>,
+    precision: usize,
+) {
+    let descriptions = diverged
+        .into_iter()
+        .map(|(label, diverged)| {
+            format!(
+                "{} stopped at t = {:.*}: {}",
+                label,
+                precision,
+                f32::from(diverged.t),
+                diverged.divergence
+            )
+        })
+        .collect::<Vec<_>>();
+    let text = match descriptions.len() {
+        0 => return,
+        1 => format!("{} diverged", GLYPH_WARNING),
+        n => format!("{} {} diverged", GLYPH_WARNING, n),
+    };
+    ui.add(Label::new(text).text_color(Color32::RED))
+        .on_hover_text(descriptions.join("\n"));
+}
@@ -1,4 +1,9 @@
-use ::std::{cell::RefCell, collections::BTreeMap, marker::PhantomData};
+use ::std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
 
 /// All references to contents of this list are based on Indexes (not `::std::rc::Rc`), because
 /// otherwise deserialization would duplicate instances which where just one before serialization.
@@ -12,7 +17,7 @@ pub struct List<T> {
     next_key: usize,
 }
 
-#[derive(Debug, ::serde::Deserialize, ::serde::Serialize)]
+#[derive(::serde::Deserialize, ::serde::Serialize)]
 #[serde(transparent)]
 pub struct Index<T> {
     inner: usize,
@@ -20,6 +25,35 @@ pub struct Index<T> {
     type_bound: PhantomData<T>,
 }
 
+/// Implemented by everything that holds `Index<T>` references into a `List<T>`.
+pub trait Referrer<T> {
+    /// Returns the number of references to `idx`.
+    fn count_references(&self, idx: Index<T>) -> usize;
+}
+
+/// Referrers which can be changed, so that [`List::remove`] can keep their references valid.
+pub trait ReferrerMut<T>: Referrer<T> {
+    /// Drops everything that refers to `idx`.
+    fn drop_references(&mut self, idx: Index<T>);
+
+    /// Lets every reference to `from` refer to `to` instead.
+    fn reassign_references(&mut self, from: Index<T>, to: Index<T>);
+}
+
+/// The referrers of entries which nothing refers to, e.g. for removing them with
+/// [`RemovalStrategy::Block`].
+pub struct NoReferrers;
+
+/// Determines what happens to the referrers of an entry that gets removed from a `List`.
+pub enum RemovalStrategy<T> {
+    /// Refuse the removal as long as the entry is still in use.
+    Block,
+    /// Remove all referrers together with the entry.
+    Cascade,
+    /// Let all referrers use another entry of the same `List`.
+    Reassign(Index<T>),
+}
+
 impl<T> List<T> {
     pub fn new() -> Self {
         Self {
@@ -47,8 +81,66 @@ impl<T> List<T> {
         }
     }
 
-    pub fn delete(&mut self, idx: Index<T>) {
-        self.inner.remove(&idx.inner);
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Checks whether [`List::remove`] would succeed, without changing anything.
+    pub fn check_removal(
+        &self,
+        idx: Index<T>,
+        strategy: RemovalStrategy<T>,
+        referrers: &impl Referrer<T>,
+    ) -> Result<(), String> {
+        idx.check_reference(self)?;
+        match strategy {
+            RemovalStrategy::Block => match referrers.count_references(idx) {
+                0 => Ok(()),
+                1 => Err("It is still in use once.".to_string()),
+                n => Err(format!("It is still in use {} times.", n)),
+            },
+            RemovalStrategy::Cascade => Ok(()),
+            RemovalStrategy::Reassign(replacement) => {
+                if replacement == idx {
+                    Err("It cannot be replaced by itself.".to_string())
+                } else {
+                    replacement
+                        .check_reference(self)
+                        .map_err(|err| format!("Replacement {}.", err))
+                }
+            }
+        }
+    }
+
+    /// Removes the entry at `idx` and returns it.  All references to it which are held by
+    /// `referrers` are dropped or reassigned, depending on `strategy`.
+    pub fn remove(
+        &mut self,
+        idx: Index<T>,
+        strategy: RemovalStrategy<T>,
+        referrers: &mut impl ReferrerMut<T>,
+    ) -> Result<T, String> {
+        self.check_removal(idx, strategy, referrers)?;
+        match strategy {
+            RemovalStrategy::Block => (),
+            RemovalStrategy::Cascade => referrers.drop_references(idx),
+            RemovalStrategy::Reassign(replacement) => {
+                referrers.reassign_references(idx, replacement);
+            }
+        }
+        debug_assert_eq!(referrers.count_references(idx), 0);
+        Ok(self.inner.remove(&idx.inner).unwrap().into_inner())
+    }
+
+    /// Replaces the entry at `idx` and returns the previous one.  The entry is inserted if there
+    /// was none, and removed if `item` is `None`.  Unlike [`List::remove`], this does not care
+    /// for references to the entry, e.g. when an edit is reverted.
+    pub fn replace(&mut self, idx: Index<T>, item: Option<T>) -> Option<T> {
+        match item {
+            Some(item) => self.inner.insert(idx.inner, RefCell::new(item)),
+            None => self.inner.remove(&idx.inner),
+        }
+        .map(RefCell::into_inner)
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &RefCell<T>> {
@@ -60,6 +152,18 @@ impl<T> List<T> {
     }
 }
 
+impl<T> Referrer<T> for NoReferrers {
+    fn count_references(&self, _idx: Index<T>) -> usize {
+        0
+    }
+}
+
+impl<T> ReferrerMut<T> for NoReferrers {
+    fn drop_references(&mut self, _idx: Index<T>) {}
+
+    fn reassign_references(&mut self, _from: Index<T>, _to: Index<T>) {}
+}
+
 impl<'a, T> IntoIterator for &'a List<T> {
     type Item = &'a RefCell<T>;
 
@@ -117,3 +221,142 @@ impl<T> PartialEq for Index<T> {
         self.inner == other.inner
     }
 }
+
+impl<T> ::std::fmt::Debug for Index<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        f.debug_struct("Index").field("inner", &self.inner).finish()
+    }
+}
+
+impl<T> Hash for Index<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner.hash(state);
+    }
+}
+
+impl<T> Clone for RemovalStrategy<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RemovalStrategy<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Index, List, Referrer, ReferrerMut, RemovalStrategy};
+
+    /// Holds references into a `List<&str>`, like canvases hold references to scenarios.
+    #[derive(Default)]
+    struct Holders(Vec<Index<&'static str>>);
+
+    impl Referrer<&'static str> for Holders {
+        fn count_references(&self, idx: Index<&'static str>) -> usize {
+            self.0.iter().filter(|&&each| each == idx).count()
+        }
+    }
+
+    impl ReferrerMut<&'static str> for Holders {
+        fn drop_references(&mut self, idx: Index<&'static str>) {
+            self.0.retain(|&each| each != idx);
+        }
+
+        fn reassign_references(&mut self, from: Index<&'static str>, to: Index<&'static str>) {
+            for each in &mut self.0 {
+                if *each == from {
+                    *each = to;
+                }
+            }
+        }
+    }
+
+    fn list() -> (List<&'static str>, [Index<&'static str>; 2]) {
+        let mut list = List::new();
+        let a = list.push("a");
+        let b = list.push("b");
+        (list, [a, b])
+    }
+
+    #[test]
+    fn block_refuses_entries_in_use() {
+        let (mut list, [a, b]) = list();
+        let mut holders = Holders(vec![a, a]);
+        assert_eq!(
+            list.remove(a, RemovalStrategy::Block, &mut holders),
+            Err("It is still in use 2 times.".to_string())
+        );
+        assert_eq!(list.len(), 2);
+        assert_eq!(holders.0, vec![a, a]);
+        assert_eq!(
+            list.remove(b, RemovalStrategy::Block, &mut holders),
+            Ok("b")
+        );
+        assert_eq!(list.len(), 1);
+        assert!(b.check_reference(&list).is_err());
+    }
+
+    #[test]
+    fn cascade_drops_referrers() {
+        let (mut list, [a, b]) = list();
+        let mut holders = Holders(vec![a, b, a]);
+        assert!(list
+            .check_removal(a, RemovalStrategy::Cascade, &holders)
+            .is_ok());
+        assert_eq!(
+            list.remove(a, RemovalStrategy::Cascade, &mut holders),
+            Ok("a")
+        );
+        assert_eq!(holders.0, vec![b]);
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn reassign_lets_referrers_use_the_replacement() {
+        let (mut list, [a, b]) = list();
+        let mut holders = Holders(vec![a, b, a]);
+        assert!(list
+            .check_removal(a, RemovalStrategy::Reassign(a), &holders)
+            .is_err());
+        assert_eq!(
+            list.remove(a, RemovalStrategy::Reassign(b), &mut holders),
+            Ok("a")
+        );
+        assert_eq!(holders.0, vec![b, b, b]);
+
+        // the replacement must exist:
+        let c = list.push("c");
+        assert!(list
+            .remove(b, RemovalStrategy::Reassign(a), &mut holders)
+            .is_err());
+        assert_eq!(holders.0, vec![b, b, b]);
+        assert_eq!(
+            list.remove(b, RemovalStrategy::Reassign(c), &mut holders),
+            Ok("b")
+        );
+        assert_eq!(holders.0, vec![c, c, c]);
+    }
+
+    #[test]
+    fn replace_inserts_and_removes_at_the_index() {
+        let (mut list, [a, b]) = list();
+        assert_eq!(list.replace(a, None), Some("a"));
+        assert!(a.check_reference(&list).is_err());
+        assert_eq!(list.replace(a, Some("c")), None);
+        assert_eq!(list.replace(a, Some("d")), Some("c"));
+        assert_eq!(*list[a].borrow(), "d");
+        // new entries do not take the index of a reinserted one:
+        let e = list.push("e");
+        assert!(e != a && e != b);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn removed_entries_cannot_be_removed_again() {
+        let (mut list, [a, _]) = list();
+        let mut holders = Holders::default();
+        assert!(list.remove(a, RemovalStrategy::Block, &mut holders).is_ok());
+        assert!(list
+            .remove(a, RemovalStrategy::Cascade, &mut holders)
+            .is_err());
+    }
+}
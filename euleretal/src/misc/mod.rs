@@ -1,12 +1,16 @@
 mod bounding_box;
+mod divergence_badge;
 pub mod entity_store;
 mod my_stroke_ui;
+mod polyline;
 pub mod settings;
 mod stroke_ext;
 mod user_label;
 
 pub use bounding_box::BoundingBox;
+pub use divergence_badge::divergence_badge;
 pub use my_stroke_ui::{my_stroke_preview, my_stroke_ui};
+pub use polyline::Polyline;
 pub use settings::{PointFormat, PointShape, Settings};
 pub use stroke_ext::StrokeExt;
 pub use user_label::UserLabel;
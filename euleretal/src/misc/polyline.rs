@@ -0,0 +1,130 @@
+use super::ui_import::{
+    egui::{self, Rect},
+    Pos2,
+};
+
+/// Maximum deviation (in screen dimensions) of a simplified polyline from the original one.  It
+/// is well below the width of a pixel, so simplification does not change the look at any zoom
+/// level.
+const TOLERANCE: f32 = 0.3;
+
+/// A polyline in screen coordinates, simplified for the viewport it has been computed for and
+/// cached until the viewport or the underlying positions change.
+#[derive(Default)]
+pub struct Polyline {
+    /// Identifies the positions and the viewport the runs have been computed for
+    key: Option<u64>,
+    /// The visible parts of the polyline
+    runs: Vec<Vec<Pos2>>,
+}
+
+impl Polyline {
+    /// Draws the polyline, re-computing it from `screen_positions` only if `key` has changed
+    /// since the last call.
+    pub fn draw<I>(
+        &mut self,
+        key: u64,
+        screen_positions: impl FnOnce() -> I,
+        clip_rect: Rect,
+        stroke: egui::Stroke,
+        painter: &egui::Painter,
+    ) where
+        I: Iterator<Item = Pos2>,
+    {
+        if self.key != Some(key) {
+            self.runs = simplify(screen_positions(), clip_rect.expand(stroke.width));
+            self.key = Some(key);
+        }
+        for run in &self.runs {
+            painter.add(egui::Shape::line(run.clone(), stroke));
+        }
+    }
+}
+
+/// Drops points which are closer than `TOLERANCE` to their predecessor, and splits the polyline
+/// into the runs of segments which intersect `clip_rect`.
+fn simplify(positions: impl Iterator<Item = Pos2>, clip_rect: Rect) -> Vec<Vec<Pos2>> {
+    let mut runs = Vec::new();
+    let mut run = Vec::new();
+    let mut positions = positions.peekable();
+    let Some(mut last_kept) = positions.next() else {
+        return runs;
+    };
+    while let Some(position) = positions.next() {
+        let is_last = positions.peek().is_none();
+        if !is_last && last_kept.distance(position) < TOLERANCE {
+            continue;
+        }
+        if clip_rect.intersects(Rect::from_two_pos(last_kept, position)) {
+            if run.is_empty() {
+                run.push(last_kept);
+            }
+            run.push(position);
+        } else if !run.is_empty() {
+            runs.push(::std::mem::take(&mut run));
+        }
+        last_kept = position;
+    }
+    if !run.is_empty() {
+        runs.push(run);
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{simplify, Pos2, Rect};
+
+    fn rect() -> Rect {
+        Rect::from_min_max(Pos2::new(0., 0.), Pos2::new(10., 10.))
+    }
+
+    #[test]
+    fn drops_close_points_but_the_last() {
+        let positions = [(0., 0.), (0.1, 0.), (0.2, 0.1), (5., 5.), (5.1, 5.)];
+        let runs = simplify(positions.iter().map(|&(x, y)| Pos2::new(x, y)), rect());
+        let expected = [(0., 0.), (5., 5.), (5.1, 5.)];
+        assert_eq!(
+            runs,
+            vec![expected
+                .iter()
+                .map(|&(x, y)| Pos2::new(x, y))
+                .collect::<Vec<_>>()]
+        );
+    }
+
+    #[test]
+    fn splits_into_visible_runs() {
+        let positions = [
+            (1., 1.),
+            (5., 5.),
+            (20., 20.),
+            (30., 30.),
+            (5., 1.),
+            (2., 2.),
+        ];
+        let runs = simplify(positions.iter().map(|&(x, y)| Pos2::new(x, y)), rect());
+        let expected = [
+            vec![(1., 1.), (5., 5.), (20., 20.)],
+            vec![(30., 30.), (5., 1.), (2., 2.)],
+        ];
+        assert_eq!(
+            runs,
+            expected
+                .iter()
+                .map(|run| run
+                    .iter()
+                    .map(|&(x, y)| Pos2::new(x, y))
+                    .collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn invisible_or_single_points_are_not_drawn() {
+        let outside = [(20., 20.), (30., 20.)];
+        assert!(simplify(outside.iter().map(|&(x, y)| Pos2::new(x, y)), rect()).is_empty());
+        assert!(simplify(::std::iter::once(Pos2::new(1., 1.)), rect()).is_empty());
+        assert!(simplify(::std::iter::empty(), rect()).is_empty());
+    }
+}
@@ -3,22 +3,26 @@ use super::core::PhysicalQuantityKind;
 use super::ui_import::{egui::Painter, Color32, Pos2, Rgba, Stroke, Vec2};
 use ::std::fmt;
 
-#[derive(Debug, ::serde::Serialize, ::serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
 pub struct Settings {
     pub layerflags: LayerFlags,
     pub strokes: Strokes,
     pub point_formats: PointFormats,
     pub format_precision: usize,
+    /// Scale the step size of each integration by the samples per step of its integrator, such that
+    /// all integrations sample the acceleration field equally often.
+    #[serde(default)]
+    pub equal_cost: bool,
 }
 
-#[derive(Debug, ::serde::Serialize, ::serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
 pub struct LayerFlags {
     pub coordinates: bool,
     pub acceleration_field: bool,
     pub inspector: bool,
 }
 
-#[derive(Debug, ::serde::Serialize, ::serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
 pub struct Strokes {
     pub trajectory: Stroke,
     pub acceleration: Stroke,
@@ -33,9 +37,14 @@ pub struct Strokes {
     pub contributing_acceleration: Stroke,
     pub derived_velocity: Stroke,
     pub reference_velocity: Stroke,
+    #[serde(default = "Strokes::default_obstacle")]
+    pub obstacle: Stroke,
+    /// to mark where integrations diverged
+    #[serde(default = "Strokes::default_divergence")]
+    pub divergence: Stroke,
 }
 
-#[derive(Debug, ::serde::Serialize, ::serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
 pub struct PointFormats {
     /// to be used for positions that are the basis for a derived position
     pub start_position: PointFormat,
@@ -44,7 +53,7 @@ pub struct PointFormats {
     pub other_position: PointFormat,
 }
 
-#[derive(Clone, Debug, ::serde::Serialize, ::serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
 pub struct PointFormat {
     pub shape: PointShape,
     // size of the shape in screen dimensions
@@ -53,7 +62,7 @@ pub struct PointFormat {
     pub stroke: Stroke,
 }
 
-#[derive(Clone, Debug, ::serde::Serialize, ::serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
 pub enum PointShape {
     Dot,
     CrossHair,
@@ -111,11 +120,21 @@ impl Default for Strokes {
             contributing_acceleration: Stroke::new(1., col_accel),
             derived_velocity: PointFormats::default().derived_position.stroke,
             reference_velocity: PointFormats::default().reference_position.stroke,
+            obstacle: Self::default_obstacle(),
+            divergence: Self::default_divergence(),
         }
     }
 }
 
 impl Strokes {
+    fn default_obstacle() -> Stroke {
+        Stroke::new(1., Color32::from_rgb(160, 120, 80))
+    }
+
+    fn default_divergence() -> Stroke {
+        Stroke::new(2_f32, Color32::RED)
+    }
+
     /// # Panics
     /// Panics if kind is `_::Position`
     pub fn for_contribution(&self, kind: PhysicalQuantityKind) -> Stroke {
@@ -134,6 +153,7 @@ impl Default for Settings {
             strokes: Strokes::default(),
             point_formats: PointFormats::default(),
             format_precision: 3,
+            equal_cost: false,
         }
     }
 }
@@ -1,6 +1,6 @@
 use ::std::{fmt::Display, ops::Deref};
 
-#[derive(Debug, ::serde::Serialize, ::serde::Deserialize)]
+#[derive(Clone, Debug, ::serde::Serialize, ::serde::Deserialize)]
 pub struct UserLabel(pub String);
 
 impl Deref for UserLabel {
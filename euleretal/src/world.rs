@@ -1,9 +1,15 @@
 use super::{
-    core::Scenario,
-    entities::{Canvas, Integrator, StepSize},
-    misc::{entity_store, Settings},
+    command::{CanvasCommand, Command, Journal},
+    core::{Duration, ReferenceCache, Scenario},
+    entities::{Canvas, Integration, Integrator, StepSize, StepSizeGroup},
+    misc::{
+        entity_store::{self, NoReferrers, Referrer, ReferrerMut, RemovalStrategy},
+        Settings,
+    },
+};
+use ::std::{
+    cell::RefCell, collections::hash_map::DefaultHasher, hash::Hasher, slice::Iter, sync::Arc,
 };
-use ::std::{cell::RefCell, slice::Iter};
 
 #[derive(Debug, Default, ::serde::Serialize, ::serde::Deserialize)]
 pub struct World {
@@ -11,9 +17,43 @@ pub struct World {
     scenarios: entity_store::List<Scenario>,
     integrators: entity_store::List<Integrator>,
     step_sizes: entity_store::List<StepSize>,
-    pub settings: Settings,
+    #[serde(default)]
+    step_size_groups: entity_store::List<StepSizeGroup>,
+    settings: Settings,
+    /// Shared by all canvases
+    #[serde(skip)]
+    references: Arc<ReferenceCache>,
+    /// Scenarios whose duration keeps growing, see `integrate_further()`
+    #[serde(skip)]
+    integrating_further: Vec<entity_store::Index<Scenario>>,
+    /// The commands which revert the edits since the last `take_journal()`
+    #[serde(skip)]
+    journal: Journal,
+}
+
+/// The entities which are kept in the lists of the [`World`]
+pub trait Entity: Clone {
+    fn list(world: &World) -> &entity_store::List<Self>;
+
+    /// The list of the entities, and the canvases which may refer to them
+    fn split(world: &mut World) -> (&mut entity_store::List<Self>, Canvases<'_>);
+
+    /// The command which replaces the entity at `idx` by `entity`
+    fn command(idx: entity_store::Index<Self>, entity: Option<Self>) -> Command;
 }
 
+/// The canvases of the [`World`] with its journal, so that the canvases can be edited reversibly,
+/// e.g. when the entities they refer to are removed.
+pub struct Canvases<'a> {
+    canvases: &'a mut Vec<RefCell<Canvas>>,
+    journal: &'a mut Journal,
+}
+
+/// Step sizes below this value would result in an excessive number of steps.
+pub const MIN_STEP_DURATION: f32 = 0.01;
+/// The largest step size which can be chosen in the controls
+pub const MAX_STEP_DURATION: f32 = 2.;
+
 impl World {
     pub fn canvases(&self) -> Iter<RefCell<Canvas>> {
         self.canvases.iter()
@@ -23,52 +63,670 @@ impl World {
         &self.scenarios
     }
 
+    pub fn references(&self) -> &Arc<ReferenceCache> {
+        &self.references
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Lets `edit` change a copy of the settings, which replaces them if it differs.
+    pub fn edit_settings(&mut self, edit: impl FnOnce(&mut Settings)) {
+        let mut settings = self.settings.clone();
+        edit(&mut settings);
+        if settings != self.settings {
+            self.apply(Command::Settings(Box::new(settings)));
+        }
+    }
+
+    /// Applies `command`, and journals the command which reverts it.
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::Scenario(idx, scenario) => self.replace(idx, scenario),
+            Command::Integrator(idx, integrator) => self.replace(idx, integrator),
+            Command::StepSize(idx, step_size) => self.replace(idx, step_size),
+            Command::StepSizeGroup(idx, group) => self.replace(idx, group),
+            Command::Settings(settings) => {
+                let previous = ::std::mem::replace(&mut self.settings, *settings);
+                self.journal.push(Command::Settings(Box::new(previous)));
+            }
+            Command::Canvas(position, canvas) => self.canvases_mut().replace(position, canvas),
+            Command::EditCanvas(position, command) => self.canvases_mut().edit(position, command),
+        }
+    }
+
+    /// The commands which revert all edits since the last call, in the order of the edits
+    pub fn take_journal(&mut self) -> Vec<Command> {
+        self.journal.take()
+    }
+
+    /// Lets `edit` change a copy of the entity at `idx`, which then replaces it.
+    pub fn edit<T: Entity>(&mut self, idx: entity_store::Index<T>, edit: impl FnOnce(&mut T)) {
+        let mut entity = T::list(self)[idx].borrow().clone();
+        edit(&mut entity);
+        self.replace(idx, Some(entity));
+    }
+
+    fn replace<T: Entity>(&mut self, idx: entity_store::Index<T>, entity: Option<T>) {
+        let (list, canvases) = T::split(self);
+        let previous = list.replace(idx, entity);
+        canvases.journal.push(T::command(idx, previous));
+    }
+
+    fn add<T: Entity>(&mut self, entity: T) -> entity_store::Index<T> {
+        let (list, canvases) = T::split(self);
+        let idx = list.push(entity);
+        canvases.journal.push(T::command(idx, None));
+        idx
+    }
+
+    /// Removes the entity at `idx`, and drops or reassigns its referrers (see
+    /// [`entity_store::List::remove`]).
+    fn remove<T: Entity>(
+        &mut self,
+        idx: entity_store::Index<T>,
+        strategy: RemovalStrategy<T>,
+    ) -> Result<(), String>
+    where
+        for<'a> Canvases<'a>: ReferrerMut<T>,
+    {
+        let (list, mut canvases) = T::split(self);
+        let entity = list.remove(idx, strategy, &mut canvases)?;
+        canvases.journal.push(T::command(idx, Some(entity)));
+        Ok(())
+    }
+
+    fn canvases_mut(&mut self) -> Canvases<'_> {
+        Canvases {
+            canvases: &mut self.canvases,
+            journal: &mut self.journal,
+        }
+    }
+
+    /// Drops the cached references which are not needed by the current scenarios anymore.
+    pub fn evict_references(&self) {
+        let scenario_hashes = self
+            .scenarios
+            .iter()
+            .map(|scenario| {
+                let mut hasher = DefaultHasher::new();
+                scenario.borrow().hash_without_duration(&mut hasher);
+                hasher.finish()
+            })
+            .collect::<Vec<_>>();
+        self.references.retain_scenarios(&scenario_hashes);
+    }
+
+    pub fn is_integrating_further(&self, scenario_idx: entity_store::Index<Scenario>) -> bool {
+        self.integrating_further.contains(&scenario_idx)
+    }
+
+    pub fn set_integrating_further(
+        &mut self,
+        scenario_idx: entity_store::Index<Scenario>,
+        enabled: bool,
+    ) {
+        self.integrating_further.retain(|&idx| idx != scenario_idx);
+        if enabled {
+            self.integrating_further.push(scenario_idx);
+        }
+    }
+
+    /// Extends the duration of each scenario which is integrated further by the largest step
+    /// duration of its integrations, as soon as all of its canvases have caught up with the
+    /// previous extension.  The integrations then only compute the appended steps.
+    ///
+    /// returns `true` while any scenario is integrated further
+    pub fn integrate_further(&mut self) -> bool {
+        let scenarios = &self.scenarios;
+        self.integrating_further
+            .retain(|&scenario_idx| scenario_idx.check_reference(scenarios).is_ok());
+        let mut extensions = Vec::new();
+        for &scenario_idx in &self.integrating_further {
+            let canvases = self
+                .canvases
+                .iter()
+                .filter(|canvas| canvas.borrow().scenario_idx() == scenario_idx)
+                .collect::<Vec<_>>();
+            if canvases.iter().any(|canvas| canvas.borrow().is_busy()) {
+                continue;
+            }
+            let max_step_duration = canvases
+                .iter()
+                .flat_map(|canvas| {
+                    canvas
+                        .borrow()
+                        .integrations()
+                        .map(|integration| integration.borrow().fetch_step_duration(self))
+                        .collect::<Vec<_>>()
+                })
+                .reduce(|a, b| if a < b { b } else { a });
+            if let Some(step_duration) = max_step_duration {
+                extensions.push((scenario_idx, step_duration));
+            }
+        }
+        for (scenario_idx, step_duration) in extensions {
+            self.edit(scenario_idx, |scenario: &mut Scenario| {
+                scenario.duration += step_duration;
+            });
+        }
+        !self.integrating_further.is_empty()
+    }
+
     pub fn integrators(&self) -> &entity_store::List<Integrator> {
         &self.integrators
     }
 
+    /// Integrators grouped by the number of samples per step (ascending), each group sorted by
+    /// order and label.  Integrators of unknown order come last within their group.
+    pub fn integrators_by_samples(&self) -> Vec<(usize, Vec<entity_store::Index<Integrator>>)> {
+        let mut sorted = self
+            .integrators
+            .enumerate()
+            .map(|(integrator_idx, integrator)| {
+                let core = &integrator.borrow().core;
+                let metadata = core.metadata();
+                let order = metadata.order.unwrap_or(usize::MAX);
+                (
+                    (metadata.samples_per_step, order, core.label()),
+                    integrator_idx,
+                )
+            })
+            .collect::<Vec<_>>();
+        sorted.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        let mut groups: Vec<(usize, Vec<_>)> = Vec::new();
+        for ((samples_per_step, ..), integrator_idx) in sorted {
+            match groups.last_mut() {
+                Some((group_samples, members)) if *group_samples == samples_per_step => {
+                    members.push(integrator_idx);
+                }
+                _ => groups.push((samples_per_step, vec![integrator_idx])),
+            }
+        }
+        groups
+    }
+
     pub fn step_sizes(&self) -> &entity_store::List<StepSize> {
         &self.step_sizes
     }
 
-    pub fn add_canvas(&mut self, canvas: Canvas) -> &RefCell<Canvas> {
-        self.canvases.push(RefCell::new(canvas));
-        self.canvases.last().unwrap()
+    pub fn step_size_groups(&self) -> &entity_store::List<StepSizeGroup> {
+        &self.step_size_groups
+    }
+
+    pub fn add_canvas(&mut self, canvas: Canvas) {
+        let position = self.canvases.len();
+        self.canvases_mut()
+            .replace(position, Some(Box::new(canvas)));
+    }
+
+    pub fn remove_canvas(&mut self, position: usize) {
+        self.canvases_mut().replace(position, None);
+    }
+
+    pub fn edit_canvas(&mut self, position: usize, command: CanvasCommand) {
+        self.canvases_mut().edit(position, command);
     }
 
     pub fn add_scenario(&mut self, scenario: Scenario) -> entity_store::Index<Scenario> {
-        self.scenarios.push(scenario)
+        self.add(scenario)
     }
 
     pub fn add_step_size(&mut self, step_size: StepSize) -> entity_store::Index<StepSize> {
-        self.step_sizes.push(step_size)
+        self.add(step_size)
+    }
+
+    /// Adds the group and scales its members according to its master step size.
+    ///
+    /// Returns an error if a member is linked twice, or if it is a member of another group
+    /// already.
+    pub fn add_step_size_group(
+        &mut self,
+        group: StepSizeGroup,
+    ) -> Result<entity_store::Index<StepSizeGroup>, String> {
+        for (n, member) in group.members.iter().enumerate() {
+            member.step_size.check_reference(&self.step_sizes)?;
+            if group.members[..n]
+                .iter()
+                .any(|other| other.step_size == member.step_size)
+                || self
+                    .step_size_groups
+                    .iter()
+                    .any(|other| other.borrow().member(member.step_size).is_some())
+            {
+                return Err(format!(
+                    "Step size {} is linked already.",
+                    self[member.step_size].borrow()
+                ));
+            }
+        }
+        let master_dt = group.master_dt;
+        let group_idx = self.add(group);
+        self.set_step_size_group_dt(group_idx, master_dt);
+        Ok(group_idx)
+    }
+
+    /// Unlinks the members of the group.  The step sizes are kept.
+    pub fn remove_step_size_group(
+        &mut self,
+        group_idx: entity_store::Index<StepSizeGroup>,
+    ) -> Result<(), String> {
+        let group =
+            self.step_size_groups
+                .remove(group_idx, RemovalStrategy::Block, &mut NoReferrers)?;
+        self.journal
+            .push(Command::StepSizeGroup(group_idx, Some(group)));
+        Ok(())
+    }
+
+    /// Sets the duration of a step size, within `MIN_STEP_DURATION..=MAX_STEP_DURATION`.  If the
+    /// step size is a member of a group, all other members of that group are scaled
+    /// proportionally.
+    pub fn set_step_size_duration(
+        &mut self,
+        step_size_idx: entity_store::Index<StepSize>,
+        duration: Duration,
+    ) {
+        let group_with_ratio = self.step_size_groups.enumerate().find_map(|(idx, group)| {
+            group
+                .borrow()
+                .member(step_size_idx)
+                .map(|member| (idx, member.ratio))
+        });
+        if let Some((group_idx, ratio)) = group_with_ratio {
+            self.set_step_size_group_dt(group_idx, duration / ratio);
+        } else {
+            self.edit(step_size_idx, |step_size: &mut StepSize| {
+                step_size.duration =
+                    duration.clamp(MIN_STEP_DURATION.into(), MAX_STEP_DURATION.into());
+            });
+        }
     }
 
-    pub fn remove_step_size(&mut self, step_size_idx: entity_store::Index<StepSize>) {
-        self.step_sizes.delete(step_size_idx);
+    /// Sets the master step size of a group, and scales all of its members accordingly.  The
+    /// master step size is limited so that all members stay within
+    /// `MIN_STEP_DURATION..=MAX_STEP_DURATION` (as far as possible).
+    pub fn set_step_size_group_dt(
+        &mut self,
+        group_idx: entity_store::Index<StepSizeGroup>,
+        master_dt: Duration,
+    ) {
+        let mut group = self.step_size_groups[group_idx].borrow().clone();
+        group.master_dt = master_dt
+            .min(group.max_master_dt(MAX_STEP_DURATION.into()))
+            .max(group.min_master_dt(MIN_STEP_DURATION.into()));
+        for member in &group.members {
+            self.edit(member.step_size, |step_size: &mut StepSize| {
+                step_size.duration = group.master_dt * member.ratio;
+            });
+        }
+        self.replace(group_idx, Some(group));
     }
 
     pub fn add_integrator(&mut self, integrator: Integrator) -> entity_store::Index<Integrator> {
-        self.integrators.push(integrator)
+        self.add(integrator)
+    }
+
+    /// Returns the number of integrations or canvases which use the entity at `idx`.
+    pub fn count_references<T>(&self, idx: entity_store::Index<T>) -> usize
+    where
+        Vec<RefCell<Canvas>>: Referrer<T>,
+    {
+        self.canvases.count_references(idx)
+    }
+
+    pub fn check_scenario_removal(
+        &self,
+        scenario_idx: entity_store::Index<Scenario>,
+        strategy: RemovalStrategy<Scenario>,
+    ) -> Result<(), String> {
+        check_removal(
+            &self.scenarios,
+            &self.canvases,
+            scenario_idx,
+            strategy,
+            "scenario",
+        )?;
+        if let RemovalStrategy::Cascade = strategy {
+            if self.count_references(scenario_idx) == self.canvases.len() {
+                return Err("This would close all canvases.".to_string());
+            }
+        }
+        Ok(())
     }
 
-    #[allow(clippy::needless_pass_by_value)]
-    pub fn remove_canvas(&mut self, canvas: *const RefCell<Canvas>) {
-        self.canvases
-            .retain(|candidate| !::std::ptr::eq(canvas, candidate));
+    pub fn remove_scenario(
+        &mut self,
+        scenario_idx: entity_store::Index<Scenario>,
+        strategy: RemovalStrategy<Scenario>,
+    ) -> Result<(), String> {
+        self.check_scenario_removal(scenario_idx, strategy)?;
+        self.remove(scenario_idx, strategy)
+    }
+
+    pub fn check_integrator_removal(
+        &self,
+        integrator_idx: entity_store::Index<Integrator>,
+        strategy: RemovalStrategy<Integrator>,
+    ) -> Result<(), String> {
+        check_removal(
+            &self.integrators,
+            &self.canvases,
+            integrator_idx,
+            strategy,
+            "integrator",
+        )
+    }
+
+    pub fn remove_integrator(
+        &mut self,
+        integrator_idx: entity_store::Index<Integrator>,
+        strategy: RemovalStrategy<Integrator>,
+    ) -> Result<(), String> {
+        self.check_integrator_removal(integrator_idx, strategy)?;
+        self.remove(integrator_idx, strategy)
+    }
+
+    pub fn check_step_size_removal(
+        &self,
+        step_size_idx: entity_store::Index<StepSize>,
+        strategy: RemovalStrategy<StepSize>,
+    ) -> Result<(), String> {
+        check_removal(
+            &self.step_sizes,
+            &self.canvases,
+            step_size_idx,
+            strategy,
+            "step size",
+        )
+    }
+
+    pub fn remove_step_size(
+        &mut self,
+        step_size_idx: entity_store::Index<StepSize>,
+        strategy: RemovalStrategy<StepSize>,
+    ) -> Result<(), String> {
+        self.check_step_size_removal(step_size_idx, strategy)?;
+        self.remove(step_size_idx, strategy)?;
+        let groups = self
+            .step_size_groups
+            .enumerate()
+            .filter(|(_, group)| group.borrow().member(step_size_idx).is_some())
+            .map(|(group_idx, _)| group_idx)
+            .collect::<Vec<_>>();
+        for group_idx in groups {
+            self.edit(group_idx, |group: &mut StepSizeGroup| {
+                group
+                    .members
+                    .retain(|member| member.step_size != step_size_idx);
+            });
+        }
+        Ok(())
     }
 
     pub fn check_references(self) -> Result<World, String> {
+        for (n, scenario) in self.scenarios.iter().enumerate() {
+            scenario
+                .borrow()
+                .validate()
+                .map_err(|err| format!("Scenario #{}: {}", n + 1, err))?;
+        }
+        for (n, integrator) in self.integrators.iter().enumerate() {
+            integrator
+                .borrow()
+                .core
+                .validate()
+                .map_err(|err| format!("Integrator #{}: {}", n + 1, err))?;
+        }
         for (n, canvas) in self.canvases.iter().enumerate() {
             canvas
                 .borrow()
                 .check_references(&self)
                 .map_err(|err| format!("Canvas #{}: {}", n + 1, err))?;
         }
+        for (n, group) in self.step_size_groups.iter().enumerate() {
+            for member in &group.borrow().members {
+                member
+                    .step_size
+                    .check_reference(&self.step_sizes)
+                    .map_err(|err| format!("Step size group #{}: step size: {}", n + 1, err))?;
+            }
+        }
         Ok(self)
     }
 }
 
+/// Removal rules which apply to all entities of the `World`, in addition to those of
+/// [`entity_store::List::check_removal`].
+fn check_removal<T>(
+    list: &entity_store::List<T>,
+    referrers: &impl Referrer<T>,
+    idx: entity_store::Index<T>,
+    strategy: RemovalStrategy<T>,
+    entity_name: &str,
+) -> Result<(), String> {
+    if list.len() <= 1 {
+        Err(format!("The last {} cannot be deleted.", entity_name))
+    } else {
+        list.check_removal(idx, strategy, referrers)
+    }
+}
+
+impl Referrer<Scenario> for Vec<RefCell<Canvas>> {
+    fn count_references(&self, idx: entity_store::Index<Scenario>) -> usize {
+        self.iter()
+            .filter(|canvas| canvas.borrow().scenario_idx() == idx)
+            .count()
+    }
+}
+
+impl Referrer<Integrator> for Vec<RefCell<Canvas>> {
+    fn count_references(&self, idx: entity_store::Index<Integrator>) -> usize {
+        self.iter()
+            .map(|canvas| canvas.borrow().count_references(idx))
+            .sum()
+    }
+}
+
+impl Referrer<StepSize> for Vec<RefCell<Canvas>> {
+    fn count_references(&self, idx: entity_store::Index<StepSize>) -> usize {
+        self.iter()
+            .map(|canvas| canvas.borrow().count_references(idx))
+            .sum()
+    }
+}
+
+impl Canvases<'_> {
+    /// Inserts `canvas` at `position`, or removes the canvas at `position` if `None`.
+    fn replace(&mut self, position: usize, canvas: Option<Box<Canvas>>) {
+        let previous = match canvas {
+            Some(canvas) => {
+                self.canvases.insert(position, RefCell::new(*canvas));
+                None
+            }
+            None => Some(Box::new(self.canvases.remove(position).into_inner())),
+        };
+        self.journal.push(Command::Canvas(position, previous));
+    }
+
+    fn edit(&mut self, position: usize, command: CanvasCommand) {
+        let revert = command.apply(&mut self.canvases[position].borrow_mut());
+        self.journal.push(Command::EditCanvas(position, revert));
+    }
+
+    /// The positions of the integrations of the canvas at `position` which satisfy `predicate`
+    fn integrations_where(
+        &self,
+        position: usize,
+        predicate: impl Fn(&Integration) -> bool,
+    ) -> Vec<usize> {
+        self.canvases[position]
+            .borrow()
+            .integrations()
+            .enumerate()
+            .filter(|(_, integration)| predicate(&integration.borrow()))
+            .map(|(integration_idx, _)| integration_idx)
+            .collect()
+    }
+
+    /// Removes the integrations which satisfy `predicate` from all canvases.
+    fn remove_integrations_where(&mut self, predicate: impl Fn(&Integration) -> bool) {
+        for position in 0..self.canvases.len() {
+            for integration_idx in self
+                .integrations_where(position, &predicate)
+                .into_iter()
+                .rev()
+            {
+                self.edit(position, CanvasCommand::Integration(integration_idx, None));
+            }
+        }
+    }
+}
+
+impl<T> Referrer<T> for Canvases<'_>
+where
+    Vec<RefCell<Canvas>>: Referrer<T>,
+{
+    fn count_references(&self, idx: entity_store::Index<T>) -> usize {
+        self.canvases.count_references(idx)
+    }
+}
+
+impl ReferrerMut<Scenario> for Canvases<'_> {
+    fn drop_references(&mut self, idx: entity_store::Index<Scenario>) {
+        for position in (0..self.canvases.len()).rev() {
+            if self.canvases[position].borrow().scenario_idx() == idx {
+                self.replace(position, None);
+            }
+        }
+    }
+
+    fn reassign_references(
+        &mut self,
+        from: entity_store::Index<Scenario>,
+        to: entity_store::Index<Scenario>,
+    ) {
+        for position in 0..self.canvases.len() {
+            if self.canvases[position].borrow().scenario_idx() == from {
+                self.edit(position, CanvasCommand::SetScenario(to));
+            }
+        }
+    }
+}
+
+impl ReferrerMut<Integrator> for Canvases<'_> {
+    fn drop_references(&mut self, idx: entity_store::Index<Integrator>) {
+        self.remove_integrations_where(|integration| integration.integrator_idx() == idx);
+    }
+
+    fn reassign_references(
+        &mut self,
+        from: entity_store::Index<Integrator>,
+        to: entity_store::Index<Integrator>,
+    ) {
+        for position in 0..self.canvases.len() {
+            for integration_idx in self
+                .integrations_where(position, |integration| integration.integrator_idx() == from)
+            {
+                self.edit(position, CanvasCommand::SetIntegrator(integration_idx, to));
+            }
+        }
+    }
+}
+
+impl ReferrerMut<StepSize> for Canvases<'_> {
+    fn drop_references(&mut self, idx: entity_store::Index<StepSize>) {
+        self.remove_integrations_where(|integration| integration.step_size_idx() == idx);
+    }
+
+    fn reassign_references(
+        &mut self,
+        from: entity_store::Index<StepSize>,
+        to: entity_store::Index<StepSize>,
+    ) {
+        for position in 0..self.canvases.len() {
+            for integration_idx in
+                self.integrations_where(position, |integration| integration.step_size_idx() == from)
+            {
+                self.edit(position, CanvasCommand::SetStepSize(integration_idx, to));
+            }
+        }
+    }
+}
+
+impl Entity for Scenario {
+    fn list(world: &World) -> &entity_store::List<Self> {
+        &world.scenarios
+    }
+
+    fn split(world: &mut World) -> (&mut entity_store::List<Self>, Canvases<'_>) {
+        let canvases = Canvases {
+            canvases: &mut world.canvases,
+            journal: &mut world.journal,
+        };
+        (&mut world.scenarios, canvases)
+    }
+
+    fn command(idx: entity_store::Index<Self>, entity: Option<Self>) -> Command {
+        Command::Scenario(idx, entity)
+    }
+}
+
+impl Entity for Integrator {
+    fn list(world: &World) -> &entity_store::List<Self> {
+        &world.integrators
+    }
+
+    fn split(world: &mut World) -> (&mut entity_store::List<Self>, Canvases<'_>) {
+        let canvases = Canvases {
+            canvases: &mut world.canvases,
+            journal: &mut world.journal,
+        };
+        (&mut world.integrators, canvases)
+    }
+
+    fn command(idx: entity_store::Index<Self>, entity: Option<Self>) -> Command {
+        Command::Integrator(idx, entity)
+    }
+}
+
+impl Entity for StepSize {
+    fn list(world: &World) -> &entity_store::List<Self> {
+        &world.step_sizes
+    }
+
+    fn split(world: &mut World) -> (&mut entity_store::List<Self>, Canvases<'_>) {
+        let canvases = Canvases {
+            canvases: &mut world.canvases,
+            journal: &mut world.journal,
+        };
+        (&mut world.step_sizes, canvases)
+    }
+
+    fn command(idx: entity_store::Index<Self>, entity: Option<Self>) -> Command {
+        Command::StepSize(idx, entity)
+    }
+}
+
+impl Entity for StepSizeGroup {
+    fn list(world: &World) -> &entity_store::List<Self> {
+        &world.step_size_groups
+    }
+
+    fn split(world: &mut World) -> (&mut entity_store::List<Self>, Canvases<'_>) {
+        let canvases = Canvases {
+            canvases: &mut world.canvases,
+            journal: &mut world.journal,
+        };
+        (&mut world.step_size_groups, canvases)
+    }
+
+    fn command(idx: entity_store::Index<Self>, entity: Option<Self>) -> Command {
+        Command::StepSizeGroup(idx, entity)
+    }
+}
+
 impl ::std::ops::Index<entity_store::Index<Integrator>> for World {
     type Output = RefCell<Integrator>;
 
@@ -85,6 +743,14 @@ impl ::std::ops::Index<entity_store::Index<Scenario>> for World {
     }
 }
 
+impl ::std::ops::Index<entity_store::Index<StepSizeGroup>> for World {
+    type Output = RefCell<StepSizeGroup>;
+
+    fn index(&self, index: entity_store::Index<StepSizeGroup>) -> &Self::Output {
+        &self.step_size_groups[index]
+    }
+}
+
 impl ::std::ops::Index<entity_store::Index<StepSize>> for World {
     type Output = RefCell<StepSize>;
 
@@ -92,3 +758,94 @@ impl ::std::ops::Index<entity_store::Index<StepSize>> for World {
         &self.step_sizes[index]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::World;
+    use crate::{
+        core::integrators::custom::{Custom, Stage},
+        entities::{GroupMember, Integrator, StepSize, StepSizeGroup},
+        misc::{entity_store, UserLabel},
+        ui_import::{Color32, Stroke},
+    };
+
+    fn add_step_size(world: &mut World, duration: f32) -> entity_store::Index<StepSize> {
+        world.add_step_size(StepSize {
+            user_label: UserLabel(String::new()),
+            duration: duration.into(),
+            color: Color32::YELLOW,
+        })
+    }
+
+    fn group(members: &[(entity_store::Index<StepSize>, f32)]) -> StepSizeGroup {
+        StepSizeGroup {
+            user_label: UserLabel(String::new()),
+            master_dt: 0.4.into(),
+            members: members
+                .iter()
+                .map(|&(step_size, ratio)| GroupMember { step_size, ratio })
+                .collect(),
+        }
+    }
+
+    fn duration(world: &World, step_size_idx: entity_store::Index<StepSize>) -> f32 {
+        world[step_size_idx].borrow().duration.into()
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // the ratios are powers of two
+    fn rescales_groups() {
+        let mut world = World::default();
+        let (a, b) = (add_step_size(&mut world, 1.), add_step_size(&mut world, 1.));
+        let unlinked = add_step_size(&mut world, 1.);
+        let group_idx = world
+            .add_step_size_group(group(&[(a, 1.), (b, 0.5)]))
+            .unwrap();
+        assert_eq!((duration(&world, a), duration(&world, b)), (0.4, 0.2));
+
+        // via a member:
+        world.set_step_size_duration(b, 0.1.into());
+        assert_eq!((duration(&world, a), duration(&world, b)), (0.2, 0.1));
+        assert_eq!(f32::from(world[group_idx].borrow().master_dt), 0.2);
+        assert_eq!(duration(&world, unlinked), 1.);
+
+        // within the limits of all members:
+        world.set_step_size_group_dt(group_idx, 10.0.into());
+        assert_eq!((duration(&world, a), duration(&world, b)), (2., 1.));
+        world.set_step_size_duration(b, 2.0.into());
+        assert_eq!((duration(&world, a), duration(&world, b)), (2., 1.));
+        world.set_step_size_group_dt(group_idx, 0.001.into());
+        assert_eq!((duration(&world, a), duration(&world, b)), (0.02, 0.01));
+    }
+
+    #[test]
+    fn links_step_sizes_only_once() {
+        let mut world = World::default();
+        let (a, b) = (add_step_size(&mut world, 1.), add_step_size(&mut world, 1.));
+        assert!(world
+            .add_step_size_group(group(&[(a, 1.), (a, 0.5)]))
+            .is_err());
+        let group_idx = world.add_step_size_group(group(&[(a, 1.)])).unwrap();
+        assert!(world
+            .add_step_size_group(group(&[(b, 1.), (a, 0.5)]))
+            .is_err());
+        assert_eq!(world.step_size_groups().len(), 1);
+
+        world.remove_step_size_group(group_idx).unwrap();
+        assert!(world
+            .add_step_size_group(group(&[(b, 1.), (a, 0.5)]))
+            .is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_integrators() {
+        let mut custom = Custom::default();
+        custom.stages.push(Stage::Acceleration { position: 3 });
+        let mut world = World::default();
+        world.add_integrator(Integrator {
+            core: Box::new(custom),
+            stroke: Stroke::new(1_f32, Color32::WHITE),
+        });
+        assert!(world.check_references().is_err());
+    }
+}